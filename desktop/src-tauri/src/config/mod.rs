@@ -7,6 +7,15 @@ use tauri::{AppHandle, Manager};
 use std::fs;
 use std::io::{Read, Write};
 
+use crate::server::{
+    validate_server_host, ExternalServerPolicy, OrphanPolicy, PortRange, ResourceThresholds, ServerOptions,
+    WatchdogPolicy,
+};
+use crate::sessions::{
+    validate_launch_profiles, validate_trigger_rules, validate_workspaces, LaunchProfile, ReaperPolicy, RecordingOptions,
+    SearchIndexOptions, TriggerRule, Workspace,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub server_port: u16,
@@ -18,6 +27,60 @@ pub struct AppConfig {
     pub minimize_to_tray: bool,
     pub server_host: String,
     pub server_executable_path: Option<PathBuf>,
+    /// How long to let the server drain sessions after SIGTERM before killing it
+    #[serde(default = "default_server_shutdown_timeout_secs")]
+    pub server_shutdown_timeout_secs: u64,
+    /// Ports the server may move to when `server_port` is held by another program
+    #[serde(default)]
+    pub server_port_range: PortRange,
+    /// What to do with a TunnelForge server started outside the app; `None` means ask
+    #[serde(default)]
+    pub external_server_policy: Option<ExternalServerPolicy>,
+    /// Limits above which the server's CPU, memory, threads or open files raise a notification
+    #[serde(default)]
+    pub server_resource_thresholds: ResourceThresholds,
+    /// When the hung-server watchdog considers the server unresponsive and restarts it
+    #[serde(default)]
+    pub server_watchdog: WatchdogPolicy,
+    /// Adopt or stop a server left running by a desktop session that crashed or was killed
+    #[serde(default)]
+    pub orphan_server_policy: OrphanPolicy,
+    /// Auth mode, log level, extra env and args, and data directory for the server process
+    #[serde(default)]
+    pub server_options: ServerOptions,
+    /// Always-on recording, file rotation and the size cap for session recordings
+    #[serde(default)]
+    pub session_recording: RecordingOptions,
+    /// Memory caps and retention of the session output search index
+    #[serde(default)]
+    pub session_search: SearchIndexOptions,
+    /// Named presets for launching sessions
+    #[serde(default)]
+    pub launch_profiles: Vec<LaunchProfile>,
+    /// Saved sets of sessions and the session window layout
+    #[serde(default)]
+    pub workspaces: Vec<Workspace>,
+    /// Automatic cleanup of exited, idle and surplus sessions
+    #[serde(default)]
+    pub session_reaper: ReaperPolicy,
+    /// Actions fired by regex matches in session output
+    #[serde(default)]
+    pub trigger_rules: Vec<TriggerRule>,
+}
+
+fn default_server_shutdown_timeout_secs() -> u64 {
+    crate::server::DEFAULT_DRAIN_TIMEOUT_SECS
+}
+
+impl AppConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_server_host(&self.server_host)?;
+        self.server_options.validate()?;
+        self.session_reaper.validate()?;
+        validate_launch_profiles(&self.launch_profiles)?;
+        validate_trigger_rules(&self.trigger_rules)?;
+        validate_workspaces(&self.workspaces)
+    }
 }
 
 impl Default for AppConfig {
@@ -32,6 +95,19 @@ impl Default for AppConfig {
             minimize_to_tray: true,
             server_host: "127.0.0.1".to_string(),
             server_executable_path: None,
+            server_shutdown_timeout_secs: default_server_shutdown_timeout_secs(),
+            server_port_range: PortRange::default(),
+            external_server_policy: None,
+            server_resource_thresholds: ResourceThresholds::default(),
+            server_watchdog: WatchdogPolicy::default(),
+            orphan_server_policy: OrphanPolicy::default(),
+            server_options: ServerOptions::default(),
+            session_recording: RecordingOptions::default(),
+            session_search: SearchIndexOptions::default(),
+            launch_profiles: Vec::new(),
+            workspaces: Vec::new(),
+            session_reaper: ReaperPolicy::default(),
+            trigger_rules: Vec::new(),
         }
     }
 }
@@ -99,6 +175,18 @@ impl ConfigManager {
     }
 }
 
+/// Load, change, validate and save the config, then apply it to the running app
+pub fn update_and_apply<T>(app: &AppHandle, update: impl FnOnce(&mut AppConfig) -> Result<T, String>) -> Result<T, String> {
+    let config_manager = ConfigManager::new(app)?;
+    let mut config = config_manager.load_config()?;
+    let result = update(&mut config)?;
+
+    config.validate()?;
+    config_manager.save_config(&config)?;
+    crate::server::apply_server_settings(app, config);
+    Ok(result)
+}
+
 // Tauri commands for configuration management
 #[tauri::command]
 pub async fn get_config(app: AppHandle) -> Result<AppConfig, String> {
@@ -108,8 +196,12 @@ pub async fn get_config(app: AppHandle) -> Result<AppConfig, String> {
 
 #[tauri::command]
 pub async fn save_config(app: AppHandle, config: AppConfig) -> Result<(), String> {
+    config.validate()?;
+
     let config_manager = ConfigManager::new(&app)?;
-    config_manager.save_config(&config)
+    config_manager.save_config(&config)?;
+    crate::server::apply_server_settings(&app, config);
+    Ok(())
 }
 
 #[tauri::command]
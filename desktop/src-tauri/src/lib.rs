@@ -1,6 +1,9 @@
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
-use std::process::Child;
 use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
 
 // Platform integration trait
 pub trait PlatformIntegration {
@@ -19,46 +22,75 @@ mod linux_platform;
 mod security;
 
 // Application state structure
+// The server process itself is owned by the managed `server::ServerManager`
 #[derive(Debug)]
 pub struct AppState {
-    pub server_process: Arc<Mutex<Option<Child>>>,
-    pub server_port: u16,
     pub config: Arc<Mutex<config::AppConfig>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
     pub timestamp: String,
     pub level: String,
     pub message: String,
+    /// Where the entry came from: `APP_LOG_SOURCE` or `SERVER_LOG_SOURCE`
+    pub source: String,
 }
 
 // Initialize the application with proper state management
 pub fn init_app_state() -> AppState {
     AppState {
-        server_process: Arc::new(Mutex::new(None)),
-        server_port: 4021, // Connect to Go server on port 4021
         config: Arc::new(Mutex::new(config::AppConfig::default())),
     }
 }
 
 // Central logging system
+/// Source tag for the desktop app's own log entries
+pub const APP_LOG_SOURCE: &str = "app";
+/// Source tag for captured Go server output
+pub const SERVER_LOG_SOURCE: &str = "server";
+
 static LOG_BUFFER: std::sync::Mutex<Vec<LogEntry>> = std::sync::Mutex::new(Vec::new());
 
+// Go server stdout/stderr, kept apart so a chatty server cannot push out the app's own entries
+const SERVER_LOG_CAPACITY: usize = 2000;
+static SERVER_LOG_BUFFER: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+// Live feed of new log entries for anything that wants to tail them
+static LOG_EVENTS: Lazy<broadcast::Sender<LogEntry>> = Lazy::new(|| broadcast::channel(256).0);
+
 pub fn add_log_entry(level: &str, message: &str) {
+    add_log_entry_with_source(APP_LOG_SOURCE, level, message);
+}
+
+pub fn add_log_entry_with_source(source: &str, level: &str, message: &str) {
     let entry = LogEntry {
         timestamp: Utc::now().format("%H:%M:%S").to_string(),
         level: level.to_string(),
         message: message.to_string(),
+        source: source.to_string(),
     };
 
-    if let Ok(mut buffer) = LOG_BUFFER.lock() {
-        buffer.push(entry);
+    if source == SERVER_LOG_SOURCE {
+        if let Ok(mut buffer) = SERVER_LOG_BUFFER.lock() {
+            if buffer.len() == SERVER_LOG_CAPACITY {
+                buffer.pop_front();
+            }
+            buffer.push_back(entry.clone());
+        }
+    } else if let Ok(mut buffer) = LOG_BUFFER.lock() {
+        buffer.push(entry.clone());
         // Keep only the last 200 entries
         if buffer.len() > 200 {
             buffer.remove(0);
         }
     }
+
+    let _ = LOG_EVENTS.send(entry);
+}
+
+pub fn subscribe_log_entries() -> broadcast::Receiver<LogEntry> {
+    LOG_EVENTS.subscribe()
 }
 
 // Backend log and version access functions (non-Tauri commands for now)
@@ -71,6 +103,7 @@ pub async fn get_backend_logs_internal(limit: Option<usize>) -> Result<Vec<LogEn
             timestamp: Utc::now().format("%H:%M:%S").to_string(),
             level: "error".to_string(),
             message: "Failed to access log buffer".to_string(),
+            source: APP_LOG_SOURCE.to_string(),
         }]
     };
 
@@ -83,6 +116,12 @@ pub async fn get_backend_logs_internal(limit: Option<usize>) -> Result<Vec<LogEn
     Ok(logs)
 }
 
+pub async fn get_server_logs_internal(limit: Option<usize>) -> Result<Vec<LogEntry>, String> {
+    let buffer = SERVER_LOG_BUFFER.lock().map_err(|_| "Failed to access server log buffer".to_string())?;
+    let start = limit.map_or(0, |limit| buffer.len().saturating_sub(limit));
+    Ok(buffer.iter().skip(start).cloned().collect())
+}
+
 pub async fn get_app_version_internal() -> Result<String, String> {
     Ok(env!("CARGO_PKG_VERSION").to_string())
 }
@@ -119,6 +158,21 @@ mod tests {
         assert_eq!(env::var("SENTRY_ENVIRONMENT").unwrap(), "test");
     }
 
+    #[tokio::test]
+    async fn test_server_logs_have_their_own_buffer() {
+        for i in 0..SERVER_LOG_CAPACITY + 5 {
+            add_log_entry_with_source(SERVER_LOG_SOURCE, "info", &format!("server line {}", i));
+        }
+
+        let server_logs = get_server_logs_internal(None).await.unwrap();
+        assert_eq!(server_logs.len(), SERVER_LOG_CAPACITY);
+        assert_eq!(server_logs.last().unwrap().message, format!("server line {}", SERVER_LOG_CAPACITY + 4));
+        assert_eq!(get_server_logs_internal(Some(3)).await.unwrap().len(), 3);
+
+        let app_logs = get_backend_logs_internal(None).await.unwrap();
+        assert!(app_logs.iter().all(|entry| entry.source != SERVER_LOG_SOURCE));
+    }
+
     #[test]
     fn test_secure_operations_logging() {
         // Test that logging setup works
@@ -241,6 +241,48 @@ async fn get_startup_metrics() -> Result<StartupMetrics, String> {
 // Global startup timer
 static STARTUP_TIMER: OnceCell<Arc<StartupTimer>> = OnceCell::new(");
 
+/// Load the server settings and start the tray, event forwarding and background services.
+/// Shared by the parallel and sequential setup paths.
+fn start_services(app: &tauri::App) {
+    let app_handle = app.handle().clone();
+
+    if let Err(e) = server::load_server_settings(&app_handle) {
+        log::warn!("Using default server settings: {}", e);
+    }
+    if let Err(e) = ui::setup_tray(&app_handle) {
+        log::warn!("Running without a tray icon: {}", e);
+    }
+
+    // Stream captured Go server output and lifecycle changes to the frontend
+    server::forward_server_logs(app_handle.clone());
+    server::forward_lifecycle_events(app_handle.clone(), app.state::<server::ServerManager>().subscribe_lifecycle());
+    server::forward_build_events(app_handle.clone(), app.state::<server::ServerManager>().builder().subscribe());
+    server::forward_resource_alerts(app_handle.clone(), app.state::<server::ServerManager>().resources().subscribe_alerts());
+    sessions::forward_terminal_events(app_handle.clone(), app.state::<sessions::WebSocketManager>().subscribe());
+    app.state::<sessions::SessionScreens>().start(app.state::<sessions::SessionOutputHub>().subscribe());
+    app.state::<sessions::SessionRecorder>().start(app.state::<sessions::SessionOutputHub>().subscribe());
+    app.state::<sessions::SessionSearch>().start(app.state::<sessions::SessionOutputHub>().subscribe());
+    app.state::<sessions::ProfileLauncher>().start(app_handle.clone());
+    app.state::<sessions::SessionReaper>().start(app_handle.clone());
+    app.state::<sessions::SessionTriggers>().start(app_handle.clone(), app.state::<sessions::SessionOutputHub>().subscribe());
+    if let Err(e) = app.state::<sessions::SessionOutputHub>().start() {
+        log::warn!("{}", e);
+    }
+    app.state::<server::ServerManager>().start_resource_sampling();
+    app.state::<server::ServerManager>().start_watchdog();
+    let server_manager = app.state::<server::ServerManager>().inner().clone();
+    if let Err(e) = app.state::<power::PowerManager>().start_monitoring(server_manager) {
+        log::warn!("{}", e);
+    }
+
+    // Follow the server's event stream for session changes and notifications
+    app.manage(notifications::NotificationService::new(app_handle.clone()));
+    let notification_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        notification_handle.state::<notifications::NotificationService>().start().await;
+    });
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize Sentry for error tracking
@@ -264,8 +306,37 @@ pub fn run() {
     let startup_timer = Arc::new(StartupTimer::new()");
     let _ = STARTUP_TIMER.set(startup_timer.clone()");
 
+    // The one server manager every command, the tray and power handling share
+    let server_manager = server::ServerManager::new(4021, "127.0.0.1".to_string(), startup_timer.clone());
+    let session_manager = sessions::SessionManager::new(server_manager.clone());
+    let session_monitor = sessions::SessionMonitor::new(session_manager.clone());
+    let session_events = sessions::SessionEventStream::new(session_manager.clone(), session_monitor.clone());
+    let terminals = sessions::WebSocketManager::new(session_manager.clone());
+    let session_output = sessions::SessionOutputHub::new(session_manager.clone());
+    let session_screens = sessions::SessionScreens::new(session_manager.clone());
+    let session_recorder = sessions::SessionRecorder::new(session_manager.clone(), session_output.clone());
+    let recording_player = sessions::RecordingPlayer::new(session_recorder.clone(), session_screens.clone(), terminals.clone());
+    let session_search = sessions::SessionSearch::new(session_manager.clone());
+    let profile_launcher = sessions::ProfileLauncher::new(session_manager.clone(), session_recorder.clone());
+    let session_reaper = sessions::SessionReaper::new(session_manager.clone(), session_monitor.clone(), profile_launcher.clone());
+    let session_triggers = sessions::SessionTriggers::new(session_manager.clone(), terminals.clone(), profile_launcher.clone());
+
     let app = tauri::Builder::default()
         .manage(init_app_state())
+        .manage(server_manager)
+        .manage(session_manager)
+        .manage(session_monitor)
+        .manage(session_events)
+        .manage(terminals)
+        .manage(session_output)
+        .manage(session_screens)
+        .manage(session_recorder)
+        .manage(recording_player)
+        .manage(session_search)
+        .manage(profile_launcher)
+        .manage(session_reaper)
+        .manage(session_triggers)
+        .manage(power::PowerManager::new())
         .plugin(tauri_plugin_log::Builder::new()
             .targets([
                 Target::new(TargetKind::Stdout),
@@ -276,6 +347,7 @@ pub fn run() {
             .build())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .invoke_handler(tauri::generate_handler![
             // Configuration commands
             config::get_config,
@@ -308,12 +380,55 @@ pub fn run() {
             server::get_server_status,
             server::get_server_url,
             server::restart_server,
+            server::get_server_logs,
+            server::get_external_server,
+            server::set_external_server_policy,
+            server::resolve_server_binary,
+            server::build_server,
+            server::cancel_server_build,
+            server::get_server_resource_history,
+            server::get_server_restart_required,
 
             // Session management commands
             sessions::get_sessions,
             sessions::create_session,
             sessions::delete_session,
             sessions::get_session_details,
+            sessions::start_session_monitoring,
+            sessions::stop_session_monitoring,
+            sessions::is_session_monitoring_active,
+            sessions::connect_websocket,
+            sessions::disconnect_websocket,
+            sessions::send_terminal_input,
+            sessions::resize_terminal,
+            sessions::get_session_preview,
+            sessions::get_session_thumbnail,
+            sessions::start_session_recording,
+            sessions::stop_session_recording,
+            sessions::is_session_recording,
+            sessions::list_session_recordings,
+            sessions::play_recording,
+            sessions::set_playback_speed,
+            sessions::stop_playback,
+            sessions::search_sessions,
+            sessions::get_search_index,
+            sessions::list_launch_profiles,
+            sessions::save_launch_profile,
+            sessions::delete_launch_profile,
+            sessions::launch_profile,
+            sessions::list_workspaces,
+            sessions::save_workspace,
+            sessions::delete_workspace,
+            sessions::restore_workspace,
+            sessions::export_workspace,
+            sessions::import_workspace,
+            sessions::run_session_reaper,
+            sessions::kill_idle_sessions,
+            sessions::delete_exited_sessions,
+            sessions::list_trigger_rules,
+            sessions::save_trigger_rule,
+            sessions::delete_trigger_rule,
+            sessions::clear_trigger_badge,
 
             // UI commands
             ui::show_main_window,
@@ -373,6 +488,8 @@ pub fn run() {
              app.manage(settings_window");
              app.manage(session_window");
 
+             start_services(app);
+
              startup_timer.record_ui_init(");
              setup_app(app).map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
          }");
@@ -390,6 +507,7 @@ pub fn run() {
             app.manage(ui::MainWindow::new()");
             app.manage(ui::SettingsWindow::new()");
              app.manage(ui::SessionWindow::new()");
+             start_services(app);
 
              startup_timer.record_ui_init(");
              setup_app(app).map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
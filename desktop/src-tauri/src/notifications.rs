@@ -5,6 +5,13 @@ use tauri::{AppHandle, Manager};
 use serde::{Serialize, Deserialize};
 use std::sync::Arc;
 use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+use crate::server::{ResourceAlert, ResourceMetric, ServerLifecycleEvent};
+use crate::sessions::{ServerEvent, SessionEventStream, SessionScreens, SessionStreamEvent};
+
+/// Screen lines shown in bell and Claude notifications
+const NOTIFICATION_SCREEN_LINES: usize = 3;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct NotificationPreferences {
@@ -106,16 +113,29 @@ impl NotificationService {
         true
     }
 
+    /// Start following the server's event stream, which also replaces session polling while connected
     pub async fn start(&self) {
-        // TODO: Start SSE connection for real-time notifications
-        self.set_sse_connected(true");
-        println!("Notification service started");
+        let Some(stream) = self.app_handle.try_state::<SessionEventStream>() else {
+            log::warn!("Session event stream is not available; notifications stay disabled");
+            return;
+        };
+
+        let receiver = stream.subscribe();
+        if let Err(e) = stream.start() {
+            log::debug!("{}", e);
+            return;
+        }
+
+        forward_stream_events(self.app_handle.clone(), Arc::clone(&self.sse_connected), receiver);
+        log::info!("Notification service started");
     }
 
     pub fn stop(&self) {
-        // TODO: Stop SSE connection
-        self.set_sse_connected(false");
-        println!("Notification service stopped");
+        if let Some(stream) = self.app_handle.try_state::<SessionEventStream>() {
+            stream.stop();
+        }
+        self.set_sse_connected(false);
+        log::info!("Notification service stopped");
     }
 
     pub async fn show_notification(
@@ -207,7 +227,7 @@ impl NotificationService {
         ).await;
     }
 
-    pub async fn show_bell_notification(&self) {
+    pub async fn show_bell_notification(&self, screen: Option<&str>) {
         let preferences = self.get_preferences(");
 
         if !preferences.bell {
@@ -217,12 +237,12 @@ impl NotificationService {
         self.show_notification(
             "Terminal Bell",
             "Terminal bell activated",
-            "🔔 Terminal bell (^G) received",
+            screen.unwrap_or("🔔 Terminal bell (^G) received"),
             None
         ).await;
     }
 
-    pub async fn show_claude_turn_notification(&self) {
+    pub async fn show_claude_turn_notification(&self, screen: Option<&str>) {
         let preferences = self.get_preferences(");
 
         if !preferences.claude_turn {
@@ -232,11 +252,111 @@ impl NotificationService {
         self.show_notification(
             "Claude AI Ready",
             "Claude has finished responding",
-            "Waiting for your input...",
+            screen.unwrap_or("Waiting for your input..."),
             None
         ).await;
     }
 
+    pub async fn show_server_lifecycle_notification(&self, event: &ServerLifecycleEvent) {
+        match event {
+            ServerLifecycleEvent::Crashed { exit, restart_in_ms: Some(delay), .. } => {
+                self.show_notification(
+                    "Server Crashed",
+                    &format!("The TunnelForge server exited unexpectedly ({:?})", exit),
+                    &format!("Restarting in {}s", delay / 1000),
+                    None
+                ).await;
+            }
+            ServerLifecycleEvent::StartFailed { error, .. } => {
+                self.show_notification(
+                    "Server Failed to Start",
+                    "The TunnelForge server did not become ready",
+                    error,
+                    None
+                ).await;
+            }
+            ServerLifecycleEvent::PortReassigned { from, to, reason } => {
+                self.show_notification(
+                    "Server Port Changed",
+                    &format!("Port {} is used by another program, TunnelForge now runs on port {}", from, to),
+                    reason,
+                    None
+                ).await;
+            }
+            ServerLifecycleEvent::ExternalServerDetected { port, policy: None, .. } => {
+                self.show_notification(
+                    "TunnelForge Server Already Running",
+                    &format!("A TunnelForge server started outside the app is using port {}", port),
+                    "Choose whether to attach to it, take it over or leave it alone.",
+                    None
+                ).await;
+            }
+            ServerLifecycleEvent::RestartRequired { changed } => {
+                self.show_notification(
+                    "Server Restart Needed",
+                    "Some settings only apply after the TunnelForge server restarts",
+                    &format!("Changed: {}. Use Restart Server in the tray menu.", changed.join(", ")),
+                    None
+                ).await;
+            }
+            ServerLifecycleEvent::WatchdogRestart { unresponsive_ms, goroutine_dump, .. } => {
+                self.show_notification(
+                    "Server Not Responding",
+                    &format!("The TunnelForge server stopped answering for {}s and was restarted", unresponsive_ms / 1000),
+                    if *goroutine_dump { "A goroutine dump was written to the server log." } else { "" },
+                    None
+                ).await;
+            }
+            ServerLifecycleEvent::GivingUp { crashes, window_secs } => {
+                self.show_notification(
+                    "Server Keeps Crashing",
+                    &format!("The TunnelForge server crashed {} times in {}s", crashes, window_secs),
+                    "Automatic restarts are paused. Start the server manually from the tray.",
+                    None
+                ).await;
+            }
+            _ => {}
+        }
+    }
+
+    pub async fn show_resource_alert_notification(&self, alert: &ResourceAlert) {
+        let (what, value, limit) = match alert.metric {
+            ResourceMetric::Cpu => ("CPU usage", format!("{:.0}%", alert.value), format!("{:.0}%", alert.threshold)),
+            ResourceMetric::Memory => ("Memory usage", format!("{:.0} MB", alert.value), format!("{:.0} MB", alert.threshold)),
+            ResourceMetric::FileDescriptors => ("Open files", format!("{:.0}", alert.value), format!("{:.0}", alert.threshold)),
+            ResourceMetric::Threads => ("Thread count", format!("{:.0}", alert.value), format!("{:.0}", alert.threshold)),
+        };
+
+        self.show_notification(
+            "Server Resource Warning",
+            &format!("{} of the TunnelForge server is {} (limit {})", what, value, limit),
+            "Long-running sessions may be leaking resources. Check the server metrics.",
+            None
+        ).await;
+    }
+
+    pub async fn show_server_event_notification(&self, event: &ServerEvent) {
+        let session = event.session_name.as_deref().or(event.session_id.as_deref()).unwrap_or("unknown");
+        let command = event.command.as_deref().unwrap_or_default();
+        // What the session shows right now, when its output is being followed
+        let screen = event.session_id.as_deref().and_then(|session_id| {
+            self.app_handle.try_state::<SessionScreens>()?.summary(session_id, NOTIFICATION_SCREEN_LINES)
+        });
+
+        match event.event_type.as_str() {
+            "session-start" => self.show_session_start_notification(session).await,
+            "session-exit" => self.show_session_exit_notification(session, event.exit_code.unwrap_or(0)).await,
+            "command-finished" => self.show_command_completion_notification(command, event.duration.unwrap_or(0)).await,
+            "command-error" => {
+                let error = event.message.clone().unwrap_or_else(|| format!("Exit code {}", event.exit_code.unwrap_or(-1)));
+                self.show_command_error_notification(command, &error).await
+            }
+            "bell" => self.show_bell_notification(screen.as_deref()).await,
+            "claude-turn" => self.show_claude_turn_notification(screen.as_deref()).await,
+            _ => {}
+        }
+    }
+
     pub async fn send_server_test_notification(&self) {
         self.show_notification(
             "Test Notification",
@@ -252,6 +372,46 @@ impl NotificationService {
     }
 }
 
+/// Track the stream's connection state and turn server events into notifications until the stream stops
+fn forward_stream_events(
+    app: AppHandle,
+    sse_connected: Arc<Mutex<bool>>,
+    mut receiver: broadcast::Receiver<SessionStreamEvent>
+) {
+    let set_connected = move |connected: bool| {
+        if let Ok(mut sse_connected) = sse_connected.lock() {
+            *sse_connected = connected;
+        }
+    };
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Dropped {} server events", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            match event {
+                SessionStreamEvent::Connected => set_connected(true),
+                SessionStreamEvent::Disconnected { .. } => set_connected(false),
+                SessionStreamEvent::Stopped => {
+                    set_connected(false);
+                    break;
+                }
+                SessionStreamEvent::Server(event) => {
+                    if let Some(notification_service) = app.try_state::<NotificationService>() {
+                        notification_service.show_server_event_notification(&event).await;
+                    }
+                }
+            }
+        }
+    });
+}
+
 // Tauri commands for notification management
 #[tauri::command]
 pub async fn show_notification(
@@ -307,10 +467,10 @@ pub async fn show_server_notification(
             }
         }
         "bell" => {
-            notification_service.show_bell_notification().await;
+            notification_service.show_bell_notification(None).await;
         }
         "claude_turn" => {
-            notification_service.show_claude_turn_notification().await;
+            notification_service.show_claude_turn_notification(None).await;
         }
         _ => {}
     }
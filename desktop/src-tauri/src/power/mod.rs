@@ -3,9 +3,20 @@
 
 use log::info;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::State;
+use tokio::time::sleep;
+
+use crate::server::{probe_server, HealthChecker, ServerManager, ServerMode};
 
 // use crate::add_log_entry; // Will be implemented later
 
+/// How often the monitor checks the clocks for a suspend
+const POWER_POLL_INTERVAL_SECS: u64 = 5;
+
+/// Clock drift beyond the poll interval that counts as the machine having slept
+const SUSPEND_THRESHOLD_SECS: u64 = 15;
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PowerSettings {
     pub pause_on_sleep: bool,
@@ -23,9 +34,61 @@ impl Default for PowerSettings {
     }
 }
 
+/// What `handle_wake_event` did about the server
+#[derive(Debug, Clone, PartialEq)]
+pub enum WakeOutcome {
+    /// Resuming is disabled or the server was not running before sleep
+    Skipped,
+    Healthy,
+    /// The managed server was still up but failed its health check, so it was restarted
+    Restarted,
+    /// The server did not survive the sleep and was launched again
+    Relaunched,
+    Failed(String),
+}
+
+/// Notices a suspend after the fact: the monotonic clock stops while the machine sleeps but the
+/// wall clock does not, and where the monotonic clock keeps counting the poll simply fires late.
+/// This needs no platform notification API, at the cost of only learning about sleep on wake.
+pub struct SuspendDetector {
+    interval: Duration,
+    last_wall: SystemTime,
+    last_mono: Instant,
+}
+
+impl SuspendDetector {
+    pub fn new(interval: Duration, wall: SystemTime, mono: Instant) -> Self {
+        Self { interval, last_wall: wall, last_mono: mono }
+    }
+
+    /// Record a poll and return how long the machine was asleep since the previous one, if at all
+    pub fn observe(&mut self, wall: SystemTime, mono: Instant) -> Option<Duration> {
+        let mono_elapsed = mono.saturating_duration_since(self.last_mono);
+        // A wall clock set backwards is a clock change, not a wake
+        let wall_elapsed = wall.duration_since(self.last_wall).unwrap_or(mono_elapsed);
+        self.last_wall = wall;
+        self.last_mono = mono;
+
+        let threshold = Duration::from_secs(SUSPEND_THRESHOLD_SECS);
+        let unseen = wall_elapsed.saturating_sub(mono_elapsed);
+        let late = mono_elapsed.saturating_sub(self.interval);
+
+        if unseen >= threshold {
+            Some(unseen)
+        } else if late >= threshold {
+            Some(late)
+        } else {
+            None
+        }
+    }
+}
+
+/// A single instance is registered with `app.manage`; `start_monitoring` runs for the app's life
+#[derive(Clone)]
 pub struct PowerManager {
     settings: Arc<Mutex<PowerSettings>>,
     monitoring: Arc<Mutex<bool>>,
+    server_running_before_sleep: Arc<Mutex<bool>>,
 }
 
 impl PowerManager {
@@ -33,140 +96,246 @@ impl PowerManager {
         Self {
             settings: Arc::new(Mutex::new(PowerSettings::default())),
             monitoring: Arc::new(Mutex::new(false)),
+            server_running_before_sleep: Arc::new(Mutex::new(false)),
         }
     }
 
-    pub fn start_monitoring(&self) -> Result<(), String> {
-        let mut monitoring = self.monitoring.lock().unwrap(");
+    /// Watch for suspend/resume and run the sleep and wake handlers against `server_manager`
+    pub fn start_monitoring(&self, server_manager: ServerManager) -> Result<(), String> {
+        let mut monitoring = self.monitoring.lock().unwrap();
         if *monitoring {
-            return Err("Power monitoring is already active".to_string()");
+            return Err("Power monitoring is already active".to_string());
         }
 
         *monitoring = true;
         log::info!("Starting power management monitoring");
 
-        #[cfg(target_os = "macos")]
-        {
-            self.start_macos_monitoring()
-        }
+        let manager = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let interval = Duration::from_secs(POWER_POLL_INTERVAL_SECS);
+            let mut detector = SuspendDetector::new(interval, SystemTime::now(), Instant::now());
 
-        #[cfg(target_os = "windows")]
-        {
-            self.start_windows_monitoring()
-        }
+            while manager.is_monitoring() {
+                sleep(interval).await;
 
-        #[cfg(target_os = "linux")]
-        {
-            self.start_linux_monitoring()
-        }
+                match detector.observe(SystemTime::now(), Instant::now()) {
+                    Some(asleep) => {
+                        info!("System woke up after about {}s asleep", asleep.as_secs());
+                        let outcome = manager.handle_wake_event(&server_manager).await;
+                        log::debug!("Wake handling: {:?}", outcome);
+                    }
+                    None => manager.handle_sleep_event(&server_manager).await,
+                }
+            }
+
+            log::debug!("Power monitoring stopped");
+        });
+
+        Ok(())
     }
 
     pub fn stop_monitoring(&self) {
-        let mut monitoring = self.monitoring.lock().unwrap(");
+        let mut monitoring = self.monitoring.lock().unwrap();
         *monitoring = false;
         log::info!("Stopping power management monitoring");
     }
 
     pub fn is_monitoring(&self) -> bool {
-        let monitoring = self.monitoring.lock().unwrap(");
+        let monitoring = self.monitoring.lock().unwrap();
         *monitoring
     }
 
     pub fn update_settings(&self, settings: PowerSettings) {
-        let mut current_settings = self.settings.lock().unwrap(");
+        let mut current_settings = self.settings.lock().unwrap();
         *current_settings = settings;
         log::info!("Power management settings updated");
     }
 
     pub fn get_settings(&self) -> PowerSettings {
-        let settings = self.settings.lock().unwrap(");
+        let settings = self.settings.lock().unwrap();
         settings.clone()
     }
 
-    #[cfg(target_os = "macos")]
-    fn start_macos_monitoring(&self) -> Result<(), String> {
-        use crate::macos_platform;
-
-        log::debug!("""Starting macOS power monitoring");
-
-        // In a real implementation, this would use IOKit to monitor power events
-        // For now, we'll log that monitoring has started
-        info!("macOS power monitoring started");
-        Ok(())
-    }
-
-    #[cfg(target_os = "windows")]
-    fn start_windows_monitoring(&self) -> Result<(), String> {
-        use crate::windows_platform;
-
-        log::debug!("""Starting Windows power monitoring");
-
-        // In a real implementation, this would use Windows API to monitor power events
-        info!("Windows power monitoring started");
-        Ok(())
+    /// Remember whether the server was up so it can be brought back after wake.
+    /// Suspend is only detected on resume, so the monitor calls this on every poll while awake.
+    /// Sessions live in the server, so it is left running rather than stopped.
+    /// Only our own server is tracked, which costs nothing while there is no process.
+    pub async fn handle_sleep_event(&self, server_manager: &ServerManager) {
+        let settings = self.settings.lock().unwrap().clone();
+        if settings.pause_on_sleep {
+            let running = server_manager.is_running().await;
+            *self.server_running_before_sleep.lock().unwrap() = running;
+        }
     }
 
-    #[cfg(target_os = "linux")]
-    fn start_linux_monitoring(&self) -> Result<(), String> {
-        use crate::linux_platform;
-
-        log::debug!("Starting Linux power monitoring");
+    /// Bring the server back if it was running before sleep: relaunch it if it did not survive,
+    /// and restart a managed server that is still there but no longer answers health checks
+    pub async fn handle_wake_event(&self, server_manager: &ServerManager) -> WakeOutcome {
+        let settings = self.settings.lock().unwrap().clone();
+        let was_running = std::mem::take(&mut *self.server_running_before_sleep.lock().unwrap());
 
-        // In a real implementation, this would use D-Bus to monitor power events
-        info!("Linux power monitoring started");
-        Ok(())
-    }
+        if !settings.resume_on_wake || !was_running {
+            return WakeOutcome::Skipped;
+        }
 
-    fn handle_sleep_event(&self) {
-        let settings = self.settings.lock().unwrap(");
-        if settings.pause_on_sleep {
-        log::info!("System is going to sleep - pausing server operations");
+        log::info!("System woke up - resuming server operations");
+        let status = server_manager.status().await;
+        if status.running {
+            // Servers we did not start are not ours to restart
+            if status.mode != ServerMode::Managed {
+                return WakeOutcome::Healthy;
+            }
+
+            let health_checker = HealthChecker::new(status.port, server_manager.get_host());
+            if probe_server(&health_checker).await.ok {
+                return WakeOutcome::Healthy;
+            }
+
+            log::warn!("Server (PID: {:?}) is unhealthy after wake, restarting it", status.pid);
+            if let Err(e) = server_manager.stop().await {
+                log::error!("Failed to stop unhealthy server after wake: {}", e);
+                return WakeOutcome::Failed(e);
+            }
+            return match server_manager.launch().await {
+                Ok(state) => {
+                    info!("Server restarted after wake: {:?}", state);
+                    WakeOutcome::Restarted
+                }
+                Err(e) => {
+                    log::error!("Failed to restart server after wake: {}", e);
+                    WakeOutcome::Failed(e)
+                }
+            };
+        }
 
-            // In a real implementation, this would pause server operations
-            // For now, we just log the event
+        match server_manager.launch().await {
+            Ok(state) => {
+                info!("Server relaunched after wake: {:?}", state);
+                WakeOutcome::Relaunched
+            }
+            Err(e) => {
+                log::error!("Failed to relaunch server after wake: {}", e);
+                WakeOutcome::Failed(e)
+            }
         }
     }
+}
 
-    fn handle_wake_event(&self) {
-        let settings = self.settings.lock().unwrap(");
-        if settings.resume_on_wake {
-            log::info!("System woke up - resuming server operations");
-
-            // In a real implementation, this would resume server operations
-            // For now, we just log the event
-        }
+impl Default for PowerManager {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 // Tauri commands for power management
 #[tauri::command]
-pub async fn start_power_monitoring() -> Result<(), String> {
-    let power_manager = PowerManager::new(");
-    power_manager.start_monitoring()
+pub async fn start_power_monitoring(
+    power_manager: State<'_, PowerManager>,
+    server_manager: State<'_, ServerManager>,
+) -> Result<(), String> {
+    power_manager.start_monitoring(server_manager.inner().clone())
 }
 
 #[tauri::command]
-pub async fn stop_power_monitoring() -> Result<(), String> {
-    let power_manager = PowerManager::new(");
-    power_manager.stop_monitoring(");
+pub async fn stop_power_monitoring(power_manager: State<'_, PowerManager>) -> Result<(), String> {
+    power_manager.stop_monitoring();
     Ok(())
 }
 
 #[tauri::command]
-pub async fn is_power_monitoring_active() -> Result<bool, String> {
-    let power_manager = PowerManager::new(");
+pub async fn is_power_monitoring_active(power_manager: State<'_, PowerManager>) -> Result<bool, String> {
     Ok(power_manager.is_monitoring())
 }
 
 #[tauri::command]
-pub async fn get_power_settings() -> Result<PowerSettings, String> {
-    let power_manager = PowerManager::new(");
+pub async fn get_power_settings(power_manager: State<'_, PowerManager>) -> Result<PowerSettings, String> {
     Ok(power_manager.get_settings())
 }
 
 #[tauri::command]
-pub async fn update_power_settings(settings: PowerSettings) -> Result<(), String> {
-    let power_manager = PowerManager::new(");
-    power_manager.update_settings(settings");
+pub async fn update_power_settings(power_manager: State<'_, PowerManager>, settings: PowerSettings) -> Result<(), String> {
+    power_manager.update_settings(settings);
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::StartupTimer;
+    use crate::server::PortRange;
+    use std::io::{Read, Write};
+
+    #[test]
+    fn test_detects_suspend_from_clock_drift() {
+        let interval = Duration::from_secs(POWER_POLL_INTERVAL_SECS);
+        let wall = SystemTime::now();
+        let mono = Instant::now();
+        let mut detector = SuspendDetector::new(interval, wall, mono);
+
+        // An ordinary poll
+        assert_eq!(detector.observe(wall + interval, mono + interval), None);
+
+        // Monotonic clock paused for the sleep while the wall clock moved on
+        let slept = detector.observe(wall + interval * 2 + Duration::from_secs(600), mono + interval * 2);
+        assert_eq!(slept, Some(Duration::from_secs(600)));
+
+        // Monotonic clock that keeps counting through sleep: the poll fires late instead
+        let wall = wall + interval * 2 + Duration::from_secs(600);
+        let late = detector.observe(wall + Duration::from_secs(300), mono + interval * 2 + Duration::from_secs(300));
+        assert_eq!(late, Some(Duration::from_secs(300) - interval));
+    }
+
+    /// A listener that answers every request as something other than TunnelForge
+    fn serve_foreign() -> u16 {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = [0u8; 1024];
+                if stream.read(&mut request).unwrap_or(0) == 0 {
+                    continue;
+                }
+                let body = r#"{"status":"UP"}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        port
+    }
+
+    #[tokio::test]
+    async fn test_sleep_without_server_process_records_not_running() {
+        let server_manager = ServerManager::new(1, "127.0.0.1".to_string(), Arc::new(StartupTimer::new()));
+        let power_manager = PowerManager::new();
+
+        *power_manager.server_running_before_sleep.lock().unwrap() = true;
+        power_manager.handle_sleep_event(&server_manager).await;
+        assert!(!*power_manager.server_running_before_sleep.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_wake_relaunch_of_server_that_was_running_reports_failure() {
+        // The server is gone after "sleep" and its port now belongs to another program with nowhere
+        // else to go, so the relaunch fails before any binary is needed
+        let port = serve_foreign();
+        let server_manager = ServerManager::new(port, "127.0.0.1".to_string(), Arc::new(StartupTimer::new()));
+        server_manager.set_port_range(PortRange { start: port, end: port });
+        let power_manager = PowerManager::new();
+
+        *power_manager.server_running_before_sleep.lock().unwrap() = true;
+        match power_manager.handle_wake_event(&server_manager).await {
+            WakeOutcome::Failed(e) => assert!(e.contains("no free port"), "unexpected error: {}", e),
+            outcome => panic!("expected the relaunch to fail on the taken port, got {:?}", outcome),
+        }
+
+        // The flag is consumed, so a second wake leaves the server alone
+        assert_eq!(power_manager.handle_wake_event(&server_manager).await, WakeOutcome::Skipped);
+    }
+}
@@ -0,0 +1,124 @@
+// Adoption of TunnelForge servers started outside the app
+// Finds the process behind the port and applies the user's attach / take over / leave alone choice
+
+use serde::{Deserialize, Serialize};
+
+use super::health::ServerIdentity;
+
+/// What to do when a TunnelForge server we did not start already owns the port
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalServerPolicy {
+    /// Use the server but never stop or restart it
+    AttachReadOnly,
+    /// Stop it gracefully and start our own supervised server in its place
+    TakeOver,
+    /// Ignore it; the app shows it as external and offers no controls
+    LeaveAlone,
+}
+
+/// A TunnelForge server found on our port that is not our child process
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalServer {
+    /// `None` when the owning process could not be determined (other platforms, or no permission)
+    pub pid: Option<u32>,
+    pub port: u16,
+    pub identity: ServerIdentity,
+}
+
+/// Find the process listening on `port`
+#[cfg(target_os = "linux")]
+pub fn find_listening_pid(port: u16) -> Option<u32> {
+    let mut inodes = Vec::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        if let Ok(contents) = std::fs::read_to_string(table) {
+            inodes.extend(parse_listening_inodes(&contents, port));
+        }
+    }
+    if inodes.is_empty() {
+        return None;
+    }
+
+    let targets: Vec<String> = inodes.iter().map(|inode| format!("socket:[{}]", inode)).collect();
+
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        let pid = match entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+
+        // Processes of other users are unreadable without privileges; skip them
+        let fds = match std::fs::read_dir(entry.path().join("fd")) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+
+        for fd in fds.flatten() {
+            if let Ok(link) = std::fs::read_link(fd.path()) {
+                if targets.iter().any(|target| link.as_os_str() == target.as_str()) {
+                    return Some(pid);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn find_listening_pid(_port: u16) -> Option<u32> {
+    None
+}
+
+/// Socket inodes in a `/proc/net/tcp{,6}` table that are listening on `port`
+pub fn parse_listening_inodes(contents: &str, port: u16) -> Vec<u64> {
+    const TCP_LISTEN: &str = "0A";
+
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // sl, local_address, rem_address, st, tx:rx, tr:when, retrnsmt, uid, timeout, inode
+            if fields.len() < 10 || fields[3] != TCP_LISTEN {
+                return None;
+            }
+
+            let local_port = fields[1].rsplit(':').next()?;
+            if u16::from_str_radix(local_port, 16).ok()? != port {
+                return None;
+            }
+
+            fields[9].parse::<u64>().ok().filter(|inode| *inode != 0)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCP_TABLE: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0FB5 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 52814 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:0FB5 0100007F:D2A4 01 00000000:00000000 00:00000000 00000000  1000        0 52990 1 0000000000000000 20 4 30 10 -1
+   2: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1204 1 0000000000000000 100 0 0 10 0
+";
+
+    #[test]
+    fn test_finds_listening_socket_for_port() {
+        // 0x0FB5 == 4021; the established connection on the same port is ignored
+        assert_eq!(parse_listening_inodes(TCP_TABLE, 4021), vec![52814]);
+        assert_eq!(parse_listening_inodes(TCP_TABLE, 22), vec![1204]);
+        assert!(parse_listening_inodes(TCP_TABLE, 8080).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_finds_own_listener_pid() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        assert_eq!(find_listening_pid(port), Some(std::process::id()));
+    }
+}
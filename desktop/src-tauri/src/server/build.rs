@@ -0,0 +1,361 @@
+// Go server build subsystem
+// Rebuilds only when the Go sources change, streams compiler output and supports cancellation
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use ring::digest;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio::time::sleep;
+use log::{info, warn, error, debug};
+
+use super::resolver::SERVER_BINARY_NAME;
+
+/// Name of the Tauri event carrying `BuildEvent`s to the frontend
+pub const SERVER_BUILD_EVENT: &str = "server-build";
+
+/// File next to the built binary recording the source hash it was built from
+const BUILD_STAMP_FILE: &str = ".tunnelforge-build-hash";
+const BUILD_POLL_INTERVAL_MS: u64 = 100;
+
+static GO_VERSION: Lazy<Regex> = Lazy::new(|| Regex::new(r"go(\d+)\.(\d+)(?:\.(\d+))?").unwrap());
+static GO_MOD_DIRECTIVE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^go\s+(\d+)\.(\d+)(?:\.(\d+))?\s*$").unwrap());
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BuildEvent {
+    UpToDate { hash: String },
+    Started { dir: PathBuf, hash: String },
+    Output { stream: String, line: String },
+    Finished { hash: String, duration_ms: u64 },
+    Failed { error: String },
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuildOutcome {
+    UpToDate,
+    Built,
+}
+
+/// SHA-256 over go.mod, go.sum and every non-test `.go` file below `source_dir`
+pub fn source_hash(source_dir: &Path) -> Result<String, String> {
+    let mut files = Vec::new();
+    collect_sources(source_dir, &mut files)?;
+    for name in ["go.mod", "go.sum"] {
+        let path = source_dir.join(name);
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    let mut context = digest::Context::new(&digest::SHA256);
+    for path in files {
+        let contents = fs::read(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let relative = path.strip_prefix(source_dir).unwrap_or(&path);
+        // Length-prefix each part so moving bytes between files changes the hash
+        for part in [relative.to_string_lossy().as_bytes(), contents.as_slice()] {
+            context.update(&(part.len() as u64).to_le_bytes());
+            context.update(part);
+        }
+    }
+
+    Ok(data_encoding::HEXLOWER.encode(context.finish().as_ref()))
+}
+
+fn collect_sources(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "testdata" {
+                collect_sources(&path, files)?;
+            }
+        } else if name.ends_with(".go") && !name.ends_with("_test.go") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Where `go build` in `source_dir` puts the server binary
+pub fn built_binary(source_dir: &Path) -> PathBuf {
+    source_dir.join(SERVER_BINARY_NAME)
+}
+
+fn parse_go_version(text: &str, pattern: &Regex) -> Option<(u64, u64, u64)> {
+    let captures = pattern.captures(text)?;
+    let part = |i: usize| captures.get(i).and_then(|m| m.as_str().parse().ok()).unwrap_or(0);
+    Some((part(1), part(2), part(3)))
+}
+
+/// Fail early with a clear message when the installed Go is older than go.mod asks for
+pub fn check_go_toolchain(source_dir: &Path) -> Result<(), String> {
+    let output = Command::new("go")
+        .arg("version")
+        .output()
+        .map_err(|e| format!("Go is not installed or not on PATH: {}", e))?;
+    let installed_text = String::from_utf8_lossy(&output.stdout);
+    let installed = parse_go_version(&installed_text, &GO_VERSION)
+        .ok_or_else(|| format!("Unrecognised `go version` output: {}", installed_text.trim()))?;
+
+    let go_mod = fs::read_to_string(source_dir.join("go.mod"))
+        .map_err(|e| format!("Failed to read go.mod: {}", e))?;
+    check_go_version(installed, &go_mod)
+}
+
+fn check_go_version(installed: (u64, u64, u64), go_mod: &str) -> Result<(), String> {
+    match parse_go_version(go_mod, &GO_MOD_DIRECTIVE) {
+        Some(required) if installed < required => Err(format!(
+            "Go {}.{}.{} is installed but go.mod requires Go {}.{}.{} or newer",
+            installed.0, installed.1, installed.2, required.0, required.1, required.2
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Clone)]
+pub struct GoBuilder {
+    events: broadcast::Sender<BuildEvent>,
+    current: Arc<Mutex<Option<Child>>>,
+    cancel_requested: Arc<AtomicBool>,
+    // Serialises builds so two launches never race on the same output file
+    build_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl GoBuilder {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(256);
+
+        Self {
+            events,
+            current: Arc::new(Mutex::new(None)),
+            cancel_requested: Arc::new(AtomicBool::new(false)),
+            build_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<BuildEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn is_building(&self) -> bool {
+        self.current.lock().unwrap().is_some()
+    }
+
+    /// Stop the running `go build`, if any. Returns false when nothing was building.
+    pub fn cancel(&self) -> bool {
+        let mut current = self.current.lock().unwrap();
+        match current.as_mut() {
+            Some(child) => {
+                self.cancel_requested.store(true, Ordering::SeqCst);
+                let _ = child.kill();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Build the server in `source_dir` unless the existing binary matches the current sources
+    pub async fn build(&self, source_dir: &Path, force: bool) -> Result<BuildOutcome, String> {
+        let _guard = self.build_lock.lock().await;
+
+        let dir = source_dir.to_path_buf();
+        let hash = tauri::async_runtime::spawn_blocking(move || source_hash(&dir))
+            .await
+            .map_err(|e| format!("Source hashing failed: {}", e))??;
+
+        let stamp = fs::read_to_string(source_dir.join(BUILD_STAMP_FILE)).unwrap_or_default();
+        if !force && stamp.trim() == hash && built_binary(source_dir).is_file() {
+            debug!("Go server is up to date ({})", hash);
+            let _ = self.events.send(BuildEvent::UpToDate { hash });
+            return Ok(BuildOutcome::UpToDate);
+        }
+
+        let dir = source_dir.to_path_buf();
+        let toolchain = tauri::async_runtime::spawn_blocking(move || check_go_toolchain(&dir))
+            .await
+            .map_err(|e| format!("Go toolchain check failed: {}", e))
+            .and_then(|checked| checked);
+        if let Err(e) = toolchain {
+            let _ = self.events.send(BuildEvent::Failed { error: e.clone() });
+            return Err(e);
+        }
+
+        match self.run_go_build(source_dir, &hash).await {
+            Ok(()) => Ok(BuildOutcome::Built),
+            Err(e) => {
+                let event = if self.cancel_requested.load(Ordering::SeqCst) {
+                    BuildEvent::Cancelled
+                } else {
+                    BuildEvent::Failed { error: e.clone() }
+                };
+                let _ = self.events.send(event);
+                Err(e)
+            }
+        }
+    }
+
+    async fn run_go_build(&self, source_dir: &Path, hash: &str) -> Result<(), String> {
+        let start_time = Instant::now();
+        // Build beside the live binary and swap it in, so a running server is never overwritten mid-write
+        let temp_binary = format!("{}.building", SERVER_BINARY_NAME);
+
+        info!("Building Go server in {:?} (sources {})", source_dir, hash);
+        let _ = self.events.send(BuildEvent::Started {
+            dir: source_dir.to_path_buf(),
+            hash: hash.to_string(),
+        });
+
+        self.cancel_requested.store(false, Ordering::SeqCst);
+        let mut child = Command::new("go")
+            .args(["build", "-o", &temp_binary, "./cmd/server"])
+            .current_dir(source_dir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run go build: {}", e))?;
+
+        if let Some(stdout) = child.stdout.take() {
+            self.stream_output("stdout", stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            self.stream_output("stderr", stderr);
+        }
+        *self.current.lock().unwrap() = Some(child);
+
+        let status = loop {
+            let polled = self.current.lock().unwrap().as_mut().map(|child| child.try_wait());
+            match polled {
+                Some(Ok(Some(status))) => break status,
+                Some(Ok(None)) => sleep(Duration::from_millis(BUILD_POLL_INTERVAL_MS)).await,
+                Some(Err(e)) => {
+                    self.current.lock().unwrap().take();
+                    return Err(format!("Failed to wait for go build: {}", e));
+                }
+                None => return Err("go build process disappeared".to_string()),
+            }
+        };
+        self.current.lock().unwrap().take();
+
+        if self.cancel_requested.load(Ordering::SeqCst) {
+            let _ = fs::remove_file(source_dir.join(&temp_binary));
+            warn!("Go server build cancelled");
+            return Err("Build cancelled".to_string());
+        }
+
+        if !status.success() {
+            let _ = fs::remove_file(source_dir.join(&temp_binary));
+            return Err(format!("go build failed with exit code {:?}", status.code()));
+        }
+
+        fs::rename(source_dir.join(&temp_binary), built_binary(source_dir))
+            .map_err(|e| format!("Failed to install built server binary: {}", e))?;
+        if let Err(e) = fs::write(source_dir.join(BUILD_STAMP_FILE), hash) {
+            // Only costs an unnecessary rebuild next time
+            warn!("Failed to write build stamp: {}", e);
+        }
+
+        let duration_ms = start_time.elapsed().as_millis() as u64;
+        info!("Go server built in {}ms", duration_ms);
+        let _ = self.events.send(BuildEvent::Finished {
+            hash: hash.to_string(),
+            duration_ms,
+        });
+        Ok(())
+    }
+
+    fn stream_output<R: Read + Send + 'static>(&self, stream: &'static str, reader: R) {
+        let events = self.events.clone();
+        let result = thread::Builder::new()
+            .name(format!("go-build-{}", stream))
+            .spawn(move || {
+                for line in BufReader::new(reader).lines() {
+                    match line {
+                        Ok(line) => {
+                            let _ = events.send(BuildEvent::Output {
+                                stream: stream.to_string(),
+                                line,
+                            });
+                        }
+                        Err(_) => break,
+                    }
+                }
+            });
+
+        if let Err(e) = result {
+            error!("Failed to start go build {} reader: {}", stream, e);
+        }
+    }
+}
+
+impl Default for GoBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, contents: &str) {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_hash_tracks_sources_only() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "go.mod", "module example\n\ngo 1.21\n");
+        write(dir.path(), "cmd/server/main.go", "package main\n");
+        let original = source_hash(dir.path()).unwrap();
+
+        // Tests, hidden directories and non-Go files do not end up in the binary
+        write(dir.path(), "cmd/server/main_test.go", "package main\n");
+        write(dir.path(), ".git/HEAD", "ref: refs/heads/main\n");
+        write(dir.path(), "README.md", "# server\n");
+        assert_eq!(source_hash(dir.path()).unwrap(), original);
+
+        write(dir.path(), "internal/api/api.go", "package api\n");
+        assert_ne!(source_hash(dir.path()).unwrap(), original);
+    }
+
+    #[tokio::test]
+    async fn test_build_skips_binary_matching_stamp() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "go.mod", "module example\n\ngo 1.21\n");
+        write(dir.path(), "main.go", "package main\n");
+        write(dir.path(), SERVER_BINARY_NAME, "");
+        write(dir.path(), BUILD_STAMP_FILE, &source_hash(dir.path()).unwrap());
+
+        let builder = GoBuilder::new();
+        let mut events = builder.subscribe();
+        assert_eq!(builder.build(dir.path(), false).await.unwrap(), BuildOutcome::UpToDate);
+        assert!(matches!(events.try_recv().unwrap(), BuildEvent::UpToDate { .. }));
+    }
+
+    #[test]
+    fn test_go_version_check() {
+        let go_mod = "module example\n\ngo 1.23.0\n\ntoolchain go1.24.2\n";
+
+        assert!(check_go_version((1, 24, 2), go_mod).is_ok());
+        assert!(check_go_version((1, 23, 0), go_mod).is_ok());
+        assert!(check_go_version((1, 22, 9), go_mod).unwrap_err().contains("requires Go 1.23.0"));
+        assert_eq!(parse_go_version("go version go1.22.3 linux/amd64", &GO_VERSION), Some((1, 22, 3)));
+    }
+}
@@ -2,7 +2,9 @@
 // Port of health check functionality from ServerManager.swift
 
 use std::time::Duration;
-use std::net::{TcpStream, SocketAddr};
+use std::net::SocketAddr;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
 use reqwest;
 use serde::{Deserialize, Serialize};
 use log::debug;
@@ -17,6 +19,27 @@ pub struct HealthStatus {
     pub error: Option<String>,
 }
 
+/// Value of `name` in the `/api/health` payload of a TunnelForge server
+pub const TUNNELFORGE_SERVER_NAME: &str = "tunnelforge";
+
+/// Identity reported by `/api/health`, used to tell our server apart from other listeners
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerIdentity {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub instance_id: Option<String>,
+}
+
+impl ServerIdentity {
+    pub fn is_tunnelforge(&self) -> bool {
+        self.name.as_deref() == Some(TUNNELFORGE_SERVER_NAME)
+    }
+}
+
 pub struct HealthChecker {
     port: u16,
     host: String,
@@ -37,11 +60,11 @@ impl HealthChecker {
         }
     }
 
-    /// Quick TCP connection check
-    pub fn check_tcp_connection(&self) -> bool {
+    /// Quick TCP connection check, without blocking a runtime worker while the connect is pending
+    pub async fn check_tcp_connection(&self) -> bool {
         let addr = format!("{}:{}", self.host, self.port");
         if let Ok(socket_addr) = addr.parse::<SocketAddr>() {
-            TcpStream::connect_timeout(&socket_addr, Duration::from_millis(1000)).is_ok()
+            matches!(timeout(Duration::from_millis(1000), TcpStream::connect(socket_addr)).await, Ok(Ok(_)))
         } else {
             false
         }
@@ -111,6 +134,20 @@ impl HealthChecker {
         }
     }
 
+    /// Ask the listener who it is. Fails when it does not answer `/api/health` with JSON.
+    pub async fn fetch_identity(&self) -> Result<ServerIdentity, String> {
+        let url = format!("http://{}:{}/api/health", self.host, self.port);
+
+        let response = self.client.get(&url).send().await
+            .map_err(|e| format!("No HTTP response on port {}: {}", self.port, e))?;
+        if !response.status().is_success() {
+            return Err(format!("/api/health returned {}", response.status()));
+        }
+
+        response.json::<ServerIdentity>().await
+            .map_err(|e| format!("/api/health did not return a TunnelForge payload: {}", e))
+    }
+
     /// Get server metrics if available
     pub async fn get_server_metrics(&self) -> Result<serde_json::Value, String> {
         let url = format!("http://{}:{}/api/metrics", self.host, self.port");
@@ -141,7 +178,7 @@ pub async fn check_server_health(port: u16) -> Result<HealthStatus, String> {
 #[tauri::command]
 pub async fn check_server_tcp(port: u16) -> Result<bool, String> {
     let health_checker = HealthChecker::new(port, "127.0.0.1".to_string()");
-    Ok(health_checker.check_tcp_connection())
+    Ok(health_checker.check_tcp_connection().await)
 }
 
 #[tauri::command]
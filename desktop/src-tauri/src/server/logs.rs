@@ -0,0 +1,201 @@
+// Go server output capture
+// Pipes the child's stdout/stderr into the desktop log store and streams it to the frontend
+
+use std::io::{BufRead, BufReader, Read};
+use std::process::Child;
+use std::thread;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast;
+use log::{debug, error};
+
+use super::options::ServerLogLevel;
+use crate::{add_log_entry_with_source, get_server_logs_internal, subscribe_log_entries, LogEntry, SERVER_LOG_SOURCE};
+
+/// Name of the Tauri event carrying each captured server `LogEntry`
+pub const SERVER_LOG_EVENT: &str = "server-log";
+
+// Go's standard logger prefix: "2025/01/27 10:00:00 " with optional microseconds
+static GO_LOG_PREFIX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\d{4}/\d{2}/\d{2} \d{2}:\d{2}:\d{2}(\.\d+)? ").unwrap()
+});
+
+static LOGFMT_LEVEL: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?:^|\s)level=("?)(\w+)"#).unwrap());
+static LOGFMT_MSG: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?:^|\s)msg=(?:"((?:[^"\\]|\\.)*)"|(\S+))"#).unwrap());
+
+/// Split a server output line into (level, message)
+pub fn parse_server_log_line(line: &str) -> (String, String) {
+    let line = line.trim_end();
+
+    // JSON lines, e.g. from log/slog's JSON handler
+    if line.starts_with('{') {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(line) {
+            let level = value.get("level").and_then(|v| v.as_str());
+            let message = value.get("msg")
+                .or_else(|| value.get("message"))
+                .and_then(|v| v.as_str());
+            if let Some(message) = message {
+                return (normalize_level(level.unwrap_or("info")), message.to_string());
+            }
+        }
+    }
+
+    // logfmt lines, e.g. `time=... level=WARN msg="session idle"`
+    if let (Some(level), Some(message)) = (LOGFMT_LEVEL.captures(line), LOGFMT_MSG.captures(line)) {
+        let message = message.get(1)
+            .map(|m| m.as_str().replace("\\\"", "\""))
+            .or_else(|| message.get(2).map(|m| m.as_str().to_string()))
+            .unwrap_or_default();
+        return (normalize_level(&level[2]), message);
+    }
+
+    // Standard library logger output
+    let message = GO_LOG_PREFIX.replace(line, "").to_string();
+    (infer_level(&message).to_string(), message)
+}
+
+fn normalize_level(level: &str) -> String {
+    match level.to_ascii_lowercase().as_str() {
+        "error" | "err" | "fatal" | "panic" => "error",
+        "warn" | "warning" => "warn",
+        "debug" | "trace" => "debug",
+        _ => "info",
+    }
+    .to_string()
+}
+
+fn infer_level(message: &str) -> &'static str {
+    let upper = message.to_ascii_uppercase();
+
+    if message.starts_with("panic:")
+        || message.starts_with("fatal error:")
+        || message.contains('❌')
+        || upper.starts_with("[ERROR]")
+        || upper.starts_with("ERROR")
+    {
+        "error"
+    } else if message.contains('⚠') || upper.starts_with("[WARN") || upper.starts_with("WARN") {
+        "warn"
+    } else if upper.starts_with("[DEBUG]") || upper.starts_with("DEBUG") {
+        "debug"
+    } else {
+        "info"
+    }
+}
+
+/// Take the piped stdout/stderr of `child` and feed every line at `min_level` or above into the log store.
+/// The child must have been spawned with `Stdio::piped()` for both streams.
+pub fn capture_server_output(child: &mut Child, min_level: ServerLogLevel) {
+    if let Some(stdout) = child.stdout.take() {
+        spawn_reader("stdout", stdout, min_level);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        spawn_reader("stderr", stderr, min_level);
+    }
+}
+
+fn spawn_reader<R: Read + Send + 'static>(stream: &'static str, reader: R, min_level: ServerLogLevel) {
+    let result = thread::Builder::new()
+        .name(format!("server-{}", stream))
+        .spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let mut buf = Vec::new();
+
+            loop {
+                buf.clear();
+                match reader.read_until(b'\n', &mut buf) {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        let line = String::from_utf8_lossy(&buf);
+                        if line.trim().is_empty() {
+                            continue;
+                        }
+                        let (level, message) = parse_server_log_line(&line);
+                        if ServerLogLevel::from_name(&level) < min_level {
+                            continue;
+                        }
+                        add_log_entry_with_source(SERVER_LOG_SOURCE, &level, &message);
+                    }
+                    Err(e) => {
+                        error!("Failed to read server {}: {}", stream, e);
+                        break;
+                    }
+                }
+            }
+
+            debug!("Server {} closed", stream);
+        });
+
+    if let Err(e) = result {
+        error!("Failed to start server {} reader: {}", stream, e);
+    }
+}
+
+/// Emit every captured server log line to the frontend as it arrives
+pub fn forward_server_logs(app: AppHandle) {
+    let mut receiver = subscribe_log_entries();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let entry: LogEntry = match receiver.recv().await {
+                Ok(entry) => entry,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!("Server log stream lagged, skipped {} entries", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            if entry.source != SERVER_LOG_SOURCE {
+                continue;
+            }
+
+            if let Err(e) = app.emit(SERVER_LOG_EVENT, &entry) {
+                error!("Failed to emit server log entry: {}", e);
+            }
+        }
+    });
+}
+
+// Tauri commands for server logs
+#[tauri::command]
+pub async fn get_server_logs(limit: Option<usize>) -> Result<Vec<LogEntry>, String> {
+    get_server_logs_internal(limit).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_standard_logger_lines() {
+        let (level, message) = parse_server_log_line("2025/01/27 10:00:00 TunnelForge Go server starting on port 4021\n");
+        assert_eq!(level, "info");
+        assert_eq!(message, "TunnelForge Go server starting on port 4021");
+
+        let (level, message) = parse_server_log_line("2025/01/27 10:00:00 ⚠️  Event channel full, dropping event: bell");
+        assert_eq!(level, "warn");
+        assert_eq!(message, "⚠️  Event channel full, dropping event: bell");
+
+        let (level, _) = parse_server_log_line("2025/01/27 10:00:00.123456 ❌ Failed to create session");
+        assert_eq!(level, "error");
+    }
+
+    #[test]
+    fn test_parses_structured_lines() {
+        let (level, message) = parse_server_log_line(r#"{"time":"2025-01-27T10:00:00Z","level":"WARN","msg":"session idle"}"#);
+        assert_eq!(level, "warn");
+        assert_eq!(message, "session idle");
+
+        let (level, message) = parse_server_log_line(r#"time=2025-01-27T10:00:00Z level=ERROR msg="pty \"closed\"" session=abc"#);
+        assert_eq!(level, "error");
+        assert_eq!(message, r#"pty "closed""#);
+    }
+
+    #[test]
+    fn test_panics_are_errors() {
+        let (level, _) = parse_server_log_line("panic: runtime error: invalid memory address");
+        assert_eq!(level, "error");
+    }
+}
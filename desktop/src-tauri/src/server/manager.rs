@@ -1,57 +1,585 @@
-use std::process::{Child, Command};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
 use tokio::time::sleep;
-use log::{info, error, debug};
-use futures::future::join_all;
+use log::{info, warn, error, debug};
 
 // use crate::add_log_entry; // Will be implemented later
 use crate::metrics::{StartupTimer, ServerDirectoryCache};
-use super::health::HealthChecker;
+use super::adopt::{find_listening_pid, ExternalServer, ExternalServerPolicy};
+use super::build::{built_binary, source_hash, BuildOutcome, GoBuilder};
+use super::health::{HealthChecker, ServerIdentity};
+use super::logs::capture_server_output;
+use super::options::{connect_host, ServerOptions, SpawnSettings};
+use super::pidfile::{process_executable, OrphanPolicy, ServerPidFile};
+use super::resolver::{ResolutionReport, ServerResolver};
+use super::resources::{ResourceMonitor, RESOURCE_SAMPLE_INTERVAL_SECS};
+use super::watchdog::{kill_hung_server, probe_server, HealthState, HealthTracker, WatchdogPolicy};
+use super::ports::{find_free_port, probe_port, PortOccupant, PortRange};
+use super::shutdown::{graceful_shutdown, pid_alive, terminate_pid, ShutdownOptions, ShutdownReport};
+use super::supervisor::{
+    classify_exit, CrashTracker, ExitKind, RestartDecision, RestartPolicy, ServerLifecycleEvent,
+    SUPERVISOR_POLL_INTERVAL_MS,
+};
 
-const MAX_HEALTH_CHECK_ATTEMPTS: u32 = 10;
+// 15s in total; a cold start that has to load persisted sessions can take a while
+const MAX_HEALTH_CHECK_ATTEMPTS: u32 = 75;
 const HEALTH_CHECK_INTERVAL_MS: u64 = 200;
+const DEV_SOURCE_POLL_INTERVAL_SECS: u64 = 3;
 
+/// Who controls the server that is (or is not) listening on our port
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerMode {
+    Stopped,
+    /// Our own supervised child
+    Managed,
+    /// An external TunnelForge server we use but never stop or restart
+    Attached,
+    /// An external server we leave alone, or have not decided about yet
+    External,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerStatus {
+    pub running: bool,
+    pub port: u16,
+    pub pid: Option<u32>,
+    pub mode: ServerMode,
+    /// Watchdog verdict for the managed server; `None` when it is not being watched
+    pub health: Option<HealthState>,
+}
+
+/// What `launch` did, returned to the frontend before the server is ready.
+/// Readiness arrives later as a `ready` or `start_failed` lifecycle event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ServerStartState {
+    Starting { pid: u32, port: u16 },
+    AlreadyRunning { port: u16 },
+    /// A server left running by a previous desktop session was taken back under supervision
+    Adopted { pid: u32, port: u16 },
+}
+
+/// Owns the Go server process. A single instance is registered with `app.manage` and every
+/// command, the tray and power handling go through it.
+#[derive(Clone)]
 pub struct ServerManager {
     process: Arc<Mutex<Option<Child>>>,
-    port: u16,
-    host: String,
+    /// Server from a previous desktop session that we supervise by pid, since it is not our child
+    adopted_pid: Arc<Mutex<Option<u32>>>,
+    pid_file: Arc<Mutex<Option<PathBuf>>>,
+    orphan_policy: Arc<Mutex<OrphanPolicy>>,
+    /// Port the current server runs on, or the next one will try first
+    port: Arc<AtomicU16>,
+    /// `AppConfig::server_port`; only becomes the running port when a server is started
+    configured_port: Arc<AtomicU16>,
+    port_range: Arc<Mutex<PortRange>>,
+    /// Address the server binds to; see `host()` for the one we connect to
+    host: Arc<Mutex<String>>,
+    options: Arc<Mutex<ServerOptions>>,
+    /// What the running child was started with, to tell whether a config change needs a restart
+    running_settings: Arc<Mutex<Option<SpawnSettings>>>,
+    /// Binary the running child was started from, so a source rebuild only restarts a development build
+    running_binary: Arc<Mutex<Option<PathBuf>>>,
+    instance_id: String,
+    external: Arc<Mutex<Option<ExternalServer>>>,
+    external_policy: Arc<Mutex<Option<ExternalServerPolicy>>>,
     startup_timer: Arc<StartupTimer>,
     server_dir_cache: Arc<Mutex<ServerDirectoryCache>>,
+    server_binary: Arc<Mutex<Option<PathBuf>>>,
+    configured_executable: Arc<Mutex<Option<PathBuf>>>,
+    builder: GoBuilder,
+    development_mode: Arc<AtomicBool>,
+    watching_sources: Arc<AtomicBool>,
+    resources: ResourceMonitor,
+    watchdog_policy: Arc<Mutex<WatchdogPolicy>>,
+    health_state: Arc<Mutex<Option<HealthState>>>,
+    watchdog_running: Arc<AtomicBool>,
+    lifecycle_sender: broadcast::Sender<ServerLifecycleEvent>,
+    restart_policy: RestartPolicy,
+    shutdown_options: Arc<Mutex<ShutdownOptions>>,
+    stop_requested: Arc<AtomicBool>,
+    /// Set while `stop` drains a child it already took out of `process`, so nothing launches over it
+    stopping: Arc<AtomicBool>,
+    supervising: Arc<AtomicBool>,
+}
+
+enum ChildPoll {
+    Running,
+    Exited(u32, ExitKind),
+    Gone,
 }
 
 impl ServerManager {
     pub fn new(port: u16, host: String, startup_timer: Arc<StartupTimer>) -> Self {
+        let (lifecycle_sender, _) = broadcast::channel(32);
+
         Self {
             process: Arc::new(Mutex::new(None)),
-            port,
-            host,
+            adopted_pid: Arc::new(Mutex::new(None)),
+            pid_file: Arc::new(Mutex::new(None)),
+            orphan_policy: Arc::new(Mutex::new(OrphanPolicy::default())),
+            port: Arc::new(AtomicU16::new(port)),
+            configured_port: Arc::new(AtomicU16::new(port)),
+            port_range: Arc::new(Mutex::new(PortRange::default())),
+            host: Arc::new(Mutex::new(host)),
+            options: Arc::new(Mutex::new(ServerOptions::default())),
+            running_settings: Arc::new(Mutex::new(None)),
+            running_binary: Arc::new(Mutex::new(None)),
+            instance_id: generate_instance_id(),
+            external: Arc::new(Mutex::new(None)),
+            external_policy: Arc::new(Mutex::new(None)),
             startup_timer,
             server_dir_cache: Arc::new(Mutex::new(ServerDirectoryCache::new())),
+            server_binary: Arc::new(Mutex::new(None)),
+            configured_executable: Arc::new(Mutex::new(None)),
+            builder: GoBuilder::new(),
+            development_mode: Arc::new(AtomicBool::new(false)),
+            watching_sources: Arc::new(AtomicBool::new(false)),
+            resources: ResourceMonitor::new(),
+            watchdog_policy: Arc::new(Mutex::new(WatchdogPolicy::default())),
+            health_state: Arc::new(Mutex::new(None)),
+            watchdog_running: Arc::new(AtomicBool::new(false)),
+            lifecycle_sender,
+            restart_policy: RestartPolicy::default(),
+            shutdown_options: Arc::new(Mutex::new(ShutdownOptions::default())),
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            stopping: Arc::new(AtomicBool::new(false)),
+            supervising: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Spawn the server and return as soon as the process exists. Readiness is reported later
+    /// through a `Ready` or `StartFailed` lifecycle event.
+    pub async fn launch(&self) -> Result<ServerStartState, String> {
+        if self.has_server_process() {
+            return Err("Server is already running".to_string());
+        }
+
+        // Each start goes back to the configured port; an earlier reassignment or adoption may have moved it
+        self.port.store(self.configured_port.load(Ordering::SeqCst), Ordering::SeqCst);
+
+        if let Some(state) = self.recover_orphan().await? {
+            return Ok(state);
+        }
+
+        let port = self.get_port();
+        match probe_port(&self.host(), port).await {
+            PortOccupant::Free => {}
+            PortOccupant::TunnelForge(identity) => {
+                if !self.adopt_external(port, identity).await? {
+                    return Ok(ServerStartState::AlreadyRunning { port });
+                }
+            }
+            PortOccupant::Foreign(reason) => self.move_off_port(port, reason)?,
+        }
+
+        let server_binary = self.resolve_server_binary().await?;
+        let pid = self.spawn_process(&server_binary, 1)?;
+        self.startup_timer.record_server_start();
+
+        // Watch the child from now on so a crash during startup is caught too
+        self.supervise();
+
+        let manager = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = manager.await_ready(pid).await;
+        });
+
+        Ok(ServerStartState::Starting { pid, port: self.get_port() })
+    }
+
+    fn has_server_process(&self) -> bool {
+        // Check `process` before `stopping`: `stop` raises the flag while still holding the lock
+        self.process.lock().unwrap().is_some()
+            || self.adopted_pid.lock().unwrap().is_some()
+            || self.stopping.load(Ordering::SeqCst)
+    }
+
+    /// Where to keep the pid file; `None` disables orphan detection
+    pub fn set_pid_file(&self, path: Option<PathBuf>) {
+        *self.pid_file.lock().unwrap() = path;
+    }
+
+    pub fn set_orphan_policy(&self, policy: OrphanPolicy) {
+        *self.orphan_policy.lock().unwrap() = policy;
+    }
+
+    /// Handle a server that a previous desktop session started and then lost track of, e.g. because
+    /// the app was killed. Returns the start state when the orphan was adopted instead of replaced.
+    async fn recover_orphan(&self) -> Result<Option<ServerStartState>, String> {
+        let path = match self.pid_file.lock().unwrap().clone() {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let record = match ServerPidFile::read(&path) {
+            Ok(Some(record)) => record,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                warn!("{}", e);
+                ServerPidFile::remove(&path);
+                return Ok(None);
+            }
+        };
+
+        let identity = match probe_port(&self.host(), record.port).await {
+            PortOccupant::TunnelForge(identity) => Some(identity),
+            _ => None,
+        };
+        let verified = record.verify(
+            pid_alive(record.pid),
+            process_executable(record.pid).as_deref(),
+            identity.as_ref(),
+            find_listening_pid(record.port),
+        );
+        if let Err(reason) = verified {
+            info!("Ignoring stale server pid file: {}", reason);
+            ServerPidFile::remove(&path);
+            return Ok(None);
+        }
+
+        let policy = *self.orphan_policy.lock().unwrap();
+        info!(
+            "Server (PID: {}) from a previous session is still running on port {}, policy: {:?}",
+            record.pid, record.port, policy
+        );
+        let _ = self.lifecycle_sender.send(ServerLifecycleEvent::OrphanFound {
+            pid: record.pid,
+            port: record.port,
+            policy,
+        });
+
+        match policy {
+            OrphanPolicy::Adopt => {
+                self.port.store(record.port, Ordering::SeqCst);
+                if record.binary.is_file() {
+                    *self.server_binary.lock().unwrap() = Some(record.binary.clone());
+                }
+                *self.adopted_pid.lock().unwrap() = Some(record.pid);
+                self.stop_requested.store(false, Ordering::SeqCst);
+                self.supervise();
+
+                let _ = self.lifecycle_sender.send(ServerLifecycleEvent::Ready {
+                    pid: record.pid,
+                    port: record.port,
+                    startup_ms: 0,
+                });
+                Ok(Some(ServerStartState::Adopted { pid: record.pid, port: record.port }))
+            }
+            OrphanPolicy::Terminate => {
+                let options = self.shutdown_options.lock().unwrap().clone();
+                let health_checker = HealthChecker::new(record.port, self.host());
+                let report = terminate_pid(record.pid, Some(&health_checker), &options).await?;
+
+                info!("Stopped orphaned server (PID: {}) via {:?}", record.pid, report.stage);
+                ServerPidFile::remove(&path);
+                Ok(None)
+            }
+        }
+    }
+
+    fn write_pid_file(&self, pid: u32, port: u16, server_binary: &Path) {
+        let path = match self.pid_file.lock().unwrap().clone() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let record = ServerPidFile {
+            pid,
+            port,
+            binary: server_binary.to_path_buf(),
+            instance_id: self.instance_id.clone(),
+            started_at: chrono::Utc::now(),
+        };
+        if let Err(e) = record.write(&path) {
+            warn!("{}", e);
+        }
+    }
+
+    fn clear_pid_file(&self) {
+        if let Some(path) = self.pid_file.lock().unwrap().as_deref() {
+            ServerPidFile::remove(path);
+        }
+    }
+
+    /// A TunnelForge server we did not start holds `port`. Applies the external server policy and
+    /// returns true when the port was freed so our own server can start.
+    async fn adopt_external(&self, port: u16, identity: ServerIdentity) -> Result<bool, String> {
+        let external = ExternalServer {
+            pid: find_listening_pid(port),
+            port,
+            identity,
+        };
+        let policy = *self.external_policy.lock().unwrap();
+
+        info!("TunnelForge server (PID: {:?}) is already running on port {}, policy: {:?}", external.pid, port, policy);
+        *self.external.lock().unwrap() = Some(external.clone());
+        let _ = self.lifecycle_sender.send(ServerLifecycleEvent::ExternalServerDetected {
+            pid: external.pid,
+            port,
+            instance_id: external.identity.instance_id.clone(),
+            policy,
+        });
+
+        if policy != Some(ExternalServerPolicy::TakeOver) {
+            return Ok(false);
+        }
+
+        let pid = external.pid.ok_or_else(|| {
+            format!("Cannot take over the server on port {}: the process owning it could not be found", port)
+        })?;
+        let options = self.shutdown_options.lock().unwrap().clone();
+        let health_checker = HealthChecker::new(port, self.host());
+        let report = terminate_pid(pid, Some(&health_checker), &options).await?;
+
+        info!("Took over external server (PID: {}), it stopped via {:?}", pid, report.stage);
+        *self.external.lock().unwrap() = None;
+        Ok(true)
+    }
+
+    pub fn set_external_policy(&self, policy: Option<ExternalServerPolicy>) {
+        *self.external_policy.lock().unwrap() = policy;
+    }
+
+    /// Apply a newly chosen policy to the server currently on our port, if any
+    pub async fn apply_external_policy(&self, policy: ExternalServerPolicy) -> Result<ServerStatus, String> {
+        self.set_external_policy(Some(policy));
+
+        let occupied = matches!(probe_port(&self.host(), self.get_port()).await, PortOccupant::TunnelForge(_));
+        if self.process.lock().unwrap().is_none() && occupied {
+            self.launch().await?;
+        }
+        Ok(self.status().await)
+    }
+
+    /// Look for a TunnelForge server on our port that is not our child
+    pub async fn discover_external(&self) -> Option<ExternalServer> {
+        if self.process.lock().unwrap().is_some() {
+            return None;
+        }
+
+        let port = self.get_port();
+        match probe_port(&self.host(), port).await {
+            PortOccupant::TunnelForge(identity) => Some(ExternalServer {
+                pid: find_listening_pid(port),
+                port,
+                identity,
+            }),
+            _ => None,
         }
     }
 
-    pub async fn start(&self, server_dir: &std::path::Path) -> Result<u32, String> {
-        let mut process = self.process.lock().unwrap(");
+    /// Another program holds `port`: switch to a free port in the configured range
+    fn move_off_port(&self, port: u16, reason: String) -> Result<(), String> {
+        let range = *self.port_range.lock().unwrap();
+        let new_port = find_free_port(&self.host(), &range, port).ok_or_else(|| {
+            format!("Port {} is in use by another program ({}) and no free port was found in {}", port, reason, range)
+        })?;
+
+        warn!("Port {} is in use by another program ({}), using port {} instead", port, reason, new_port);
+        self.port.store(new_port, Ordering::SeqCst);
+        let _ = self.lifecycle_sender.send(ServerLifecycleEvent::PortReassigned {
+            from: port,
+            to: new_port,
+            reason,
+        });
+        Ok(())
+    }
+
+    async fn resolve_server_binary(&self) -> Result<PathBuf, String> {
+        let source_dir = self.development_source_dir();
+
+        // In development mode the checkout is rebuilt whenever its sources changed since the last build,
+        // and that build is what runs rather than whichever binary resolution would pick first
+        if self.development_mode.load(Ordering::SeqCst) {
+            if let Some(source_dir) = &source_dir {
+                self.builder.build(source_dir, false).await?;
+                let binary = built_binary(source_dir);
+                *self.server_binary.lock().unwrap() = Some(binary.clone());
+                return Ok(binary);
+            }
+        }
+
+        let cached = self.server_binary.lock().unwrap().clone().filter(|binary| binary.is_file());
+        let binary = match cached {
+            Some(binary) => binary,
+            None => {
+                let mut report = self.resolution_report().await?;
+                if report.selected.is_none() {
+                    if let Some(source_dir) = &source_dir {
+                        info!("No server binary found, building the development tree at {:?}", source_dir);
+                        self.builder.build(source_dir, false).await?;
+                        report = self.resolution_report().await?;
+                    }
+                }
+
+                let selected = report.into_result()?;
+                *self.server_binary.lock().unwrap() = Some(selected.path.clone());
+                selected.path
+            }
+        };
+
+        // Outside development mode a checkout's binary is still never run once its sources have moved on
+        if let Some(source_dir) = source_dir.filter(|dir| binary.parent() == Some(dir.as_path())) {
+            self.builder.build(&source_dir, false).await?;
+        }
+
+        Ok(binary)
+    }
+
+    fn development_source_dir(&self) -> Option<PathBuf> {
+        let configured = self.configured_executable.lock().unwrap().clone();
+        ServerResolver::standard(configured.as_deref()).development_source_dir()
+    }
+
+    pub fn builder(&self) -> &GoBuilder {
+        &self.builder
+    }
+
+    /// Build the development checkout now, even when the binary looks up to date if `force` is set
+    pub async fn build_server(&self, force: bool) -> Result<BuildOutcome, String> {
+        let source_dir = self.development_source_dir()
+            .ok_or_else(|| "No TunnelForge server source tree found".to_string())?;
+        self.builder.build(&source_dir, force).await
+    }
+
+    /// `AppConfig::use_development_server`: rebuild the checkout and restart the server when the Go sources change
+    pub fn set_development_mode(&self, enabled: bool) {
+        let was_enabled = self.development_mode.swap(enabled, Ordering::SeqCst);
+        if enabled {
+            self.watch_development_sources();
+        } else if was_enabled {
+            // The cached binary is the development build; resolve normally from the next launch on
+            self.server_binary.lock().unwrap().take();
+        }
+    }
+
+    fn watch_development_sources(&self) {
+        if self.watching_sources.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let manager = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut last_hash: Option<String> = None;
+
+            while manager.development_mode.load(Ordering::SeqCst) {
+                sleep(Duration::from_secs(DEV_SOURCE_POLL_INTERVAL_SECS)).await;
+
+                let source_dir = match manager.development_source_dir() {
+                    Some(dir) => dir,
+                    None => continue,
+                };
+                let hash_dir = source_dir.clone();
+                let hash = match tauri::async_runtime::spawn_blocking(move || source_hash(&hash_dir)).await {
+                    Ok(Ok(hash)) => hash,
+                    Ok(Err(e)) => {
+                        debug!("Failed to hash server sources: {}", e);
+                        continue;
+                    }
+                    Err(_) => continue,
+                };
+
+                // Only react to new edits, so a broken build is not retried every poll
+                if last_hash.as_deref() == Some(hash.as_str()) {
+                    continue;
+                }
+                last_hash = Some(hash);
+
+                match manager.builder.build(&source_dir, false).await {
+                    Ok(BuildOutcome::Built) => manager.restart_after_rebuild(&source_dir).await,
+                    Ok(BuildOutcome::UpToDate) => {}
+                    Err(e) => warn!("Development server rebuild failed: {}", e),
+                }
+            }
+
+            manager.watching_sources.store(false, Ordering::SeqCst);
+            debug!("Stopped watching server sources");
+        });
+    }
+
+    async fn restart_after_rebuild(&self, source_dir: &Path) {
+        if self.process.lock().unwrap().is_none() {
+            return;
+        }
+
+        // A bundled or installed server keeps running; only the build we just replaced needs a restart
+        let running = self.running_binary.lock().unwrap().clone();
+        if !running.is_some_and(|binary| same_path(&binary, &built_binary(source_dir))) {
+            debug!("Running server is not the development build, not restarting it after the rebuild");
+            return;
+        }
+
+        info!("Server sources changed, restarting the development server");
+        if let Err(e) = self.stop().await {
+            error!("Failed to stop server for rebuild: {}", e);
+            return;
+        }
+        if let Err(e) = self.launch().await {
+            error!("Failed to restart server after rebuild: {}", e);
+        }
+    }
+
+    /// Run the binary resolver without caching its choice, so the user can see why each candidate was picked or rejected.
+    /// Each candidate is run with `--version`, so this happens on the blocking pool.
+    pub async fn resolution_report(&self) -> Result<ResolutionReport, String> {
+        let configured = self.configured_executable.lock().unwrap().clone();
+        tauri::async_runtime::spawn_blocking(move || ServerResolver::standard(configured.as_deref()).resolve())
+            .await
+            .map_err(|e| format!("Server resolution failed: {}", e))
+    }
+
+    /// Use `path` (from `AppConfig::server_executable_path`) ahead of bundled and system binaries
+    pub fn set_configured_executable(&self, path: Option<PathBuf>) {
+        *self.configured_executable.lock().unwrap() = path;
+        self.server_binary.lock().unwrap().take();
+    }
+
+    fn spawn_process(&self, server_binary: &Path, attempt: u32) -> Result<u32, String> {
+        let mut process = self.process.lock().unwrap();
 
         if process.is_some() {
-            return Err("Server is already running".to_string()");
+            return Err("Server is already running".to_string());
+        }
+        if self.stopping.load(Ordering::SeqCst) {
+            return Err("The previous server is still shutting down".to_string());
         }
 
-        // Cache server directory
-        self.server_dir_cache.lock().unwrap().set_path(server_dir.to_path_buf()");
+        if !server_binary.is_file() {
+            return Err(format!("Server binary not found at {:?}", server_binary));
+        }
 
-        // Check if server binary exists
-        let server_binary = server_dir.join("tunnelforge-server");
-        if !server_binary.exists() {
-            return Err("Server binary not found".to_string()");
+        // The server resolves relative assets from its own directory
+        let server_dir = server_binary.parent().unwrap_or_else(|| Path::new("."));
+        self.server_dir_cache.lock().unwrap().set_path(server_dir.to_path_buf());
+
+        let settings = self.desired_settings();
+        if let Some(data_dir) = &settings.options.data_dir {
+            std::fs::create_dir_all(data_dir)
+                .map_err(|e| format!("Failed to create server data directory {:?}: {}", data_dir, e))?;
         }
 
+        let port = self.get_port();
+        let _ = self.lifecycle_sender.send(ServerLifecycleEvent::Starting {
+            port,
+            attempt,
+        });
+
         // Set up server command
-        let mut cmd = Command::new("./tunnelforge-server");
-        cmd.current_dir(server_dir)
-           .env("PORT", self.port.to_string())
-           .env("HOST", &self.host");
+        let mut cmd = Command::new(server_binary);
+        cmd.args(&settings.options.args)
+           .current_dir(server_dir)
+           .envs(settings.options.env_vars())
+           .env("PORT", port.to_string())
+           .env("HOST", &settings.host)
+           .env("TUNNELFORGE_INSTANCE_ID", &self.instance_id)
+           .stdout(Stdio::piped())
+           .stderr(Stdio::piped());
 
         // Platform-specific configuration
         #[cfg(target_os = "windows")]
@@ -61,48 +589,70 @@ impl ServerManager {
         }
 
         match cmd.spawn() {
-            Ok(child) => {
-                let pid = child.id(");
-                info!("Server started with PID: {}", pid");
-                log::info!("&format!("Server started with PID: {}", pid)");
-                *process = Some(child");
-
-                self.startup_timer.record_server_start(");
-
-                // Wait for server to be ready using health check polling
-                if let Err(e) = self.wait_for_server_ready().await {
-                    error!("Server failed to become ready: {}", e");
-                    log::error!("&format!("Server failed to become ready: {}", e)");
-                    return Err(e");
-                }
-
-                self.startup_timer.record_server_ready(");
+            Ok(mut child) => {
+                let pid = child.id();
+                info!("Server started with PID: {}", pid);
+                capture_server_output(&mut child, settings.options.log_level);
+                *process = Some(child);
+                *self.running_settings.lock().unwrap() = Some(settings);
+                *self.running_binary.lock().unwrap() = Some(server_binary.to_path_buf());
+                self.write_pid_file(pid, port, server_binary);
+                self.stop_requested.store(false, Ordering::SeqCst);
                 Ok(pid)
             }
             Err(e) => {
-                let error_msg = format!("Failed to start server: {}", e");
-                error!("{}", error_msg");
-                log::error!("&error_msg");
+                let error_msg = format!("Failed to start server: {}", e);
+                error!("{}", error_msg);
                 Err(error_msg)
             }
         }
     }
 
-    async fn wait_for_server_ready(&self) -> Result<(), String> {
-        let health_checker = HealthChecker::new(self.port, self.host.clone()");
-        let start_time = Instant::now(");
+    async fn await_ready(&self, pid: u32) -> Result<(), String> {
+        let start_time = Instant::now();
+
+        match Self::wait_for_server_ready(self.get_port(), &self.host(), &self.startup_timer).await {
+            Ok(startup_time) => {
+                let _ = self.lifecycle_sender.send(ServerLifecycleEvent::Ready {
+                    pid,
+                    port: self.get_port(),
+                    startup_ms: startup_time.as_millis() as u64,
+                });
+                Ok(())
+            }
+            Err(e) => {
+                error!("Server failed to become ready: {}", e);
+                let _ = self.lifecycle_sender.send(ServerLifecycleEvent::StartFailed {
+                    pid: Some(pid),
+                    error: e.clone(),
+                    elapsed_ms: start_time.elapsed().as_millis() as u64,
+                });
+                Err(e)
+            }
+        }
+    }
+
+    /// Poll `host:port` until the server accepts TCP connections and `/health` reports healthy.
+    /// Returns how long that took and records it in `startup_timer`.
+    pub async fn wait_for_server_ready(
+        port: u16,
+        host: &str,
+        startup_timer: &StartupTimer,
+    ) -> Result<Duration, String> {
+        let health_checker = HealthChecker::new(port, host.to_string());
+        let start_time = Instant::now();
 
         for attempt in 1..=MAX_HEALTH_CHECK_ATTEMPTS {
-            self.startup_timer.record_health_check(");
+            startup_timer.record_health_check();
 
             // Try TCP check first (faster)
-            if health_checker.check_tcp_connection() {
+            if health_checker.check_tcp_connection().await {
                 // If TCP succeeds, do a full health check
                 if health_checker.check_health().await.healthy {
-                    let elapsed = start_time.elapsed().as_millis(");
-                    info!("Server ready after {}ms ({} attempts)", elapsed, attempt");
-                    log::info!("&format!("Server ready after {}ms", elapsed)");
-                    return Ok(()");
+                    let elapsed = start_time.elapsed();
+                    info!("Server ready after {}ms ({} attempts)", elapsed.as_millis(), attempt);
+                    startup_timer.record_server_ready();
+                    return Ok(elapsed);
                 }
             }
 
@@ -111,56 +661,578 @@ impl ServerManager {
             }
         }
 
-        Err("Server failed to respond to health checks".to_string())
+        Err(format!(
+            "Server failed to respond to health checks within {}s",
+            start_time.elapsed().as_secs()
+        ))
     }
 
-    pub fn stop(&self) -> Result<(), String> {
-        let mut process = self.process.lock().unwrap(");
+    /// Start watching the child process in the background. Calling this while a
+    /// supervisor is already running is a no-op.
+    pub fn supervise(&self) {
+        if self.supervising.swap(true, Ordering::SeqCst) {
+            return;
+        }
 
-        if let Some(mut child) = process.take() {
-            info!("Stopping server (PID: {})...", child.id()");
-            log::info!("&format!("Stopping server (PID: {})...", child.id())");
+        let manager = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                manager.supervise_loop().await;
+                manager.supervising.store(false, Ordering::SeqCst);
 
-            match child.kill() {
-                Ok(_) => {
-                    // Wait for process to exit
-                    let _ = child.wait(");
-                    info!("Server stopped successfully");
-                    log::info!(""Server stopped successfully");
-                    Ok(())
+                // A new child may have been spawned after the loop decided to stop watching
+                let respawned = manager.has_server_process();
+                if !respawned || manager.supervising.swap(true, Ordering::SeqCst) {
+                    break;
+                }
+            }
+            debug!("Server supervisor stopped");
+        });
+    }
+
+    async fn supervise_loop(&self) {
+        let mut tracker = CrashTracker::new(self.restart_policy.clone());
+        let mut running_since = Instant::now();
+
+        loop {
+            sleep(Duration::from_millis(SUPERVISOR_POLL_INTERVAL_MS)).await;
+
+            let (pid, exit) = match self.poll_child() {
+                ChildPoll::Running => {
+                    if tracker.crash_count() > 0 && running_since.elapsed() >= tracker.policy().crash_window {
+                        tracker.record_stable();
+                    }
+                    continue;
+                }
+                ChildPoll::Gone => return,
+                ChildPoll::Exited(pid, exit) => (pid, exit),
+            };
+
+            if !exit.is_crash() {
+                info!("Server (PID: {}) exited cleanly", pid);
+                self.clear_pid_file();
+                let _ = self.lifecycle_sender.send(ServerLifecycleEvent::Stopped { pid });
+                return;
+            }
+
+            match tracker.record_crash(Instant::now()) {
+                RestartDecision::RestartAfter(delay) => {
+                    warn!("Server (PID: {}) exited unexpectedly ({:?}), restarting in {}ms", pid, exit, delay.as_millis());
+                    let _ = self.lifecycle_sender.send(ServerLifecycleEvent::Crashed {
+                        pid,
+                        exit,
+                        restart_in_ms: Some(delay.as_millis() as u64),
+                    });
+
+                    if !self.restart_after(delay, &mut tracker).await {
+                        return;
+                    }
+                    running_since = Instant::now();
+                }
+                RestartDecision::GiveUp => {
+                    self.give_up(pid, exit, &tracker);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn poll_child(&self) -> ChildPoll {
+        let mut process = self.process.lock().unwrap();
+
+        let polled = match process.as_mut() {
+            None => return self.poll_adopted(),
+            Some(child) => (child.id(), child.try_wait()),
+        };
+
+        match polled {
+            (_, Ok(None)) => ChildPoll::Running,
+            (pid, Ok(Some(status))) => {
+                process.take();
+                ChildPoll::Exited(pid, classify_exit(&status, self.stop_requested.load(Ordering::SeqCst)))
+            }
+            (pid, Err(e)) => {
+                error!("Failed to poll server process (PID: {}): {}", pid, e);
+                ChildPoll::Running
+            }
+        }
+    }
+
+    /// An adopted server is not our child, so all we can observe is whether its pid still exists
+    fn poll_adopted(&self) -> ChildPoll {
+        let mut adopted = self.adopted_pid.lock().unwrap();
+
+        match *adopted {
+            None => ChildPoll::Gone,
+            Some(pid) if pid_alive(pid) => ChildPoll::Running,
+            Some(pid) => {
+                adopted.take();
+                let exit = if self.stop_requested.load(Ordering::SeqCst) {
+                    ExitKind::Clean
+                } else {
+                    ExitKind::Crashed { code: None }
+                };
+                ChildPoll::Exited(pid, exit)
+            }
+        }
+    }
+
+    /// Restart the server after `delay`, retrying with backoff if spawning fails.
+    /// Returns false once supervision should end.
+    async fn restart_after(&self, mut delay: Duration, tracker: &mut CrashTracker) -> bool {
+        loop {
+            sleep(delay).await;
+
+            if self.stop_requested.load(Ordering::SeqCst) {
+                debug!("Stop requested during restart backoff, not restarting server");
+                return false;
+            }
+
+            // Someone else started the server while we were backing off
+            if self.has_server_process() {
+                return true;
+            }
+
+            let server_binary = match self.server_binary.lock().unwrap().clone() {
+                Some(binary) => binary,
+                None => {
+                    error!("No resolved server binary, cannot restart server");
+                    return false;
+                }
+            };
+
+            match self.spawn_process(&server_binary, tracker.crash_count() + 1) {
+                Ok(pid) => {
+                    // A failed readiness check is not fatal; the next poll decides
+                    let _ = self.await_ready(pid).await;
+                    return true;
                 }
                 Err(e) => {
-                    let error_msg = format!("Failed to stop server: {}", e");
-                    error!("{}", error_msg");
-                    log::error!("&error_msg");
-                    Err(error_msg)
+                    warn!("Server restart failed: {}", e);
+                    match tracker.record_crash(Instant::now()) {
+                        RestartDecision::RestartAfter(next) => delay = next,
+                        RestartDecision::GiveUp => {
+                            let _ = self.lifecycle_sender.send(ServerLifecycleEvent::GivingUp {
+                                crashes: tracker.crash_count(),
+                                window_secs: tracker.policy().crash_window.as_secs(),
+                            });
+                            return false;
+                        }
+                    }
                 }
             }
-        } else {
-            debug!("Server is not running");
-            Ok(())
         }
     }
 
-    pub fn is_running(&self) -> bool {
-        let process = self.process.lock().unwrap(");
-        process.is_some() && super::is_server_running(self.port)
+    fn give_up(&self, pid: u32, exit: ExitKind, tracker: &CrashTracker) {
+        self.clear_pid_file();
+        error!(
+            "Server crashed {} times within {}s, giving up on automatic restarts",
+            tracker.crash_count(),
+            tracker.policy().crash_window.as_secs()
+        );
+        let _ = self.lifecycle_sender.send(ServerLifecycleEvent::Crashed {
+            pid,
+            exit,
+            restart_in_ms: None,
+        });
+        let _ = self.lifecycle_sender.send(ServerLifecycleEvent::GivingUp {
+            crashes: tracker.crash_count(),
+            window_secs: tracker.policy().crash_window.as_secs(),
+        });
+    }
+
+    pub fn resources(&self) -> &ResourceMonitor {
+        &self.resources
+    }
+
+    /// Sample the managed child's resource usage for the rest of the app's life.
+    /// External servers are not sampled; we only answer for processes we started.
+    pub fn start_resource_sampling(&self) {
+        if !self.resources.begin_sampling() {
+            return;
+        }
+
+        let manager = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut health_checker: Option<HealthChecker> = None;
+            let mut sampled_port = 0;
+
+            loop {
+                sleep(Duration::from_secs(RESOURCE_SAMPLE_INTERVAL_SECS)).await;
+
+                let pid = match manager.get_pid() {
+                    Some(pid) => pid,
+                    None => {
+                        manager.resources.reset();
+                        continue;
+                    }
+                };
+
+                let port = manager.get_port();
+                if health_checker.is_none() || sampled_port != port {
+                    health_checker = Some(HealthChecker::new(port, manager.host()));
+                    sampled_port = port;
+                }
+                if let Some(health_checker) = &health_checker {
+                    manager.resources.sample(pid, health_checker).await;
+                }
+            }
+        });
+    }
+
+    pub fn set_watchdog_policy(&self, policy: WatchdogPolicy) {
+        *self.watchdog_policy.lock().unwrap() = policy;
+    }
+
+    pub fn health_state(&self) -> Option<HealthState> {
+        *self.health_state.lock().unwrap()
+    }
+
+    /// Watch the managed child's health checks and restart it when it hangs.
+    /// An open port is not enough: a deadlocked server still accepts connections.
+    pub fn start_watchdog(&self) {
+        if self.watchdog_running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let manager = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut watched: Option<(u32, HealthTracker)> = None;
+            let mut health_checker: Option<HealthChecker> = None;
+            let mut checked_port = 0;
+
+            loop {
+                let policy = manager.watchdog_policy.lock().unwrap().clone();
+                sleep(Duration::from_secs(policy.interval_secs.max(1))).await;
+
+                let pid = match manager.get_pid() {
+                    Some(pid) if policy.enabled => pid,
+                    _ => {
+                        watched = None;
+                        *manager.health_state.lock().unwrap() = None;
+                        continue;
+                    }
+                };
+
+                let port = manager.get_port();
+                if health_checker.is_none() || checked_port != port {
+                    health_checker = Some(HealthChecker::new(port, manager.host()));
+                    checked_port = port;
+                }
+                let probe = match &health_checker {
+                    Some(health_checker) => probe_server(health_checker).await,
+                    None => continue,
+                };
+
+                if watched.as_ref().map(|(watched_pid, _)| *watched_pid) != Some(pid) {
+                    watched = Some((pid, HealthTracker::new(policy.clone())));
+                }
+                let Some((_, tracker)) = watched.as_mut() else { continue };
+
+                let now = Instant::now();
+                if let Some(transition) = tracker.record(probe, now) {
+                    info!("Server (PID: {}) health {:?} -> {:?}", pid, transition.from, transition.to);
+                    *manager.health_state.lock().unwrap() = Some(transition.to);
+                    let _ = manager.lifecycle_sender.send(ServerLifecycleEvent::HealthChanged {
+                        pid,
+                        from: transition.from,
+                        to: transition.to,
+                        consecutive_failures: tracker.consecutive_failures(),
+                        latency_ms: tracker.smoothed_latency_ms(),
+                    });
+                } else if manager.health_state.lock().unwrap().is_none() {
+                    *manager.health_state.lock().unwrap() = Some(tracker.state());
+                }
+
+                if tracker.should_restart(now) {
+                    let unresponsive_for = tracker.unresponsive_for(now).unwrap_or_default();
+                    watched = None;
+                    manager.restart_unresponsive(pid, unresponsive_for, policy.goroutine_dump).await;
+                }
+            }
+        });
+    }
+
+    async fn restart_unresponsive(&self, pid: u32, unresponsive_for: Duration, goroutine_dump: bool) {
+        // Taking the process makes the supervisor stand down instead of treating the kill as a crash
+        let child = {
+            let mut process = self.process.lock().unwrap();
+            if process.as_ref().map(|child| child.id()) == Some(pid) { process.take() } else { None }
+        };
+        let adopted = {
+            let mut adopted = self.adopted_pid.lock().unwrap();
+            if *adopted == Some(pid) { adopted.take() } else { None }
+        };
+        if child.is_none() && adopted.is_none() {
+            return;
+        }
+
+        // An adopted server's stderr went away with the previous desktop session, so a dump would be lost
+        let goroutine_dump = goroutine_dump && child.is_some();
+        warn!("Server (PID: {}) has not answered health checks for {}s, restarting it", pid, unresponsive_for.as_secs());
+        *self.health_state.lock().unwrap() = None;
+        let _ = self.lifecycle_sender.send(ServerLifecycleEvent::WatchdogRestart {
+            pid,
+            unresponsive_ms: unresponsive_for.as_millis() as u64,
+            goroutine_dump,
+        });
+
+        let killed = match child {
+            Some(mut child) => kill_hung_server(&mut child, goroutine_dump).await,
+            None => terminate_pid(pid, None, &ShutdownOptions::with_drain_timeout_secs(0)).await.map(|_| ()),
+        };
+        if let Err(e) = killed {
+            error!("{}", e);
+            return;
+        }
+
+        if let Err(e) = self.launch().await {
+            error!("Failed to restart unresponsive server: {}", e);
+        }
+    }
+
+    pub fn set_shutdown_options(&self, options: ShutdownOptions) {
+        *self.shutdown_options.lock().unwrap() = options;
+    }
+
+    /// Stop the server gracefully. Returns `None` when no server was running.
+    pub async fn stop(&self) -> Result<Option<ShutdownReport>, String> {
+        self.stop_requested.store(true, Ordering::SeqCst);
+        let child = {
+            let mut process = self.process.lock().unwrap();
+            if process.is_some() && self.stopping.swap(true, Ordering::SeqCst) {
+                return Err("The server is already stopping".to_string());
+            }
+            process.take()
+        };
+
+        let mut child = match child {
+            Some(child) => child,
+            None if self.stopping.load(Ordering::SeqCst) => {
+                return Err("The server is already stopping".to_string());
+            }
+            None if self.adopted_pid.lock().unwrap().is_some() => return self.stop_adopted().await,
+            None if self.mode().await == ServerMode::Attached => {
+                return Err("The server was started outside TunnelForge and is attached read-only".to_string());
+            }
+            None => {
+                debug!("Server is not running");
+                return Ok(None);
+            }
+        };
+
+        let pid = child.id();
+        info!("Stopping server (PID: {})...", pid);
+        let _stopping = StoppingGuard(&self.stopping);
+
+        let options = self.shutdown_options.lock().unwrap().clone();
+        let health_checker = HealthChecker::new(self.get_port(), self.host());
+        match graceful_shutdown(&mut child, Some(&health_checker), &options).await {
+            Ok(report) => {
+                info!("Server stopped successfully ({:?} after {}ms)", report.stage, report.elapsed_ms);
+                self.clear_pid_file();
+                let _ = self.lifecycle_sender.send(ServerLifecycleEvent::Stopped { pid });
+                Ok(Some(report))
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to stop server: {}", e);
+                error!("{}", error_msg);
+                Err(error_msg)
+            }
+        }
+    }
+
+    async fn stop_adopted(&self) -> Result<Option<ShutdownReport>, String> {
+        let pid = {
+            let mut adopted = self.adopted_pid.lock().unwrap();
+            match adopted.take() {
+                Some(pid) if !self.stopping.swap(true, Ordering::SeqCst) => pid,
+                Some(pid) => {
+                    *adopted = Some(pid);
+                    return Err("The server is already stopping".to_string());
+                }
+                None => return Ok(None),
+            }
+        };
+        info!("Stopping adopted server (PID: {})...", pid);
+        let _stopping = StoppingGuard(&self.stopping);
+
+        let options = self.shutdown_options.lock().unwrap().clone();
+        let health_checker = HealthChecker::new(self.get_port(), self.host());
+        let report = terminate_pid(pid, Some(&health_checker), &options).await.map_err(|e| {
+            let error_msg = format!("Failed to stop server: {}", e);
+            error!("{}", error_msg);
+            error_msg
+        })?;
+
+        info!("Adopted server stopped ({:?} after {}ms)", report.stage, report.elapsed_ms);
+        self.clear_pid_file();
+        let _ = self.lifecycle_sender.send(ServerLifecycleEvent::Stopped { pid });
+        Ok(Some(report))
+    }
+
+    /// Whether our server process exists and is the one answering on our port
+    pub async fn is_running(&self) -> bool {
+        if !self.has_server_process() {
+            return false;
+        }
+
+        match probe_port(&self.host(), self.get_port()).await {
+            // An adopted server was started by a previous desktop session with that session's instance id
+            PortOccupant::TunnelForge(identity) => {
+                self.adopted_pid.lock().unwrap().is_some()
+                    || identity.instance_id.as_deref() == Some(self.instance_id.as_str())
+            }
+            _ => false,
+        }
     }
 
     pub fn get_pid(&self) -> Option<u32> {
-        let process = self.process.lock().unwrap(");
-        process.as_ref().map(|child| child.id())
+        let process = self.process.lock().unwrap();
+        process.as_ref().map(|child| child.id()).or(*self.adopted_pid.lock().unwrap())
     }
 
     pub fn get_port(&self) -> u16 {
-        self.port
+        self.port.load(Ordering::SeqCst)
+    }
+
+    /// Change the port used for the next launch; a running server keeps its port until restarted
+    pub fn set_port(&self, port: u16) {
+        self.configured_port.store(port, Ordering::SeqCst);
+        if !self.has_server_process() {
+            self.port.store(port, Ordering::SeqCst);
+        }
+    }
+
+    pub fn set_port_range(&self, range: PortRange) {
+        *self.port_range.lock().unwrap() = range;
+    }
+
+    /// Id passed to the server as `TUNNELFORGE_INSTANCE_ID` and echoed back by `/api/health`
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    pub fn get_host(&self) -> String {
+        self.host()
+    }
+
+    fn host(&self) -> String {
+        connect_host(&self.host.lock().unwrap())
     }
 
-    pub fn get_host(&self) -> &str {
-        &self.host
+    /// Bind address for the next launch, from `AppConfig::server_host`
+    pub fn set_host(&self, host: String) {
+        *self.host.lock().unwrap() = host;
     }
 
-    pub fn get_cached_server_dir(&self) -> Option<std::path::PathBuf> {
+    pub fn set_server_options(&self, options: ServerOptions) {
+        *self.options.lock().unwrap() = options;
+    }
+
+    fn desired_settings(&self) -> SpawnSettings {
+        SpawnSettings {
+            host: self.host.lock().unwrap().clone(),
+            // The configured port, so a reassignment after a port clash does not count as a change
+            port: self.configured_port.load(Ordering::SeqCst),
+            options: self.options.lock().unwrap().clone(),
+        }
+    }
+
+    /// Settings that changed since the managed child was started. Empty when no restart is needed,
+    /// including for adopted and external servers whose launch settings we do not know.
+    pub fn restart_required(&self) -> Vec<String> {
+        if self.process.lock().unwrap().is_none() {
+            return Vec::new();
+        }
+
+        match self.running_settings.lock().unwrap().as_ref() {
+            Some(running) => self.desired_settings().changes_from(running),
+            None => Vec::new(),
+        }
+    }
+
+    /// Current status as seen by every command, the tray and power handling.
+    /// A server started outside the app still counts as running, just without a pid.
+    pub async fn status(&self) -> ServerStatus {
+        let port = self.get_port();
+        let mode = self.mode().await;
+        let pid = match mode {
+            ServerMode::Managed => self.get_pid(),
+            ServerMode::Attached | ServerMode::External => self.external.lock().unwrap()
+                .as_ref()
+                .and_then(|external| external.pid)
+                .or_else(|| find_listening_pid(port)),
+            ServerMode::Stopped => None,
+        };
+
+        ServerStatus {
+            running: mode != ServerMode::Stopped,
+            port,
+            pid,
+            mode,
+            health: if mode == ServerMode::Managed { self.health_state() } else { None },
+        }
+    }
+
+    /// Without a process of our own, only a listener that identifies as TunnelForge counts as a server
+    pub async fn mode(&self) -> ServerMode {
+        if self.has_server_process() {
+            return ServerMode::Managed;
+        }
+
+        let occupant = probe_port(&self.host(), self.get_port()).await;
+        let mut external = self.external.lock().unwrap();
+        if !matches!(occupant, PortOccupant::TunnelForge(_)) {
+            external.take();
+            return ServerMode::Stopped;
+        }
+
+        let attached = external.is_some()
+            && *self.external_policy.lock().unwrap() == Some(ExternalServerPolicy::AttachReadOnly);
+        if attached {
+            ServerMode::Attached
+        } else {
+            ServerMode::External
+        }
+    }
+
+    pub fn get_cached_server_dir(&self) -> Option<PathBuf> {
         self.server_dir_cache.lock().unwrap().get_path().cloned()
     }
+
+    pub fn subscribe_lifecycle(&self) -> broadcast::Receiver<ServerLifecycleEvent> {
+        self.lifecycle_sender.subscribe()
+    }
+}
+
+/// Clears `ServerManager::stopping` once the drain is over, including when the stop future is dropped
+struct StoppingGuard<'a>(&'a AtomicBool);
+
+impl Drop for StoppingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Resolver candidates are built from relative paths, so compare where they actually point
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn generate_instance_id() -> String {
+    let mut bytes = [0u8; 16];
+    if ring::rand::SecureRandom::fill(&ring::rand::SystemRandom::new(), &mut bytes).is_err() {
+        // Only used to recognise our own child, so a time-based id is good enough as a fallback
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        return format!("{:032x}", nanos);
+    }
+    data_encoding::HEXLOWER.encode(&bytes)
 }
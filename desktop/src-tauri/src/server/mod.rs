@@ -2,361 +2,308 @@
 // Ported from mac/TunnelForge/Core/Services/ServerManager.swift
 
 pub mod manager;
+pub mod adopt;
 pub mod process;
 pub mod health;
+pub mod supervisor;
+pub mod shutdown;
+pub mod logs;
+pub mod ports;
+pub mod resolver;
+pub mod build;
+pub mod resources;
+pub mod watchdog;
+pub mod pidfile;
+pub mod options;
 
 pub use manager::*;
+pub use adopt::*;
 pub use process::*;
 pub use health::*;
-
-use tauri::{AppHandle, State};
-use std::process::Command;
-
-use std::thread;
-use std::time::Duration;
-use std::path::{Path, PathBuf};
+pub use supervisor::*;
+pub use shutdown::*;
+pub use logs::*;
+pub use ports::*;
+pub use resolver::*;
+pub use build::*;
+pub use resources::*;
+pub use watchdog::*;
+pub use pidfile::*;
+pub use options::*;
+
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::broadcast;
 use log::{info, error};
 
-// use crate::{AppState, ServerStatus, add_log_entry}; // Will be implemented later
-
-// Server management functions
-pub fn start_server_internal(state: &State<AppState>, _app: &AppHandle) -> Result<(), String> {
-    let mut server_process = state.server_process.lock().unwrap(");
-
-    if server_process.is_some() {
-        log::warn!(""Attempt to start server when already running");
-        return Err("Server is already running".to_string()");
-    }
+use crate::AppState;
+
+/// Forward supervisor lifecycle events to the frontend, tray and notifications
+pub fn forward_lifecycle_events(app: AppHandle, mut receiver: broadcast::Receiver<ServerLifecycleEvent>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Dropped {} server lifecycle events", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            if let ServerLifecycleEvent::PortReassigned { to, .. } = &event {
+                persist_server_port(&app, *to).await;
+            }
 
-    log::info!("&format!("Checking if server is already running on port {}", state.server_port)");
+            publish_lifecycle_event(&app, &event).await;
+        }
+    });
+}
 
-    // First, check if a server is already running on the target port
-    if is_server_running(state.server_port) {
-        let msg = format!("Server is already running on port {}, not starting a new one", state.server_port");
-        info!("{}", msg");
-        log::info!("&msg");
-        return Ok(()");
+async fn persist_server_port(app: &AppHandle, port: u16) {
+    match crate::config::update_server_port(app.clone(), port).await {
+        Ok(config) => *app.state::<AppState>().config.lock().unwrap() = config,
+        Err(e) => error!("Failed to save new server port {}: {}", port, e),
     }
+}
 
-    // Find the server directory - look for the Go server
-    log::debug!("""Looking for server directory...");
-    let server_dir = match find_server_directory() {
-        Ok(dir) => {
-            log::info!("&format!("Found server directory: {:?}", dir)");
-            dir
-        }
-        Err(e) => {
-            log::error!("&format!("Server directory not found: {}", e)");
-            return Err(e");
+/// Stream Go build progress and compiler output to the frontend
+pub fn forward_build_events(app: AppHandle, mut receiver: broadcast::Receiver<BuildEvent>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Dropped {} server build events", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            if let Err(e) = app.emit(SERVER_BUILD_EVENT, &event) {
+                error!("Failed to emit server build event: {}", e);
+            }
         }
-    };
-
-    info!("Starting TunnelForge Go server from directory: {:?}", server_dir");
+    });
+}
 
-    // Check if the server binary exists, if not try to build it
-    let server_binary = server_dir.join("tunnelforge-server");
-    if !server_binary.exists() {
-        log::info!(""Server binary not found, attempting to build...");
-        info!("Server binary not found, attempting to build...");
+/// Send resource threshold alerts to the frontend and the notification system
+pub fn forward_resource_alerts(app: AppHandle, mut receiver: broadcast::Receiver<ResourceAlert>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let alert = match receiver.recv().await {
+                Ok(alert) => alert,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Dropped {} server resource alerts", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            if let Err(e) = app.emit(SERVER_RESOURCE_ALERT_EVENT, &alert) {
+                error!("Failed to emit server resource alert: {}", e);
+            }
 
-        if let Err(e) = build_go_server(&server_dir) {
-            log::error!("&format!("Failed to build server: {}", e)");
-            return Err(e");
+            if let Some(notification_service) = app.try_state::<crate::notifications::NotificationService>() {
+                notification_service.show_resource_alert_notification(&alert).await;
+            }
         }
-        log::info!(""Server binary built successfully");
-    } else {
-        log::debug!("""Using existing server binary");
-    }
-
-    // Set up server command
-    let mut cmd = Command::new("./tunnelforge-server");
-    cmd.current_dir(&server_dir)
-       .env("PORT", state.server_port.to_string())
-       .env("HOST", "127.0.0.1");
+    });
+}
 
-    log::debug!(""&format!("Starting server with PORT={} HOST=127.0.0.1", state.server_port)");
+/// Save the user's choice for external servers and apply it right away
+pub async fn apply_external_server_policy(app: &AppHandle, policy: ExternalServerPolicy) -> Result<ServerStatus, String> {
+    let config = crate::config::ConfigManager::new(app)?.update_config(|config| {
+        config.external_server_policy = Some(policy);
+    })?;
+    *app.state::<AppState>().config.lock().unwrap() = config;
 
-    // Platform-specific configuration
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-    }
+    app.state::<ServerManager>().apply_external_policy(policy).await
+}
 
-    // Start the process
-    match cmd.spawn() {
-        Ok(child) => {
-            let child_id = child.id(");
-            let msg = format!("TunnelForge server started with PID: {}", child_id");
-            info!("{}", msg");
-            log::info!("&msg");
-            *server_process = Some(child");
-
-            // Wait a moment for the server to start up
-            log::debug!("""Waiting for server to initialize...");
-            thread::sleep(Duration::from_millis(3000)");
-
-            // Verify the server actually started
-            if is_server_running(state.server_port) {
-                log::info!(""Server started successfully and is responding");
-            } else {
-                log::warn!(""Server process started but not responding on expected port");
-            }
+/// Load the saved config into `AppState` and apply its server settings to the `ServerManager`
+pub fn load_server_settings(app: &AppHandle) -> Result<(), String> {
+    let config = crate::config::ConfigManager::new(app)?.load_config()?;
+    apply_server_settings(app, config);
+    Ok(())
+}
 
-            // TODO: Emit status change event to frontend - will be implemented in Phase 2
-            log::info!("&format!("Server started with PID: {}", child_id)");
+/// Apply `config` to the `ServerManager` and store it in `AppState`. Returns the settings the
+/// running server would need a restart for, and offers that restart when there are any.
+pub fn apply_server_settings(app: &AppHandle, config: crate::config::AppConfig) -> Vec<String> {
+    let server_manager = app.state::<ServerManager>();
+    server_manager.set_port(config.server_port);
+    match validate_server_host(&config.server_host) {
+        Ok(()) => server_manager.set_host(config.server_host.clone()),
+        Err(e) => error!("{}, keeping {}", e, server_manager.get_host()),
+    }
+    match config.server_options.validate() {
+        Ok(()) => server_manager.set_server_options(config.server_options.clone()),
+        Err(e) => error!("Ignoring invalid server options: {}", e),
+    }
+    server_manager.set_port_range(config.server_port_range);
+    server_manager.set_external_policy(config.external_server_policy);
+    server_manager.set_configured_executable(config.server_executable_path.clone());
+    server_manager.set_development_mode(config.use_development_server);
+    server_manager.resources().set_thresholds(config.server_resource_thresholds.clone());
+    server_manager.set_watchdog_policy(config.server_watchdog.clone());
+    server_manager.set_orphan_policy(config.orphan_server_policy);
+    let app_data_dir = app.path().app_data_dir();
+    match &app_data_dir {
+        Ok(dir) => server_manager.set_pid_file(Some(dir.join(SERVER_PID_FILE))),
+        Err(e) => log::warn!("No app data directory, orphaned servers will not be detected: {}", e),
+    }
+    if let Some(recorder) = app.try_state::<crate::sessions::SessionRecorder>() {
+        recorder.set_default_directory(app_data_dir.ok().map(|dir| dir.join(crate::sessions::RECORDINGS_DIR)));
+        recorder.set_options(config.session_recording.clone());
+    }
+    if let Some(search) = app.try_state::<crate::sessions::SessionSearch>() {
+        search.set_options(config.session_search.clone());
+    }
+    if let Some(reaper) = app.try_state::<crate::sessions::SessionReaper>() {
+        reaper.set_policy(config.session_reaper.clone());
+    }
+    if let Some(triggers) = app.try_state::<crate::sessions::SessionTriggers>() {
+        triggers.set_rules(&config.trigger_rules);
+    }
+    if let Some(launcher) = app.try_state::<crate::sessions::ProfileLauncher>() {
+        launcher.register_shortcuts(app, &config.launch_profiles);
+    }
+    server_manager.set_shutdown_options(ShutdownOptions::with_drain_timeout_secs(config.server_shutdown_timeout_secs));
 
-            log::info!(""NOTIFICATION: TunnelForge - Server started successfully");
-            Ok(())
-        }
-        Err(e) => {
-            let msg = format!("Failed to start TunnelForge server: {}. Make sure Go is installed and the server can be built.", e");
-            error!("{}", msg");
-            log::error!("&msg");
-            Err(msg)
+    *app.state::<AppState>().config.lock().unwrap() = config;
+    // The tray lists the launch profiles
+    if let Some(tray_manager) = app.try_state::<crate::ui::TrayManager>() {
+        if let Err(e) = tray_manager.refresh_menu() {
+            error!("Failed to refresh tray menu: {}", e);
         }
     }
-}
-
-pub fn stop_server_internal(state: &State<AppState>) -> Result<(), String> {
-    let mut server_process = state.server_process.lock().unwrap(");
-
-    if let Some(mut child) = server_process.take() {
-        info!("Stopping TunnelForge server (PID: {})...", child.id()");
 
-        // Try graceful shutdown first
-        match child.kill() {
-            Ok(_) => {
-                // Wait for process to exit
-                let _ = child.wait(");
-                info!("TunnelForge server stopped successfully");
-                log::info!(""NOTIFICATION: TunnelForge - Server stopped successfully");
-                Ok(())
-            }
-            Err(e) => {
-                error!("Failed to stop TunnelForge server: {}", e");
-                Err(format!("Failed to stop server: {}", e))
-            }
-        }
-    } else {
-        Ok(()) // Already stopped
+    let changed = server_manager.restart_required();
+    if !changed.is_empty() {
+        info!("Server restart needed to apply: {}", changed.join(", "));
+        let event = ServerLifecycleEvent::RestartRequired { changed: changed.clone() };
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            publish_lifecycle_event(&app, &event).await;
+        });
     }
+    changed
 }
 
-// Helper function to check if server is already running
-pub fn is_server_running(port: u16) -> bool {
-    use std::net::{TcpStream, SocketAddr};
-    use std::time::Duration;
-
-    let addr = format!("127.0.0.1:{}", port");
-    if let Ok(socket_addr) = addr.parse::<SocketAddr>() {
-        TcpStream::connect_timeout(&socket_addr, Duration::from_millis(1000)).is_ok()
-    } else {
-        false
+/// Deliver a single lifecycle event to the frontend, tray and notifications
+pub async fn publish_lifecycle_event(app: &AppHandle, event: &ServerLifecycleEvent) {
+    if let Err(e) = app.emit(SERVER_LIFECYCLE_EVENT, event) {
+        error!("Failed to emit server lifecycle event: {}", e);
     }
-}
 
-// Helper function to find the server directory
-pub fn find_server_directory() -> Result<PathBuf, String> {
-    // Get the executable path to determine the correct relative paths
-    let exe_path = std::env::current_exe()
-        .map_err(|e| format!("Failed to get executable path: {}", e))?;
-
-    let exe_dir = exe_path.parent()
-        .ok_or_else(|| "Failed to get executable directory".to_string())?;
-
-    log::debug!(""&format!("Executable directory: {:?}", exe_dir)");
-
-    let current_dir = std::env::current_dir()
-        .map_err(|e| format!("Failed to get current directory: {}", e))?;
-
-    log::debug!(""&format!("Current directory: {:?}", current_dir)");
-
-    // Get the home directory as a fallback
-    let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/Users/")");
-
-    // Try different possible locations for the server
-    let possible_paths = vec![
-        // When running from app bundle, use executable-relative paths
-        exe_dir.join("../../../server"),
-        exe_dir.join("../../server"),
-        exe_dir.join("../server"),
-
-        // Development paths (when running from project)
-        current_dir.join("../server"),
-        current_dir.join("server"),
-        current_dir.join("../../server"),
-
-        // Common project locations
-        home_dir.join("Github/tunnelforge/server"),
-        home_dir.join("Projects/tunnelforge/server"),
-        home_dir.join("tunnelforge/server"),
-
-        // System-wide locations
-        PathBuf::from("/usr/local/share/tunnelforge/server"),
-        PathBuf::from("/opt/tunnelforge/server"),
-
-        // Fallback: assume we can use the web server's built executable
-        home_dir.join("Github/tunnelforge/web/native"),
-    ];
-
-    for path in &possible_paths {
-        log::debug!(""&format!("Checking server path: {:?}", path)");
-        if path.exists() {
-            // Check for Go server
-            if path.join("go.mod").exists() {
-                log::info!("&format!("Found Go server directory with go.mod at: {:?}", path)");
-                return Ok(path.clone()");
-            }
-            // Check for pre-built Bun executable
-            else if path.join("tunnelforge").exists() {
-                log::info!("&format!("Found pre-built server executable at: {:?}", path)");
-                return Ok(path.clone()");
-            }
-            else {
-                log::debug!(""&format!("Directory exists but no server found: {:?}", path)");
-            }
-        } else {
-            log::debug!(""&format!("Directory does not exist: {:?}", path)");
+    if let Some(tray_manager) = app.try_state::<crate::ui::TrayManager>() {
+        if let Err(e) = tray_manager.apply_lifecycle_event(event) {
+            error!("Failed to update tray for server lifecycle event: {}", e);
         }
     }
 
-    let error_msg = format!(
-        "TunnelForge server directory not found. Searched paths: {}. Make sure the server directory exists with go.mod or a pre-built executable.",
-        possible_paths.iter().map(|p| format!("{:?}", p)).collect::<Vec<_>>().join(", ")
-    ");
-    log::error!("&error_msg");
-    Err(error_msg)
+    if let Some(notification_service) = app.try_state::<crate::notifications::NotificationService>() {
+        notification_service.show_server_lifecycle_notification(event).await;
+    }
 }
 
-// Helper function to build the Go server
-pub fn build_go_server(server_dir: &Path) -> Result<(), String> {
-    let msg = format!("Building Go server in directory: {:?}", server_dir");
-    info!("{}", msg");
-    log::info!("&msg");
-
-    // Check if go.mod exists
-    if !server_dir.join("go.mod").exists() {
-        let error = "go.mod not found in server directory";
-        log::error!("error");
-        return Err(error.to_string()");
-    }
+/// Stop the managed server using the drain timeout from the current config
+pub async fn stop_managed_server(app: &AppHandle) -> Result<Option<ShutdownReport>, String> {
+    let drain_timeout_secs = app.state::<AppState>().config.lock().unwrap().server_shutdown_timeout_secs;
 
-    // Check if cmd/server/main.go exists
-    if !server_dir.join("cmd/server/main.go").exists() {
-        let error = "cmd/server/main.go not found in server directory";
-        log::error!("error");
-        return Err(error.to_string()");
-    }
+    let server_manager = app.state::<ServerManager>();
+    server_manager.set_shutdown_options(ShutdownOptions::with_drain_timeout_secs(drain_timeout_secs));
+    server_manager.stop().await
+}
 
-    log::debug!("""Running: go build -o tunnelforge-server cmd/server/main.go");
-
-    let output = Command::new("go")
-        .args(&["build", "-o", "tunnelforge-server", "cmd/server/main.go"])
-        .current_dir(server_dir)
-        .output()
-        .map_err(|e| {
-            let error = format!("Failed to run go build: {}", e");
-            log::error!("&error");
-            error
-        })?;
-
-    if output.status.success() {
-        let msg = "Go server built successfully";
-        info!("{}", msg");
-        log::info!("msg");
-        Ok(())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr");
-        let error = format!("Go server build failed with exit code: {:?}. Error: {}", output.status.code(), stderr");
-        log::error!("&error");
-        Err(error)
+/// Restart the managed server, letting the old one drain first
+pub async fn restart_managed_server(app: &AppHandle) -> Result<ServerStartState, String> {
+    if let Some(report) = stop_managed_server(app).await? {
+        info!("Server stopped for restart ({:?})", report.stage);
     }
+    app.state::<ServerManager>().launch().await
 }
 
 // Tauri commands for server management
 #[tauri::command]
-pub async fn get_server_status(state: State<'_, AppState>) -> Result<ServerStatus, String> {
-    let server_process = state.server_process.lock().unwrap(");
-
-    let status = match &*server_process {
-        Some(child) => {
-            // Check if the process is still alive
-            let running = is_server_running(state.server_port");
-            log::debug!(""&format!("Server process PID {} running: {}", child.id(), running)");
-
-            ServerStatus {
-                running,
-                port: state.server_port,
-                pid: Some(child.id()),
-            }
-        }
-        None => {
-            // Check if server is running externally
-            let running = is_server_running(state.server_port");
-            if running {
-                log::info!(""Server running externally (not managed by this app)");
-            }
+pub async fn get_server_status(server_manager: State<'_, ServerManager>) -> Result<ServerStatus, String> {
+    Ok(server_manager.status().await)
+}
 
-            ServerStatus {
-                running,
-                port: state.server_port,
-                pid: None,
-            }
-        },
-    };
+#[tauri::command]
+pub async fn start_server(server_manager: State<'_, ServerManager>) -> Result<ServerStartState, String> {
+    log::info!("Starting server...");
+    server_manager.launch().await
+}
 
-    Ok(status)
+#[tauri::command]
+pub async fn stop_server(app: AppHandle) -> Result<Option<ShutdownReport>, String> {
+    log::info!("Stopping server...");
+    stop_managed_server(&app).await
 }
 
 #[tauri::command]
-pub async fn start_server(state: State<'_, AppState>, app: AppHandle) -> Result<(), String> {
-    log::info!(""Starting server...");
-    start_server_internal(&state, &app)
+pub async fn restart_server(app: AppHandle) -> Result<ServerStartState, String> {
+    log::info!("Restarting server...");
+    restart_managed_server(&app).await.map_err(|e| {
+        log::error!("Failed to restart server: {}", e);
+        e
+    })
 }
 
 #[tauri::command]
-pub async fn stop_server(state: State<'_, AppState>) -> Result<(), String> {
-    log::info!(""Stopping server...");
-    stop_server_internal(&state)
+pub async fn get_external_server(server_manager: State<'_, ServerManager>) -> Result<Option<ExternalServer>, String> {
+    Ok(server_manager.discover_external().await)
 }
 
 #[tauri::command]
-pub async fn restart_server(state: State<'_, AppState>, app: AppHandle) -> Result<(), String> {
-    log::info!(""Restarting server...");
+pub async fn set_external_server_policy(app: AppHandle, policy: ExternalServerPolicy) -> Result<ServerStatus, String> {
+    apply_external_server_policy(&app, policy).await
+}
 
-    // Stop current server
-    if let Err(e) = stop_server_internal(&state) {
-        log::error!("&format!("Failed to stop server: {}", e)");
-        return Err(e");
-    }
+/// Re-run binary resolution and report which candidate wins and why the others were rejected
+#[tauri::command]
+pub async fn resolve_server_binary(server_manager: State<'_, ServerManager>) -> Result<ResolutionReport, String> {
+    server_manager.resolution_report().await
+}
 
-    // Wait a moment
-    log::debug!("""Waiting for server to shutdown...");
-    thread::sleep(Duration::from_millis(1000)");
+/// Build the development server; progress arrives as `server-build` events
+#[tauri::command]
+pub async fn build_server(server_manager: State<'_, ServerManager>, force: Option<bool>) -> Result<BuildOutcome, String> {
+    server_manager.build_server(force.unwrap_or(false)).await
+}
 
-    // Start new server
-    if let Err(e) = start_server_internal(&state, &app) {
-        log::error!("&format!("Failed to start server: {}", e)");
-        return Err(e");
-    }
+#[tauri::command]
+pub async fn cancel_server_build(server_manager: State<'_, ServerManager>) -> Result<bool, String> {
+    Ok(server_manager.builder().cancel())
+}
 
-    log::info!(""Server restarted successfully");
-    log::info!(""NOTIFICATION: TunnelForge - Server restarted successfully");
-    Ok(())
+/// Resource samples of the managed server, oldest first
+#[tauri::command]
+pub async fn get_server_resource_history(
+    server_manager: State<'_, ServerManager>,
+    limit: Option<usize>,
+) -> Result<Vec<ResourceSample>, String> {
+    Ok(server_manager.resources().history(limit))
+}
+
+/// Settings changed since the server started that only take effect after a restart
+#[tauri::command]
+pub async fn get_server_restart_required(server_manager: State<'_, ServerManager>) -> Result<Vec<String>, String> {
+    Ok(server_manager.restart_required())
 }
 
 #[tauri::command]
-pub async fn get_server_url(state: State<'_, AppState>) -> Result<String, String> {
-    let url = format!("http://localhost:{}", state.server_port");
-    log::debug!(""&format!("Returning server URL: {}", url)");
+pub async fn get_server_url(server_manager: State<'_, ServerManager>) -> Result<String, String> {
+    let url = format!("http://localhost:{}", server_manager.get_port());
+    log::debug!("Returning server URL: {}", url);
     Ok(url)
 }
 
 // Tauri command to open web interface in external browser
 #[tauri::command]
-pub async fn open_web_interface(state: State<'_, AppState>) -> Result<(), String> {
-    let url = format!("http://localhost:{}", state.server_port");
+pub async fn open_web_interface(server_manager: State<'_, ServerManager>) -> Result<(), String> {
+    let url = format!("http://localhost:{}", server_manager.get_port());
     log::info!("&format!("Opening web interface at: {}", url)");
     
     // Use the open_external_url command that we already have in main.rs
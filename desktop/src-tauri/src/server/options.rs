@@ -0,0 +1,252 @@
+// Go server runtime options
+// User-configurable auth mode, log level, environment, arguments and data directory, applied at spawn
+
+use std::collections::BTreeMap;
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+static ENV_NAME: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap());
+
+/// Variables the desktop app sets itself; users change them through the dedicated options instead
+const RESERVED_ENV: &[&str] = &[
+    "PORT",
+    "HOST",
+    "TUNNELFORGE_INSTANCE_ID",
+    "ENABLE_AUTH",
+    "AUTH_REQUIRED",
+    "ALLOW_LOCAL_BYPASS",
+    "ENABLE_REQUEST_LOG",
+    "PERSISTENCE_DIR",
+    "VAPID_KEY_PATH",
+    "CLOUDFLARE_CONFIG_DIR",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerAuthMode {
+    /// No authentication at all, the server's own default
+    #[default]
+    Disabled,
+    /// Remote clients must authenticate; the desktop app on this machine may bypass it
+    LocalBypass,
+    /// Every client must authenticate, including this machine
+    Required,
+}
+
+/// The Go server logs through the unlevelled standard logger, so the level switches its per-request
+/// log (`ENABLE_REQUEST_LOG`) and the app drops captured lines below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerLogLevel {
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl ServerLogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServerLogLevel::Debug => "debug",
+            ServerLogLevel::Info => "info",
+            ServerLogLevel::Warn => "warn",
+            ServerLogLevel::Error => "error",
+        }
+    }
+
+    /// Level of a captured line as named by `parse_server_log_line`
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "debug" => ServerLogLevel::Debug,
+            "warn" => ServerLogLevel::Warn,
+            "error" => ServerLogLevel::Error,
+            _ => ServerLogLevel::Info,
+        }
+    }
+}
+
+/// Everything passed to the server process besides its port, host and instance id.
+/// Changing any of these only takes effect after a restart.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerOptions {
+    pub auth_mode: ServerAuthMode,
+    pub log_level: ServerLogLevel,
+    /// Extra environment variables; names the app sets itself are rejected
+    pub env: BTreeMap<String, String>,
+    pub args: Vec<String>,
+    /// Root for persisted sessions, push keys and tunnel config, passed as `PERSISTENCE_DIR`,
+    /// `VAPID_KEY_PATH` and `CLOUDFLARE_CONFIG_DIR`; the server's defaults when `None`
+    pub data_dir: Option<PathBuf>,
+}
+
+impl ServerOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in &self.env {
+            if !ENV_NAME.is_match(name) {
+                return Err(format!("Invalid environment variable name {:?}", name));
+            }
+            if RESERVED_ENV.contains(&name.as_str()) {
+                return Err(format!("{} is set by TunnelForge; use the server options instead", name));
+            }
+            if value.contains('\0') {
+                return Err(format!("Environment variable {} contains a NUL byte", name));
+            }
+        }
+
+        for arg in &self.args {
+            if arg.is_empty() || arg.contains('\0') {
+                return Err(format!("Invalid server argument {:?}", arg));
+            }
+        }
+
+        if let Some(data_dir) = &self.data_dir {
+            if !data_dir.is_absolute() {
+                return Err(format!("Server data directory must be an absolute path, got {:?}", data_dir));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Environment for the server process, in the variables the Go server reads
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        let (enable_auth, local_bypass) = match self.auth_mode {
+            ServerAuthMode::Disabled => (false, true),
+            ServerAuthMode::LocalBypass => (true, true),
+            ServerAuthMode::Required => (true, false),
+        };
+
+        let mut vars = vec![
+            ("ENABLE_AUTH".to_string(), enable_auth.to_string()),
+            ("AUTH_REQUIRED".to_string(), enable_auth.to_string()),
+            ("ALLOW_LOCAL_BYPASS".to_string(), local_bypass.to_string()),
+            // Per-request lines are info-level chatter; warn and error turn them off
+            ("ENABLE_REQUEST_LOG".to_string(), (self.log_level <= ServerLogLevel::Info).to_string()),
+        ];
+
+        if let Some(data_dir) = &self.data_dir {
+            let path = |sub: &str| data_dir.join(sub).to_string_lossy().to_string();
+            vars.push(("PERSISTENCE_DIR".to_string(), path("sessions")));
+            vars.push(("VAPID_KEY_PATH".to_string(), path("keys")));
+            vars.push(("CLOUDFLARE_CONFIG_DIR".to_string(), path("cloudflare")));
+        }
+
+        vars.extend(self.env.iter().map(|(name, value)| (name.clone(), value.clone())));
+        vars
+    }
+}
+
+/// The Go server joins host and port with a plain `:`, so only IPv4 addresses and `localhost` work
+pub fn validate_server_host(host: &str) -> Result<(), String> {
+    if host == "localhost" || host.parse::<Ipv4Addr>().is_ok() {
+        Ok(())
+    } else {
+        Err(format!("Invalid server host {:?}; expected an IPv4 address or localhost", host))
+    }
+}
+
+/// Address the app uses to reach a server bound to `host`
+pub fn connect_host(host: &str) -> String {
+    match host {
+        "localhost" | "0.0.0.0" => "127.0.0.1".to_string(),
+        host => host.to_string(),
+    }
+}
+
+/// Settings a server process was started with, compared against the config to decide on restarts
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnSettings {
+    pub host: String,
+    pub port: u16,
+    pub options: ServerOptions,
+}
+
+impl SpawnSettings {
+    /// Names of the settings that differ from `running`, i.e. why a restart is needed
+    pub fn changes_from(&self, running: &SpawnSettings) -> Vec<String> {
+        let mut changed = Vec::new();
+        let mut check = |name: &str, differs: bool| {
+            if differs {
+                changed.push(name.to_string());
+            }
+        };
+
+        check("host", self.host != running.host);
+        check("port", self.port != running.port);
+        check("auth_mode", self.options.auth_mode != running.options.auth_mode);
+        check("log_level", self.options.log_level != running.options.log_level);
+        check("env", self.options.env != running.options.env);
+        check("args", self.options.args != running.options.args);
+        check("data_dir", self.options.data_dir != running.options.data_dir);
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation() {
+        let mut options = ServerOptions::default();
+        assert!(options.validate().is_ok());
+
+        options.env.insert("GOMAXPROCS".to_string(), "4".to_string());
+        assert!(options.validate().is_ok());
+
+        options.env.insert("PORT".to_string(), "9999".to_string());
+        assert!(options.validate().unwrap_err().contains("set by TunnelForge"));
+        options.env.remove("PORT");
+
+        options.env.insert("1BAD".to_string(), String::new());
+        assert!(options.validate().is_err());
+        options.env.remove("1BAD");
+
+        options.data_dir = Some(PathBuf::from("relative/dir"));
+        assert!(options.validate().is_err());
+
+        assert!(validate_server_host("0.0.0.0").is_ok());
+        assert!(validate_server_host("localhost").is_ok());
+        assert!(validate_server_host("::1").is_err());
+        assert!(validate_server_host("127.0.0.1; rm -rf").is_err());
+        assert_eq!(connect_host("0.0.0.0"), "127.0.0.1");
+    }
+
+    #[test]
+    fn test_env_vars_follow_auth_mode_and_data_dir() {
+        let options = ServerOptions {
+            auth_mode: ServerAuthMode::Required,
+            data_dir: Some(PathBuf::from("/srv/tunnelforge")),
+            ..Default::default()
+        };
+        let vars: BTreeMap<_, _> = options.env_vars().into_iter().collect();
+
+        assert_eq!(vars["ENABLE_AUTH"], "true");
+        assert_eq!(vars["ALLOW_LOCAL_BYPASS"], "false");
+        assert_eq!(vars["ENABLE_REQUEST_LOG"], "true");
+        assert_eq!(vars["PERSISTENCE_DIR"], "/srv/tunnelforge/sessions");
+        // Only variables the Go config actually reads
+        assert!(!vars.contains_key("LOG_LEVEL"));
+        assert!(!vars.contains_key("TUNNELFORGE_DATA_DIR"));
+    }
+
+    #[test]
+    fn test_reports_only_changed_settings() {
+        let running = SpawnSettings {
+            host: "127.0.0.1".to_string(),
+            port: 4021,
+            options: ServerOptions::default(),
+        };
+        assert!(running.changes_from(&running).is_empty());
+
+        let mut desired = running.clone();
+        desired.options.log_level = ServerLogLevel::Debug;
+        desired.options.args.push("--verbose".to_string());
+        assert_eq!(desired.changes_from(&running), vec!["log_level", "args"]);
+    }
+}
@@ -0,0 +1,174 @@
+// Server pid file
+// Remembers the running server across desktop crashes so the next launch can find and handle the orphan
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use log::warn;
+
+use super::health::ServerIdentity;
+
+/// File name of the pid file inside the app data directory
+pub const SERVER_PID_FILE: &str = "server.pid.json";
+
+/// What to do with a server left running by a previous desktop session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanPolicy {
+    /// Keep it and its sessions, and supervise it like our own child
+    #[default]
+    Adopt,
+    /// Stop it gracefully and start a fresh server
+    Terminate,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerPidFile {
+    pub pid: u32,
+    pub port: u16,
+    pub binary: PathBuf,
+    pub instance_id: String,
+    pub started_at: DateTime<Utc>,
+}
+
+impl ServerPidFile {
+    pub fn read(path: &Path) -> Result<Option<Self>, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read pid file {:?}: {}", path, e)),
+        };
+
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| format!("Corrupt pid file {:?}: {}", path, e))
+    }
+
+    /// Write atomically so a crash mid-write never leaves a half-written file behind
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize pid file: {}", e))?;
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, contents).map_err(|e| format!("Failed to write {:?}: {}", temp_path, e))?;
+        fs::rename(&temp_path, path).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    pub fn remove(path: &Path) {
+        if let Err(e) = fs::remove_file(path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Failed to remove pid file {:?}: {}", path, e);
+            }
+        }
+    }
+
+    /// Check that the recorded server is still the process on the recorded port.
+    /// `identity` is what `/api/health` on `self.port` answered, `listener_pid` who owns the socket.
+    pub fn verify(
+        &self,
+        process_alive: bool,
+        executable: Option<&Path>,
+        identity: Option<&ServerIdentity>,
+        listener_pid: Option<u32>,
+    ) -> Result<(), String> {
+        if !process_alive {
+            return Err(format!("process {} is no longer running", self.pid));
+        }
+
+        if let Some(executable) = executable {
+            if !same_executable(executable, &self.binary) {
+                return Err(format!("process {} is {:?}, not {:?}", self.pid, executable, self.binary));
+            }
+        }
+
+        let identity = identity.ok_or_else(|| format!("no TunnelForge server answers on port {}", self.port))?;
+        if identity.instance_id.as_deref() != Some(self.instance_id.as_str()) {
+            return Err(format!("the server on port {} belongs to another instance", self.port));
+        }
+
+        match listener_pid {
+            Some(listener_pid) if listener_pid != self.pid => Err(format!(
+                "port {} is owned by process {}, not {}",
+                self.port, listener_pid, self.pid
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn same_executable(running: &Path, recorded: &Path) -> bool {
+    // A binary replaced by a rebuild shows up as "<path> (deleted)" in /proc/<pid>/exe
+    let running = running.to_string_lossy();
+    let running = Path::new(running.strip_suffix(" (deleted)").unwrap_or(&running));
+
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    running == recorded || canonical(running) == canonical(recorded)
+}
+
+/// Path of the executable running as `pid`, where the platform exposes it
+#[cfg(target_os = "linux")]
+pub fn process_executable(pid: u32) -> Option<PathBuf> {
+    fs::read_link(format!("/proc/{}/exe", pid)).ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn process_executable(_pid: u32) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> ServerPidFile {
+        ServerPidFile {
+            pid: 4242,
+            port: 4021,
+            binary: PathBuf::from("/opt/tunnelforge/tunnelforge-server"),
+            instance_id: "abc123".to_string(),
+            started_at: Utc::now(),
+        }
+    }
+
+    fn identity(instance_id: &str) -> ServerIdentity {
+        ServerIdentity {
+            name: Some("tunnelforge".to_string()),
+            version: Some("1.0.0".to_string()),
+            instance_id: Some(instance_id.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_round_trips_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data").join(SERVER_PID_FILE);
+        assert_eq!(ServerPidFile::read(&path).unwrap(), None);
+
+        let pid_file = record();
+        pid_file.write(&path).unwrap();
+        assert_eq!(ServerPidFile::read(&path).unwrap(), Some(pid_file));
+
+        ServerPidFile::remove(&path);
+        assert_eq!(ServerPidFile::read(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn test_verifies_orphan() {
+        let pid_file = record();
+        let rebuilt = Path::new("/opt/tunnelforge/tunnelforge-server (deleted)");
+
+        assert!(pid_file.verify(true, Some(rebuilt), Some(&identity("abc123")), Some(4242)).is_ok());
+        assert!(pid_file.verify(true, None, Some(&identity("abc123")), None).is_ok());
+
+        assert!(pid_file.verify(false, None, Some(&identity("abc123")), None).is_err());
+        assert!(pid_file.verify(true, Some(Path::new("/usr/bin/python3")), Some(&identity("abc123")), None).is_err());
+        assert!(pid_file.verify(true, None, Some(&identity("other")), None).is_err());
+        assert!(pid_file.verify(true, None, None, None).is_err());
+        assert!(pid_file.verify(true, None, Some(&identity("abc123")), Some(1)).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+// Port selection for the Go server
+// Detects foreign listeners on the configured port and picks a free replacement
+
+use std::fmt;
+use std::net::TcpListener;
+use serde::{Deserialize, Serialize};
+use log::debug;
+
+use super::health::{HealthChecker, ServerIdentity};
+
+/// Inclusive range of ports the server may move to when its configured port is taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl Default for PortRange {
+    fn default() -> Self {
+        Self { start: 4021, end: 4121 }
+    }
+}
+
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// Who, if anyone, is listening on a port
+#[derive(Debug, Clone, PartialEq)]
+pub enum PortOccupant {
+    Free,
+    TunnelForge(ServerIdentity),
+    /// Something else holds the port; the string says why it was not accepted
+    Foreign(String),
+}
+
+pub async fn probe_port(host: &str, port: u16) -> PortOccupant {
+    let health_checker = HealthChecker::new(port, host.to_string());
+    if !health_checker.check_tcp_connection().await {
+        return PortOccupant::Free;
+    }
+
+    match health_checker.fetch_identity().await {
+        Ok(identity) if identity.is_tunnelforge() => PortOccupant::TunnelForge(identity),
+        Ok(identity) => PortOccupant::Foreign(format!(
+            "port {} is served by {}",
+            port,
+            identity.name.unwrap_or_else(|| "an unnamed service".to_string())
+        )),
+        Err(e) => PortOccupant::Foreign(e),
+    }
+}
+
+/// True when nothing listens on `port` and we are allowed to bind it
+pub fn is_port_free(host: &str, port: u16) -> bool {
+    TcpListener::bind((host, port)).is_ok()
+}
+
+/// First bindable port in `range`, skipping `exclude`
+pub fn find_free_port(host: &str, range: &PortRange, exclude: u16) -> Option<u16> {
+    (range.start..=range.end)
+        .filter(|port| *port != exclude)
+        .find(|port| {
+            let free = is_port_free(host, *port);
+            if !free {
+                debug!("Port {} is taken", port);
+            }
+            free
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::thread;
+
+    fn serve_once(body: &'static str) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        thread::spawn(move || {
+            // The TCP probe connects first, then the HTTP request follows
+            for stream in listener.incoming().take(2) {
+                let mut stream = stream.unwrap();
+                let mut request = [0u8; 1024];
+                if stream.read(&mut request).unwrap_or(0) == 0 {
+                    continue;
+                }
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        port
+    }
+
+    #[tokio::test]
+    async fn test_recognizes_tunnelforge_listener() {
+        let port = serve_once(r#"{"status":"ok","sessions":0,"uptime":"1s","name":"tunnelforge","version":"1.0.0","instanceId":"abc"}"#);
+
+        match probe_port("127.0.0.1", port).await {
+            PortOccupant::TunnelForge(identity) => assert_eq!(identity.instance_id.as_deref(), Some("abc")),
+            other => panic!("expected TunnelForge, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejects_foreign_listener() {
+        let port = serve_once(r#"{"status":"UP"}"#);
+
+        assert!(matches!(probe_port("127.0.0.1", port).await, PortOccupant::Foreign(_)));
+    }
+
+    #[test]
+    fn test_find_free_port_skips_taken_ports() {
+        let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+        let range = PortRange { start: port, end: port };
+
+        assert!(!is_port_free("127.0.0.1", port));
+        assert_eq!(find_free_port("127.0.0.1", &range, 0), None);
+    }
+}
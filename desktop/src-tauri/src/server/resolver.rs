@@ -0,0 +1,350 @@
+// Server binary resolution
+// Picks the tunnelforge-server executable from an explicit precedence list and checks its version
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use log::{info, debug};
+
+/// Environment variable that overrides every other candidate
+pub const SERVER_ENV_OVERRIDE: &str = "TUNNELFORGE_SERVER";
+
+#[cfg(windows)]
+pub const SERVER_BINARY_NAME: &str = "tunnelforge-server.exe";
+#[cfg(not(windows))]
+pub const SERVER_BINARY_NAME: &str = "tunnelforge-server";
+
+/// Oldest server release this desktop app can drive
+const MIN_SERVER_VERSION: (u64, u64, u64) = (1, 0, 0);
+const VERSION_CHECK_TIMEOUT_MS: u64 = 3000;
+
+static VERSION_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d+)\.(\d+)\.(\d+)").unwrap());
+
+/// Where a candidate came from, in order of precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateSource {
+    EnvOverride,
+    ConfiguredPath,
+    BundledSidecar,
+    SystemInstall,
+    /// A Go source checkout next to the app, used during development
+    DevelopmentTree,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerCandidate {
+    pub source: CandidateSource,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RejectedCandidate {
+    pub source: CandidateSource,
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectedServer {
+    pub source: CandidateSource,
+    pub path: PathBuf,
+    pub version: String,
+}
+
+/// Outcome of a resolution: the winner, if any, and why every earlier candidate lost
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolutionReport {
+    pub selected: Option<SelectedServer>,
+    pub rejected: Vec<RejectedCandidate>,
+}
+
+impl ResolutionReport {
+    pub fn into_result(self) -> Result<SelectedServer, String> {
+        match self.selected {
+            Some(selected) => Ok(selected),
+            None if self.rejected.is_empty() => Err("No TunnelForge server candidates to check".to_string()),
+            None => Err(format!(
+                "No usable TunnelForge server found. {}",
+                self.rejected.iter()
+                    .map(|r| format!("{:?} {}: {}", r.source, r.path.display(), r.reason))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            )),
+        }
+    }
+}
+
+type VersionProbe = Box<dyn Fn(&Path) -> Result<String, String> + Send + Sync>;
+
+pub struct ServerResolver {
+    candidates: Vec<ServerCandidate>,
+    probe: VersionProbe,
+}
+
+impl ServerResolver {
+    /// A resolver with no candidates that checks versions by running `--version`
+    pub fn new() -> Self {
+        Self {
+            candidates: Vec::new(),
+            probe: Box::new(probe_version),
+        }
+    }
+
+    /// The standard precedence: env override, configured path, bundled sidecar,
+    /// system install locations, then development checkouts
+    pub fn standard(configured_path: Option<&Path>) -> Self {
+        let mut resolver = Self::new();
+
+        if let Some(path) = std::env::var_os(SERVER_ENV_OVERRIDE).filter(|v| !v.is_empty()) {
+            resolver = resolver.with_candidate(CandidateSource::EnvOverride, PathBuf::from(path));
+        }
+
+        if let Some(path) = configured_path {
+            resolver = resolver.with_candidate(CandidateSource::ConfiguredPath, path.to_path_buf());
+        }
+
+        let exe_dir = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf));
+        if let Some(exe_dir) = &exe_dir {
+            resolver = resolver.with_candidate(CandidateSource::BundledSidecar, exe_dir.join(SERVER_BINARY_NAME));
+            // macOS app bundles keep resources beside Contents/MacOS
+            resolver = resolver.with_candidate(CandidateSource::BundledSidecar, exe_dir.join("../Resources").join(SERVER_BINARY_NAME));
+        }
+
+        for dir in system_install_dirs() {
+            resolver = resolver.with_candidate(CandidateSource::SystemInstall, dir.join(SERVER_BINARY_NAME));
+        }
+
+        let mut dev_dirs = Vec::new();
+        if let Some(exe_dir) = &exe_dir {
+            // target/{debug,release} inside desktop/src-tauri
+            dev_dirs.push(exe_dir.join("../../../../server"));
+        }
+        if let Ok(current_dir) = std::env::current_dir() {
+            dev_dirs.push(current_dir.join("../../server"));
+            dev_dirs.push(current_dir.join("../server"));
+            dev_dirs.push(current_dir.join("server"));
+        }
+        for dir in dev_dirs {
+            resolver = resolver.with_candidate(CandidateSource::DevelopmentTree, dir.join(SERVER_BINARY_NAME));
+        }
+
+        resolver
+    }
+
+    pub fn with_candidate(mut self, source: CandidateSource, path: PathBuf) -> Self {
+        self.candidates.push(ServerCandidate { source, path });
+        self
+    }
+
+    /// Replace the `--version` check, e.g. in tests
+    pub fn with_probe<F>(mut self, probe: F) -> Self
+    where
+        F: Fn(&Path) -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.probe = Box::new(probe);
+        self
+    }
+
+    pub fn candidates(&self) -> &[ServerCandidate] {
+        &self.candidates
+    }
+
+    /// The first development checkout that has Go sources, built or not
+    pub fn development_source_dir(&self) -> Option<PathBuf> {
+        self.candidates.iter()
+            .filter(|c| c.source == CandidateSource::DevelopmentTree)
+            .filter_map(|c| c.path.parent())
+            .find(|dir| dir.join("go.mod").exists())
+            .map(Path::to_path_buf)
+    }
+
+    /// Walk the candidates in order and stop at the first compatible one
+    pub fn resolve(&self) -> ResolutionReport {
+        let mut rejected = Vec::new();
+
+        for candidate in &self.candidates {
+            match self.check(&candidate.path) {
+                Ok(version) => {
+                    info!("Using {:?} server {} (version {})", candidate.source, candidate.path.display(), version);
+                    return ResolutionReport {
+                        selected: Some(SelectedServer {
+                            source: candidate.source,
+                            path: candidate.path.clone(),
+                            version,
+                        }),
+                        rejected,
+                    };
+                }
+                Err(reason) => {
+                    debug!("Rejected {:?} server {}: {}", candidate.source, candidate.path.display(), reason);
+                    rejected.push(RejectedCandidate {
+                        source: candidate.source,
+                        path: candidate.path.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+
+        ResolutionReport { selected: None, rejected }
+    }
+
+    fn check(&self, path: &Path) -> Result<String, String> {
+        if !path.is_file() {
+            return Err("not found".to_string());
+        }
+
+        let version = (self.probe)(path)?;
+        if !is_compatible_version(&version) {
+            return Err(format!(
+                "version {} is not compatible with TunnelForge Desktop {}",
+                version,
+                env!("CARGO_PKG_VERSION")
+            ));
+        }
+        Ok(version)
+    }
+}
+
+impl Default for ServerResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn system_install_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![
+        PathBuf::from("/usr/local/bin"),
+        PathBuf::from("/usr/bin"),
+        PathBuf::from("/opt/tunnelforge/bin"),
+    ];
+
+    if cfg!(target_os = "macos") {
+        dirs.push(PathBuf::from("/opt/homebrew/bin"));
+    }
+
+    #[cfg(windows)]
+    if let Some(program_files) = std::env::var_os("ProgramFiles") {
+        dirs.push(PathBuf::from(program_files).join("TunnelForge"));
+    }
+
+    dirs
+}
+
+/// Run `<path> --version` and return the version number it prints.
+/// Blocks for up to `VERSION_CHECK_TIMEOUT_MS`; async code resolves through `ServerManager::resolution_report`.
+pub fn probe_version(path: &Path) -> Result<String, String> {
+    let mut child = Command::new(path)
+        .arg("--version")
+        // A server too old to know --version would start serving; keep it off our port
+        .env("PORT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to run: {}", e))?;
+
+    let deadline = Instant::now() + Duration::from_millis(VERSION_CHECK_TIMEOUT_MS);
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(50)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err("does not support --version".to_string());
+            }
+            Err(e) => return Err(format!("failed to run: {}", e)),
+        }
+    }
+
+    let output = child.wait_with_output().map_err(|e| format!("failed to read --version output: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_version(&stdout).ok_or_else(|| format!("unrecognised --version output: {:?}", stdout.trim()))
+}
+
+fn parse_version(output: &str) -> Option<String> {
+    VERSION_PATTERN.find(output).map(|m| m.as_str().to_string())
+}
+
+/// Same major version as the desktop app and no older than `MIN_SERVER_VERSION`
+pub fn is_compatible_version(version: &str) -> bool {
+    let parse = |v: &str| -> Option<(u64, u64, u64)> {
+        let captures = VERSION_PATTERN.captures(v)?;
+        Some((captures[1].parse().ok()?, captures[2].parse().ok()?, captures[3].parse().ok()?))
+    };
+
+    match (parse(version), parse(env!("CARGO_PKG_VERSION"))) {
+        (Some(server), Some(desktop)) => server.0 == desktop.0 && server >= MIN_SERVER_VERSION,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(dir: &Path, name: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, b"").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_precedence_and_rejection_reasons() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = touch(dir.path(), "old-server");
+        let good = touch(dir.path(), "good-server");
+        let later = touch(dir.path(), "later-server");
+
+        let report = ServerResolver::new()
+            .with_candidate(CandidateSource::EnvOverride, dir.path().join("missing"))
+            .with_candidate(CandidateSource::ConfiguredPath, old.clone())
+            .with_candidate(CandidateSource::BundledSidecar, good.clone())
+            .with_candidate(CandidateSource::SystemInstall, later)
+            .with_probe(|path| {
+                if path.ends_with("old-server") {
+                    Ok("0.9.0".to_string())
+                } else {
+                    Ok("1.2.0".to_string())
+                }
+            })
+            .resolve();
+
+        let selected = report.selected.unwrap();
+        assert_eq!(selected.source, CandidateSource::BundledSidecar);
+        assert_eq!(selected.path, good);
+
+        assert_eq!(report.rejected.len(), 2);
+        assert_eq!(report.rejected[0].reason, "not found");
+        assert_eq!(report.rejected[1].path, old);
+        assert!(report.rejected[1].reason.contains("not compatible"));
+    }
+
+    #[test]
+    fn test_version_parsing_and_compatibility() {
+        assert_eq!(parse_version("tunnelforge-server 1.4.2\n").as_deref(), Some("1.4.2"));
+        assert_eq!(parse_version("usage: tunnelforge-server"), None);
+
+        assert!(is_compatible_version("1.0.0"));
+        assert!(!is_compatible_version("0.9.9"));
+        assert!(!is_compatible_version("2.0.0"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_probe_runs_version_flag() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("fake-server");
+        std::fs::write(&script, "#!/bin/sh\n[ \"$1\" = --version ] && echo \"tunnelforge-server 1.0.3\"\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(probe_version(&script).unwrap(), "1.0.3");
+    }
+}
@@ -0,0 +1,417 @@
+// Go server resource monitoring
+// Samples CPU, memory, threads and open files of the managed child and keeps a rolling history
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use log::warn;
+
+use super::health::HealthChecker;
+
+/// Name of the Tauri event carrying each `ResourceAlert` to the frontend
+pub const SERVER_RESOURCE_ALERT_EVENT: &str = "server-resource-alert";
+
+pub const RESOURCE_SAMPLE_INTERVAL_SECS: u64 = 5;
+/// One hour of samples at the default interval
+const RESOURCE_HISTORY_CAPACITY: usize = 720;
+/// A metric must drop below this fraction of its threshold before it can alert again
+const ALERT_CLEAR_RATIO: f64 = 0.9;
+/// CPU spikes during startup and bursts of output are normal; only sustained load alerts
+const CPU_ALERT_SAMPLES: u32 = 3;
+
+/// One point in the resource history. Process fields are `None` where `/proc` is unavailable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceSample {
+    pub timestamp: DateTime<Utc>,
+    pub pid: u32,
+    pub cpu_percent: Option<f64>,
+    pub rss_bytes: Option<u64>,
+    pub threads: Option<u32>,
+    pub open_fds: Option<u32>,
+    /// Whatever the server reports at `/api/metrics`, if it answered
+    pub server_metrics: Option<serde_json::Value>,
+}
+
+/// Raw counters read from `/proc/<pid>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessStats {
+    /// utime + stime in clock ticks
+    pub cpu_ticks: u64,
+    pub rss_bytes: u64,
+    pub threads: u32,
+    pub open_fds: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceThresholds {
+    pub max_cpu_percent: f64,
+    pub max_rss_mb: u64,
+    pub max_open_fds: u32,
+    pub max_threads: u32,
+}
+
+impl Default for ResourceThresholds {
+    fn default() -> Self {
+        Self {
+            max_cpu_percent: 90.0,
+            max_rss_mb: 1024,
+            max_open_fds: 1024,
+            max_threads: 512,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceMetric {
+    Cpu,
+    Memory,
+    FileDescriptors,
+    Threads,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceAlert {
+    pub pid: u32,
+    pub metric: ResourceMetric,
+    pub value: f64,
+    pub threshold: f64,
+}
+
+/// Turns samples into alerts, once per excursion above a threshold
+#[derive(Debug, Default)]
+pub struct AlertTracker {
+    active: HashSet<ResourceMetric>,
+    cpu_over: u32,
+}
+
+impl AlertTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn evaluate(&mut self, sample: &ResourceSample, thresholds: &ResourceThresholds) -> Vec<ResourceAlert> {
+        let mut alerts = Vec::new();
+
+        if let Some(cpu) = sample.cpu_percent {
+            self.cpu_over = if cpu > thresholds.max_cpu_percent { self.cpu_over + 1 } else { 0 };
+            let sustained = self.cpu_over >= CPU_ALERT_SAMPLES;
+            self.check(ResourceMetric::Cpu, cpu, thresholds.max_cpu_percent, sustained, sample.pid, &mut alerts);
+        }
+
+        let readings = [
+            (ResourceMetric::Memory, sample.rss_bytes.map(|b| b as f64 / (1024.0 * 1024.0)), thresholds.max_rss_mb as f64),
+            (ResourceMetric::FileDescriptors, sample.open_fds.map(f64::from), thresholds.max_open_fds as f64),
+            (ResourceMetric::Threads, sample.threads.map(f64::from), thresholds.max_threads as f64),
+        ];
+        for (metric, value, threshold) in readings {
+            if let Some(value) = value {
+                self.check(metric, value, threshold, value > threshold, sample.pid, &mut alerts);
+            }
+        }
+
+        alerts
+    }
+
+    fn check(
+        &mut self,
+        metric: ResourceMetric,
+        value: f64,
+        threshold: f64,
+        exceeded: bool,
+        pid: u32,
+        alerts: &mut Vec<ResourceAlert>,
+    ) {
+        if exceeded {
+            if self.active.insert(metric) {
+                alerts.push(ResourceAlert { pid, metric, value, threshold });
+            }
+        } else if value <= threshold * ALERT_CLEAR_RATIO {
+            self.active.remove(&metric);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.active.clear();
+        self.cpu_over = 0;
+    }
+}
+
+/// Rolling window of the most recent samples
+#[derive(Debug)]
+pub struct ResourceHistory {
+    samples: VecDeque<ResourceSample>,
+    capacity: usize,
+}
+
+impl ResourceHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, sample: ResourceSample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Oldest first, limited to the newest `limit` samples
+    pub fn recent(&self, limit: Option<usize>) -> Vec<ResourceSample> {
+        let skip = limit.map_or(0, |limit| self.samples.len().saturating_sub(limit));
+        self.samples.iter().skip(skip).cloned().collect()
+    }
+}
+
+/// Parse `/proc/<pid>/stat` into (cpu ticks, threads)
+pub fn parse_proc_stat(contents: &str) -> Option<(u64, u32)> {
+    // The command name is in parentheses and may itself contain spaces or ')'
+    let rest = &contents[contents.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+
+    // fields[0] is field 3 (state); utime, stime and num_threads are fields 14, 15 and 20
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    let threads: u32 = fields.get(17)?.parse().ok()?;
+    Some((utime + stime, threads))
+}
+
+/// Resident set size in bytes from `/proc/<pid>/status`
+pub fn parse_proc_status_rss(contents: &str) -> Option<u64> {
+    let line = contents.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_process_stats(pid: u32) -> Result<ProcessStats, String> {
+    let proc_dir = std::path::PathBuf::from(format!("/proc/{}", pid));
+
+    let stat = std::fs::read_to_string(proc_dir.join("stat"))
+        .map_err(|e| format!("Failed to read /proc/{}/stat: {}", pid, e))?;
+    let (cpu_ticks, threads) = parse_proc_stat(&stat)
+        .ok_or_else(|| format!("Unexpected /proc/{}/stat format", pid))?;
+
+    let status = std::fs::read_to_string(proc_dir.join("status"))
+        .map_err(|e| format!("Failed to read /proc/{}/status: {}", pid, e))?;
+    let rss_bytes = parse_proc_status_rss(&status).unwrap_or(0);
+
+    let open_fds = std::fs::read_dir(proc_dir.join("fd"))
+        .map_err(|e| format!("Failed to read /proc/{}/fd: {}", pid, e))?
+        .count() as u32;
+
+    Ok(ProcessStats { cpu_ticks, rss_bytes, threads, open_fds })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_process_stats(_pid: u32) -> Result<ProcessStats, String> {
+    Err("Process resource sampling is only supported on Linux".to_string())
+}
+
+fn clock_ticks_per_second() -> f64 {
+    #[cfg(target_os = "linux")]
+    {
+        // SAFETY: sysconf has no preconditions
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        if ticks > 0 {
+            return ticks as f64;
+        }
+    }
+    100.0
+}
+
+/// CPU usage between two readings, where 100% is one fully busy core
+pub fn cpu_percent(prev_ticks: u64, ticks: u64, elapsed_secs: f64, ticks_per_second: f64) -> f64 {
+    if elapsed_secs <= 0.0 {
+        return 0.0;
+    }
+    ticks.saturating_sub(prev_ticks) as f64 / ticks_per_second / elapsed_secs * 100.0
+}
+
+struct CpuBaseline {
+    pid: u32,
+    ticks: u64,
+    at: Instant,
+}
+
+/// Collects samples for one server process at a time and raises threshold alerts
+#[derive(Clone)]
+pub struct ResourceMonitor {
+    history: Arc<Mutex<ResourceHistory>>,
+    thresholds: Arc<Mutex<ResourceThresholds>>,
+    alert_sender: broadcast::Sender<ResourceAlert>,
+    baseline: Arc<Mutex<Option<CpuBaseline>>>,
+    tracker: Arc<Mutex<AlertTracker>>,
+    sampling: Arc<AtomicBool>,
+}
+
+impl ResourceMonitor {
+    pub fn new() -> Self {
+        let (alert_sender, _) = broadcast::channel(16);
+
+        Self {
+            history: Arc::new(Mutex::new(ResourceHistory::new(RESOURCE_HISTORY_CAPACITY))),
+            thresholds: Arc::new(Mutex::new(ResourceThresholds::default())),
+            alert_sender,
+            baseline: Arc::new(Mutex::new(None)),
+            tracker: Arc::new(Mutex::new(AlertTracker::new())),
+            sampling: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn set_thresholds(&self, thresholds: ResourceThresholds) {
+        *self.thresholds.lock().unwrap() = thresholds;
+    }
+
+    pub fn subscribe_alerts(&self) -> broadcast::Receiver<ResourceAlert> {
+        self.alert_sender.subscribe()
+    }
+
+    pub fn history(&self, limit: Option<usize>) -> Vec<ResourceSample> {
+        self.history.lock().unwrap().recent(limit)
+    }
+
+    /// Claim the sampling loop; returns false when one is already running
+    pub fn begin_sampling(&self) -> bool {
+        !self.sampling.swap(true, Ordering::SeqCst)
+    }
+
+    /// Forget the CPU baseline and active alerts, e.g. when the server stops
+    pub fn reset(&self) {
+        self.baseline.lock().unwrap().take();
+        self.tracker.lock().unwrap().reset();
+    }
+
+    /// Take one sample of `pid`, merge in `/api/metrics` and record it
+    pub async fn sample(&self, pid: u32, health_checker: &HealthChecker) -> ResourceSample {
+        let stats = read_process_stats(pid);
+        let now = Instant::now();
+
+        let cpu = match &stats {
+            Ok(stats) => {
+                let mut baseline = self.baseline.lock().unwrap();
+                let cpu = baseline.as_ref()
+                    .filter(|b| b.pid == pid)
+                    .map(|b| cpu_percent(b.ticks, stats.cpu_ticks, now.duration_since(b.at).as_secs_f64(), clock_ticks_per_second()));
+                *baseline = Some(CpuBaseline { pid, ticks: stats.cpu_ticks, at: now });
+                cpu
+            }
+            Err(_) => None,
+        };
+        let stats = stats.ok();
+
+        let sample = ResourceSample {
+            timestamp: Utc::now(),
+            pid,
+            cpu_percent: cpu,
+            rss_bytes: stats.map(|s| s.rss_bytes),
+            threads: stats.map(|s| s.threads),
+            open_fds: stats.map(|s| s.open_fds),
+            server_metrics: health_checker.get_server_metrics().await.ok(),
+        };
+
+        let thresholds = self.thresholds.lock().unwrap().clone();
+        let alerts = self.tracker.lock().unwrap().evaluate(&sample, &thresholds);
+        for alert in alerts {
+            warn!("Server (PID: {}) {:?} at {:.1} exceeds threshold {:.1}", alert.pid, alert.metric, alert.value, alert.threshold);
+            let _ = self.alert_sender.send(alert);
+        }
+
+        self.history.lock().unwrap().push(sample.clone());
+        sample
+    }
+}
+
+impl Default for ResourceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(cpu: f64, rss_mb: u64, fds: u32) -> ResourceSample {
+        ResourceSample {
+            timestamp: Utc::now(),
+            pid: 42,
+            cpu_percent: Some(cpu),
+            rss_bytes: Some(rss_mb * 1024 * 1024),
+            threads: Some(12),
+            open_fds: Some(fds),
+            server_metrics: None,
+        }
+    }
+
+    #[test]
+    fn test_parses_proc_files() {
+        let stat = "4021 (tunnelforge (srv)) S 1 4021 4021 0 -1 4194560 2000 0 0 0 150 50 0 0 20 0 14 0 123456 1234567 2048 18446744073709551615";
+        assert_eq!(parse_proc_stat(stat), Some((200, 14)));
+
+        let status = "Name:\ttunnelforge-ser\nVmPeak:\t  900000 kB\nVmRSS:\t   20480 kB\nThreads:\t14\n";
+        assert_eq!(parse_proc_status_rss(status), Some(20480 * 1024));
+
+        assert_eq!(cpu_percent(100, 150, 1.0, 100.0), 50.0);
+    }
+
+    #[test]
+    fn test_alerts_once_per_excursion() {
+        let thresholds = ResourceThresholds { max_open_fds: 100, ..Default::default() };
+        let mut tracker = AlertTracker::new();
+
+        let alerts = tracker.evaluate(&sample(1.0, 50, 150), &thresholds);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].metric, ResourceMetric::FileDescriptors);
+
+        // Still above, and just below the threshold: no repeat
+        assert!(tracker.evaluate(&sample(1.0, 50, 160), &thresholds).is_empty());
+        assert!(tracker.evaluate(&sample(1.0, 50, 95), &thresholds).is_empty());
+        assert!(tracker.evaluate(&sample(1.0, 50, 120), &thresholds).is_empty());
+
+        // Clearing needs a real drop before the next excursion alerts again
+        assert!(tracker.evaluate(&sample(1.0, 50, 80), &thresholds).is_empty());
+        assert_eq!(tracker.evaluate(&sample(1.0, 50, 120), &thresholds).len(), 1);
+    }
+
+    #[test]
+    fn test_cpu_alert_needs_sustained_load() {
+        let thresholds = ResourceThresholds::default();
+        let mut tracker = AlertTracker::new();
+
+        assert!(tracker.evaluate(&sample(99.0, 50, 10), &thresholds).is_empty());
+        assert!(tracker.evaluate(&sample(99.0, 50, 10), &thresholds).is_empty());
+        let alerts = tracker.evaluate(&sample(99.0, 50, 10), &thresholds);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].metric, ResourceMetric::Cpu);
+    }
+
+    #[test]
+    fn test_history_keeps_newest_samples() {
+        let mut history = ResourceHistory::new(3);
+        for fds in 1..=5 {
+            history.push(sample(0.0, 10, fds));
+        }
+
+        let fds: Vec<_> = history.recent(None).iter().map(|s| s.open_fds.unwrap()).collect();
+        assert_eq!(fds, vec![3, 4, 5]);
+        assert_eq!(history.recent(Some(1))[0].open_fds, Some(5));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_reads_own_process() {
+        let stats = read_process_stats(std::process::id()).unwrap();
+        assert!(stats.rss_bytes > 0);
+        assert!(stats.threads >= 1);
+        assert!(stats.open_fds >= 1);
+    }
+}
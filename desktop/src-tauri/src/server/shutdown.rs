@@ -0,0 +1,282 @@
+// Staged server shutdown
+// SIGTERM first so the Go server can drain sessions, SIGKILL only after the drain timeout
+
+use std::process::Child;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+use log::{info, warn, debug};
+
+use super::health::HealthChecker;
+
+/// The Go server gives itself 10s to shut down after SIGTERM, so wait a little longer than that
+pub const DEFAULT_DRAIN_TIMEOUT_SECS: u64 = 15;
+const DRAIN_POLL_INTERVAL_MS: u64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct ShutdownOptions {
+    pub drain_timeout: Duration,
+    pub poll_interval: Duration,
+}
+
+impl Default for ShutdownOptions {
+    fn default() -> Self {
+        Self {
+            drain_timeout: Duration::from_secs(DEFAULT_DRAIN_TIMEOUT_SECS),
+            poll_interval: Duration::from_millis(DRAIN_POLL_INTERVAL_MS),
+        }
+    }
+}
+
+impl ShutdownOptions {
+    pub fn with_drain_timeout_secs(secs: u64) -> Self {
+        Self {
+            drain_timeout: Duration::from_secs(secs),
+            ..Self::default()
+        }
+    }
+}
+
+/// Which stage of the shutdown actually stopped the process
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownStage {
+    /// The process had already exited before we asked it to stop
+    AlreadyExited,
+    /// The process exited on its own after SIGTERM
+    Terminated,
+    /// The drain timeout expired (or no graceful signal exists) and the process was killed
+    Killed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    pub pid: u32,
+    pub stage: ShutdownStage,
+    pub elapsed_ms: u64,
+    /// Time until the server stopped accepting connections, if it was observed
+    pub listener_closed_after_ms: Option<u64>,
+}
+
+/// Stop `child` in stages: SIGTERM, drain while polling `health_checker`, then SIGKILL
+pub async fn graceful_shutdown(
+    child: &mut Child,
+    health_checker: Option<&HealthChecker>,
+    options: &ShutdownOptions,
+) -> Result<ShutdownReport, String> {
+    let pid = child.id();
+    let start_time = Instant::now();
+
+    if let Ok(Some(_)) = child.try_wait() {
+        debug!("Server (PID: {}) had already exited", pid);
+        return Ok(ShutdownReport {
+            pid,
+            stage: ShutdownStage::AlreadyExited,
+            elapsed_ms: 0,
+            listener_closed_after_ms: None,
+        });
+    }
+
+    let mut listener_closed_after_ms = None;
+
+    if request_termination(pid) {
+        info!("Sent SIGTERM to server (PID: {}), draining for up to {}s", pid, options.drain_timeout.as_secs());
+
+        while start_time.elapsed() < options.drain_timeout {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    let elapsed_ms = start_time.elapsed().as_millis() as u64;
+                    info!("Server (PID: {}) exited after SIGTERM in {}ms ({})", pid, elapsed_ms, status);
+                    return Ok(ShutdownReport {
+                        pid,
+                        stage: ShutdownStage::Terminated,
+                        elapsed_ms,
+                        listener_closed_after_ms,
+                    });
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to poll server process (PID: {}): {}", pid, e),
+            }
+
+            if listener_closed_after_ms.is_none() {
+                if let Some(checker) = health_checker {
+                    if !checker.check_tcp_connection().await {
+                        let elapsed_ms = start_time.elapsed().as_millis() as u64;
+                        debug!("Server stopped accepting connections after {}ms", elapsed_ms);
+                        listener_closed_after_ms = Some(elapsed_ms);
+                    }
+                }
+            }
+
+            sleep(options.poll_interval).await;
+        }
+
+        warn!("Server (PID: {}) did not exit within {}s, killing it", pid, options.drain_timeout.as_secs());
+    }
+
+    child.kill()
+        .map_err(|e| format!("Failed to kill server process: {}", e))?;
+    let _ = child.wait();
+
+    Ok(ShutdownReport {
+        pid,
+        stage: ShutdownStage::Killed,
+        elapsed_ms: start_time.elapsed().as_millis() as u64,
+        listener_closed_after_ms,
+    })
+}
+
+/// Stop a server we did not spawn (and so cannot `wait` on) with the same SIGTERM, drain, SIGKILL stages
+#[cfg(unix)]
+pub async fn terminate_pid(
+    pid: u32,
+    health_checker: Option<&HealthChecker>,
+    options: &ShutdownOptions,
+) -> Result<ShutdownReport, String> {
+    let start_time = Instant::now();
+
+    if !pid_alive(pid) {
+        return Ok(ShutdownReport {
+            pid,
+            stage: ShutdownStage::AlreadyExited,
+            elapsed_ms: 0,
+            listener_closed_after_ms: None,
+        });
+    }
+
+    if !request_termination(pid) {
+        return Err(format!("Failed to signal process {}", pid));
+    }
+    info!("Sent SIGTERM to external server (PID: {}), draining for up to {}s", pid, options.drain_timeout.as_secs());
+
+    let mut listener_closed_after_ms = None;
+    while start_time.elapsed() < options.drain_timeout {
+        if !pid_alive(pid) {
+            return Ok(ShutdownReport {
+                pid,
+                stage: ShutdownStage::Terminated,
+                elapsed_ms: start_time.elapsed().as_millis() as u64,
+                listener_closed_after_ms,
+            });
+        }
+
+        if listener_closed_after_ms.is_none() {
+            if let Some(checker) = health_checker {
+                if !checker.check_tcp_connection().await {
+                    listener_closed_after_ms = Some(start_time.elapsed().as_millis() as u64);
+                }
+            }
+        }
+
+        sleep(options.poll_interval).await;
+    }
+
+    warn!("External server (PID: {}) did not exit within {}s, killing it", pid, options.drain_timeout.as_secs());
+    // SAFETY: see request_termination
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) } != 0 {
+        return Err(format!("Failed to kill process {}: {}", pid, std::io::Error::last_os_error()));
+    }
+
+    Ok(ShutdownReport {
+        pid,
+        stage: ShutdownStage::Killed,
+        elapsed_ms: start_time.elapsed().as_millis() as u64,
+        listener_closed_after_ms,
+    })
+}
+
+#[cfg(not(unix))]
+pub async fn terminate_pid(
+    pid: u32,
+    _health_checker: Option<&HealthChecker>,
+    _options: &ShutdownOptions,
+) -> Result<ShutdownReport, String> {
+    Err(format!("Stopping external process {} is not supported on this platform", pid))
+}
+
+/// Whether `pid` still exists. Always false where we cannot check.
+#[cfg(unix)]
+pub fn pid_alive(pid: u32) -> bool {
+    // SAFETY: signal 0 only checks that the process exists and we may signal it
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH)
+}
+
+#[cfg(not(unix))]
+pub fn pid_alive(_pid: u32) -> bool {
+    false
+}
+
+/// Ask the process to terminate. Returns false when no graceful signal is available.
+#[cfg(unix)]
+fn request_termination(pid: u32) -> bool {
+    // SAFETY: kill(2) has no memory safety requirements; a stale pid only yields ESRCH
+    let result = unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+    if result != 0 {
+        warn!("Failed to send SIGTERM to PID {}: {}", pid, std::io::Error::last_os_error());
+        return false;
+    }
+    true
+}
+
+#[cfg(not(unix))]
+fn request_termination(pid: u32) -> bool {
+    // The server runs without a console (CREATE_NO_WINDOW), so there is no Ctrl+C to deliver
+    debug!("No graceful termination signal for PID {} on this platform", pid);
+    false
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[tokio::test]
+    async fn test_sigterm_stops_cooperative_process() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let options = ShutdownOptions::with_drain_timeout_secs(5);
+
+        let report = graceful_shutdown(&mut child, None, &options).await.unwrap();
+        assert_eq!(report.stage, ShutdownStage::Terminated);
+    }
+
+    #[tokio::test]
+    async fn test_escalates_to_kill_when_sigterm_is_ignored() {
+        let mut child = Command::new("sh")
+            .args(["-c", "trap '' TERM; exec sleep 30"])
+            .spawn()
+            .unwrap();
+        // `exec` keeps the ignored TERM but leaves no `sleep` grandchild behind after the kill.
+        // Give the shell time to install the trap
+        sleep(Duration::from_millis(200)).await;
+
+        let options = ShutdownOptions {
+            drain_timeout: Duration::from_millis(300),
+            poll_interval: Duration::from_millis(50),
+        };
+        let report = graceful_shutdown(&mut child, None, &options).await.unwrap();
+        assert_eq!(report.stage, ShutdownStage::Killed);
+    }
+
+    #[tokio::test]
+    async fn test_terminates_process_by_pid() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+
+        // Reap the child in the background the way its real parent would
+        let reaper = std::thread::spawn(move || child.wait());
+
+        let report = terminate_pid(pid, None, &ShutdownOptions::with_drain_timeout_secs(5)).await.unwrap();
+        assert_eq!(report.stage, ShutdownStage::Terminated);
+        assert!(reaper.join().unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reports_already_exited_process() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let _ = child.wait();
+
+        let report = graceful_shutdown(&mut child, None, &ShutdownOptions::default()).await.unwrap();
+        assert_eq!(report.stage, ShutdownStage::AlreadyExited);
+    }
+}
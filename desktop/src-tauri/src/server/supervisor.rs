@@ -0,0 +1,272 @@
+// Server process supervision
+// Port of the crash recovery behaviour in ServerManager.swift
+
+use std::collections::VecDeque;
+use std::process::ExitStatus;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use super::adopt::ExternalServerPolicy;
+use super::pidfile::OrphanPolicy;
+use super::watchdog::HealthState;
+
+/// Name of the Tauri event carrying `ServerLifecycleEvent`s to the frontend
+pub const SERVER_LIFECYCLE_EVENT: &str = "server-lifecycle";
+
+/// Interval at which the supervisor polls the child with `try_wait`
+pub const SUPERVISOR_POLL_INTERVAL_MS: u64 = 500;
+
+#[cfg(unix)]
+const SIGKILL: i32 = 9;
+
+/// How the server process ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExitKind {
+    /// Exited with status 0, or was stopped on request
+    Clean,
+    /// Exited with a non-zero status code
+    Crashed { code: Option<i32> },
+    /// Terminated by a signal we did not send
+    Signaled { signal: i32 },
+    /// Killed with SIGKILL without us asking, which on Linux is almost always the OOM killer
+    OomKilled,
+}
+
+impl ExitKind {
+    pub fn is_crash(&self) -> bool {
+        !matches!(self, ExitKind::Clean)
+    }
+}
+
+/// Classify an exit status. `stop_requested` is true when the app itself asked the server to stop,
+/// in which case any exit counts as clean.
+pub fn classify_exit(status: &ExitStatus, stop_requested: bool) -> ExitKind {
+    if stop_requested || status.success() {
+        return ExitKind::Clean;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+
+        if let Some(signal) = status.signal() {
+            return if signal == SIGKILL {
+                ExitKind::OomKilled
+            } else {
+                ExitKind::Signaled { signal }
+            };
+        }
+
+        // A shell wrapper reports a SIGKILLed child as 128 + 9
+        if status.code() == Some(128 + SIGKILL) {
+            return ExitKind::OomKilled;
+        }
+    }
+
+    ExitKind::Crashed { code: status.code() }
+}
+
+/// Lifecycle events emitted by the supervisor for the tray, notifications and frontend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerLifecycleEvent {
+    Starting { port: u16, attempt: u32 },
+    /// The server answered its health check `startup_ms` after it was spawned
+    Ready { pid: u32, port: u16, startup_ms: u64 },
+    /// The server never became healthy; `pid` is `None` when it could not be spawned at all
+    StartFailed { pid: Option<u32>, error: String, elapsed_ms: u64 },
+    Crashed { pid: u32, exit: ExitKind, restart_in_ms: Option<u64> },
+    Stopped { pid: u32 },
+    GivingUp { crashes: u32, window_secs: u64 },
+    /// Another program held `from`, so the server was moved to `to`
+    PortReassigned { from: u16, to: u16, reason: String },
+    /// A TunnelForge server we did not start owns the port; `policy` is `None` until the user picks one
+    ExternalServerDetected {
+        pid: Option<u32>,
+        port: u16,
+        instance_id: Option<String>,
+        policy: Option<ExternalServerPolicy>,
+    },
+    /// A server from a previous desktop session was still running; `policy` says what happens to it
+    OrphanFound { pid: u32, port: u16, policy: OrphanPolicy },
+    /// Saved settings differ from what the running server was started with
+    RestartRequired { changed: Vec<String> },
+    /// The watchdog's view of a live server changed
+    HealthChanged {
+        pid: u32,
+        from: HealthState,
+        to: HealthState,
+        consecutive_failures: u32,
+        latency_ms: Option<u64>,
+    },
+    /// The server stopped answering health checks and was killed so it can be restarted
+    WatchdogRestart { pid: u32, unresponsive_ms: u64, goroutine_dump: bool },
+}
+
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: u32,
+    /// Number of crashes inside `crash_window` that trips the circuit breaker
+    pub max_crashes: u32,
+    pub crash_window: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2,
+            max_crashes: 5,
+            crash_window: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartDecision {
+    RestartAfter(Duration),
+    GiveUp,
+}
+
+/// Tracks recent crashes to compute exponential backoff and trip the crash-loop breaker
+#[derive(Debug)]
+pub struct CrashTracker {
+    policy: RestartPolicy,
+    crashes: VecDeque<Instant>,
+    consecutive: u32,
+}
+
+impl CrashTracker {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Self {
+            policy,
+            crashes: VecDeque::new(),
+            consecutive: 0,
+        }
+    }
+
+    pub fn policy(&self) -> &RestartPolicy {
+        &self.policy
+    }
+
+    pub fn record_crash(&mut self, now: Instant) -> RestartDecision {
+        self.crashes.push_back(now);
+        while let Some(first) = self.crashes.front() {
+            if now.duration_since(*first) > self.policy.crash_window {
+                self.crashes.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if self.crashes.len() as u32 >= self.policy.max_crashes {
+            return RestartDecision::GiveUp;
+        }
+
+        let backoff = self.next_backoff();
+        self.consecutive += 1;
+        RestartDecision::RestartAfter(backoff)
+    }
+
+    /// Called once the server has stayed up for a full crash window
+    pub fn record_stable(&mut self) {
+        self.consecutive = 0;
+        self.crashes.clear();
+    }
+
+    pub fn crash_count(&self) -> u32 {
+        self.crashes.len() as u32
+    }
+
+    fn next_backoff(&self) -> Duration {
+        let factor = self.policy.multiplier.saturating_pow(self.consecutive);
+        self.policy
+            .initial_backoff
+            .checked_mul(factor)
+            .map(|backoff| backoff.min(self.policy.max_backoff))
+            .unwrap_or(self.policy.max_backoff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RestartPolicy {
+        RestartPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            multiplier: 2,
+            max_crashes: 5,
+            crash_window: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let mut tracker = CrashTracker::new(policy());
+        let now = Instant::now();
+
+        assert_eq!(tracker.record_crash(now), RestartDecision::RestartAfter(Duration::from_millis(100)));
+        assert_eq!(tracker.record_crash(now), RestartDecision::RestartAfter(Duration::from_millis(200)));
+        assert_eq!(tracker.record_crash(now), RestartDecision::RestartAfter(Duration::from_millis(400)));
+        assert_eq!(tracker.record_crash(now), RestartDecision::RestartAfter(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_crash_loop_gives_up() {
+        let mut tracker = CrashTracker::new(policy());
+        let now = Instant::now();
+
+        for _ in 0..4 {
+            assert!(matches!(tracker.record_crash(now), RestartDecision::RestartAfter(_)));
+        }
+        assert_eq!(tracker.record_crash(now), RestartDecision::GiveUp);
+    }
+
+    #[test]
+    fn test_old_crashes_leave_the_window() {
+        let mut tracker = CrashTracker::new(policy());
+        let start = Instant::now();
+
+        for _ in 0..4 {
+            tracker.record_crash(start);
+        }
+        let later = start + Duration::from_secs(61);
+        assert!(matches!(tracker.record_crash(later), RestartDecision::RestartAfter(_)));
+        assert_eq!(tracker.crash_count(), 1);
+    }
+
+    #[test]
+    fn test_stable_run_resets_backoff() {
+        let mut tracker = CrashTracker::new(policy());
+        let now = Instant::now();
+
+        tracker.record_crash(now);
+        tracker.record_crash(now);
+        tracker.record_stable();
+        assert_eq!(tracker.record_crash(now), RestartDecision::RestartAfter(Duration::from_millis(100)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_classify_exit() {
+        use std::os::unix::process::ExitStatusExt;
+
+        // Raw wait statuses: exit code lives in the high byte, signal in the low bits
+        let clean = ExitStatus::from_raw(0);
+        let failed = ExitStatus::from_raw(2 << 8);
+        let killed = ExitStatus::from_raw(9);
+        let segv = ExitStatus::from_raw(11);
+
+        assert_eq!(classify_exit(&clean, false), ExitKind::Clean);
+        assert_eq!(classify_exit(&failed, false), ExitKind::Crashed { code: Some(2) });
+        assert_eq!(classify_exit(&killed, false), ExitKind::OomKilled);
+        assert_eq!(classify_exit(&segv, false), ExitKind::Signaled { signal: 11 });
+        assert_eq!(classify_exit(&killed, true), ExitKind::Clean);
+    }
+}
@@ -0,0 +1,273 @@
+// Hung-server watchdog
+// Tells a server that merely accepts connections apart from one that answers, and restarts it when it stops answering
+
+use std::process::Child;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+use log::{info, warn};
+
+use super::health::HealthChecker;
+
+/// How long a goroutine dump may take before the hung server is killed outright
+const GOROUTINE_DUMP_TIMEOUT_MS: u64 = 3000;
+const EXIT_POLL_INTERVAL_MS: u64 = 50;
+/// Weight of the newest probe in the smoothed latency
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthState {
+    Healthy,
+    /// Slow or intermittently failing health checks
+    Degraded,
+    /// Consecutive health check failures while the process is still alive
+    Unresponsive,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogPolicy {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// A successful check slower than this counts against the server
+    pub slow_response_ms: u64,
+    pub failures_to_degrade: u32,
+    pub failures_to_unresponsive: u32,
+    pub successes_to_recover: u32,
+    /// How long the server may stay unresponsive before it is restarted
+    pub restart_grace_secs: u64,
+    /// Send SIGQUIT first so the Go runtime writes all goroutine stacks to the server log
+    pub goroutine_dump: bool,
+}
+
+impl Default for WatchdogPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 5,
+            slow_response_ms: 2000,
+            failures_to_degrade: 2,
+            failures_to_unresponsive: 4,
+            successes_to_recover: 2,
+            restart_grace_secs: 30,
+            goroutine_dump: true,
+        }
+    }
+}
+
+/// Result of one watchdog probe
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthProbe {
+    pub ok: bool,
+    pub latency_ms: Option<u64>,
+}
+
+/// Probe `/health` and `/api/health`; both have to answer for the server to count as healthy
+pub async fn probe_server(health_checker: &HealthChecker) -> HealthProbe {
+    let status = health_checker.check_health().await;
+    let ok = status.healthy && health_checker.check_api_health().await;
+
+    HealthProbe {
+        ok,
+        latency_ms: status.response_time_ms,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthTransition {
+    pub from: HealthState,
+    pub to: HealthState,
+}
+
+/// Health state machine with hysteresis: states only change after several probes agree
+#[derive(Debug)]
+pub struct HealthTracker {
+    policy: WatchdogPolicy,
+    state: HealthState,
+    consecutive_bad: u32,
+    consecutive_good: u32,
+    smoothed_latency_ms: Option<f64>,
+    unresponsive_since: Option<Instant>,
+    // Failures before the first success are startup, which `await_ready` already handles
+    armed: bool,
+}
+
+impl HealthTracker {
+    pub fn new(policy: WatchdogPolicy) -> Self {
+        Self {
+            policy,
+            state: HealthState::Healthy,
+            consecutive_bad: 0,
+            consecutive_good: 0,
+            smoothed_latency_ms: None,
+            unresponsive_since: None,
+            armed: false,
+        }
+    }
+
+    pub fn state(&self) -> HealthState {
+        self.state
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_bad
+    }
+
+    pub fn smoothed_latency_ms(&self) -> Option<u64> {
+        self.smoothed_latency_ms.map(|latency| latency.round() as u64)
+    }
+
+    pub fn record(&mut self, probe: HealthProbe, now: Instant) -> Option<HealthTransition> {
+        if let Some(latency) = probe.latency_ms {
+            let latency = latency as f64;
+            self.smoothed_latency_ms = Some(match self.smoothed_latency_ms {
+                Some(previous) => previous + LATENCY_SMOOTHING * (latency - previous),
+                None => latency,
+            });
+        }
+
+        if !self.armed {
+            if !probe.ok {
+                return None;
+            }
+            self.armed = true;
+        }
+
+        let slow = probe.latency_ms.is_some_and(|latency| latency > self.policy.slow_response_ms);
+        if probe.ok && !slow {
+            self.consecutive_good += 1;
+            self.consecutive_bad = 0;
+        } else {
+            self.consecutive_bad += 1;
+            self.consecutive_good = 0;
+        }
+
+        let next = match self.state {
+            _ if self.consecutive_good >= self.policy.successes_to_recover => HealthState::Healthy,
+            // Slow but answering never escalates past degraded
+            HealthState::Healthy | HealthState::Degraded
+                if !probe.ok && self.consecutive_bad >= self.policy.failures_to_unresponsive =>
+            {
+                HealthState::Unresponsive
+            }
+            HealthState::Healthy if self.consecutive_bad >= self.policy.failures_to_degrade => HealthState::Degraded,
+            state => state,
+        };
+
+        if next == self.state {
+            return None;
+        }
+
+        self.unresponsive_since = (next == HealthState::Unresponsive).then_some(now);
+        let transition = HealthTransition { from: self.state, to: next };
+        self.state = next;
+        Some(transition)
+    }
+
+    /// True once the server has been unresponsive for longer than the grace period
+    pub fn should_restart(&self, now: Instant) -> bool {
+        self.unresponsive_since.is_some_and(|since| {
+            now.duration_since(since) >= Duration::from_secs(self.policy.restart_grace_secs)
+        })
+    }
+
+    pub fn unresponsive_for(&self, now: Instant) -> Option<Duration> {
+        self.unresponsive_since.map(|since| now.duration_since(since))
+    }
+}
+
+/// Kill a hung server. With `goroutine_dump` the Go runtime first gets SIGQUIT, which makes it
+/// print every goroutine's stack to stderr (captured in the server log) and exit.
+pub async fn kill_hung_server(child: &mut Child, goroutine_dump: bool) -> Result<(), String> {
+    let pid = child.id();
+
+    #[cfg(unix)]
+    if goroutine_dump {
+        // SAFETY: we own the child, so the pid cannot have been reused while it is unreaped
+        if unsafe { libc::kill(pid as libc::pid_t, libc::SIGQUIT) } == 0 {
+            let deadline = Instant::now() + Duration::from_millis(GOROUTINE_DUMP_TIMEOUT_MS);
+            while Instant::now() < deadline {
+                if let Ok(Some(_)) = child.try_wait() {
+                    info!("Hung server (PID: {}) wrote a goroutine dump and exited", pid);
+                    return Ok(());
+                }
+                sleep(Duration::from_millis(EXIT_POLL_INTERVAL_MS)).await;
+            }
+            warn!("Hung server (PID: {}) did not exit after SIGQUIT", pid);
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = goroutine_dump;
+
+    child.kill().map_err(|e| format!("Failed to kill hung server (PID: {}): {}", pid, e))?;
+    child.wait().map_err(|e| format!("Failed to reap hung server (PID: {}): {}", pid, e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OK: HealthProbe = HealthProbe { ok: true, latency_ms: Some(10) };
+    const SLOW: HealthProbe = HealthProbe { ok: true, latency_ms: Some(5000) };
+    const FAIL: HealthProbe = HealthProbe { ok: false, latency_ms: None };
+
+    fn tracker() -> HealthTracker {
+        HealthTracker::new(WatchdogPolicy::default())
+    }
+
+    #[test]
+    fn test_ignores_failures_before_first_success() {
+        let mut tracker = tracker();
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            assert_eq!(tracker.record(FAIL, now), None);
+        }
+        assert_eq!(tracker.state(), HealthState::Healthy);
+    }
+
+    #[test]
+    fn test_escalates_and_recovers_with_hysteresis() {
+        let mut tracker = tracker();
+        let now = Instant::now();
+        tracker.record(OK, now);
+
+        assert_eq!(tracker.record(FAIL, now), None);
+        assert_eq!(tracker.record(FAIL, now).unwrap().to, HealthState::Degraded);
+        assert_eq!(tracker.record(FAIL, now), None);
+        assert_eq!(tracker.record(FAIL, now).unwrap().to, HealthState::Unresponsive);
+
+        // A single success is not enough to recover
+        assert_eq!(tracker.record(OK, now), None);
+        assert_eq!(tracker.record(OK, now).unwrap().to, HealthState::Healthy);
+    }
+
+    #[test]
+    fn test_slow_responses_only_degrade() {
+        let mut tracker = tracker();
+        let now = Instant::now();
+        tracker.record(OK, now);
+
+        for _ in 0..10 {
+            tracker.record(SLOW, now);
+        }
+        assert_eq!(tracker.state(), HealthState::Degraded);
+        assert!(tracker.smoothed_latency_ms().unwrap() > 2000);
+    }
+
+    #[test]
+    fn test_restart_after_grace_period() {
+        let mut tracker = tracker();
+        let start = Instant::now();
+        tracker.record(OK, start);
+        for _ in 0..4 {
+            tracker.record(FAIL, start);
+        }
+
+        assert!(!tracker.should_restart(start + Duration::from_secs(29)));
+        assert!(tracker.should_restart(start + Duration::from_secs(30)));
+    }
+}
@@ -0,0 +1,359 @@
+// Terminal buffer snapshots
+// Decodes the binary frames the server's /buffers WebSocket sends into a screen model
+
+use super::websocket::websocket_url;
+use super::LOCAL_BYPASS_HEADER;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+
+/// First byte of every binary buffer frame (`BufferMagicByte` in the Go server)
+pub const BUFFER_MAGIC_BYTE: u8 = 0xBF;
+/// Magic byte plus the little-endian session id length
+const HEADER_LEN: usize = 5;
+/// Session ids are UUIDs; anything much longer means a corrupt frame
+const MAX_SESSION_ID_LEN: usize = 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CellAttributes {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikeout: bool,
+    pub inverse: bool,
+    pub dim: bool,
+    pub blink: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferCell {
+    /// The grapheme in this cell; empty for the second half of a wide character
+    #[serde(rename = "char", default)]
+    pub ch: String,
+    /// Colors as sent by the server
+    #[serde(default)]
+    pub fg_color: i32,
+    #[serde(default)]
+    pub bg_color: i32,
+    #[serde(flatten)]
+    pub attributes: CellAttributes,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub x: u16,
+    pub y: u16,
+}
+
+/// Screen contents of one session (`TerminalSnapshot` in the Go server)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TerminalSnapshot {
+    pub session_id: String,
+    pub cols: u16,
+    pub rows: u16,
+    /// First buffer line shown in the viewport
+    pub viewport_y: u32,
+    pub cursor: Cursor,
+    /// At most `rows` rows of at most `cols` cells; missing rows and cells are blank
+    pub cells: Vec<Vec<BufferCell>>,
+}
+
+/// Snapshot payload exactly as the Go server marshals it. The fields are Go `int`s,
+/// so they are read as i64 and clamped into the screen model.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WireSnapshot {
+    cols: i64,
+    rows: i64,
+    #[serde(default)]
+    viewport_y: i64,
+    #[serde(default)]
+    cursor_x: i64,
+    #[serde(default)]
+    cursor_y: i64,
+    #[serde(default)]
+    cells: Option<Vec<Vec<BufferCell>>>,
+}
+
+impl TerminalSnapshot {
+    pub fn cell(&self, x: u16, y: u16) -> Option<&BufferCell> {
+        self.cells.get(y as usize)?.get(x as usize)
+    }
+
+    /// Text of one row without trailing blanks
+    pub fn row_text(&self, y: u16) -> String {
+        let Some(row) = self.cells.get(y as usize) else {
+            return String::new();
+        };
+
+        let text: String = row.iter().map(|cell| cell.ch.as_str()).collect();
+        text.trim_end().to_string()
+    }
+
+    /// Whole screen as text, one line per row, for previews and content scanning
+    pub fn text(&self) -> String {
+        let lines: Vec<String> = (0..self.rows).map(|y| self.row_text(y)).collect();
+        let end = lines.iter().rposition(|line| !line.is_empty()).map_or(0, |i| i + 1);
+        lines[..end].join("\n")
+    }
+}
+
+pub fn is_buffer_frame(data: &[u8]) -> bool {
+    data.first() == Some(&BUFFER_MAGIC_BYTE)
+}
+
+/// Decode one binary frame: magic byte, u32 LE session id length, session id, snapshot payload
+pub fn decode_buffer_frame(data: &[u8]) -> Result<TerminalSnapshot, String> {
+    if data.len() < HEADER_LEN {
+        return Err(format!("Buffer frame too short: {} bytes", data.len()));
+    }
+    if !is_buffer_frame(data) {
+        return Err(format!("Not a buffer frame: magic byte 0x{:02X}", data[0]));
+    }
+
+    let id_len = u32::from_le_bytes([data[1], data[2], data[3], data[4]]) as usize;
+    if id_len > MAX_SESSION_ID_LEN || data.len() - HEADER_LEN < id_len {
+        return Err(format!("Invalid session id length {} in a {} byte frame", id_len, data.len()));
+    }
+
+    let session_id = std::str::from_utf8(&data[HEADER_LEN..HEADER_LEN + id_len])
+        .map_err(|_| "Session id is not valid UTF-8".to_string())?
+        .to_string();
+
+    let wire: WireSnapshot = serde_json::from_slice(&data[HEADER_LEN + id_len..])
+        .map_err(|e| format!("Invalid snapshot for session {}: {}", session_id, e))?;
+    let cells = wire.cells.unwrap_or_default();
+
+    let cols = wire.cols.clamp(0, u16::MAX as i64) as u16;
+    let rows = wire.rows.clamp(0, u16::MAX as i64) as u16;
+    if cells.len() > rows as usize {
+        return Err(format!("Snapshot has {} rows, expected at most {}", cells.len(), rows));
+    }
+    if let Some(row) = cells.iter().find(|row| row.len() > cols as usize) {
+        return Err(format!("Snapshot row has {} cells, expected at most {}", row.len(), cols));
+    }
+
+    // xterm parks the cursor one past the last column, so `cols`/`rows` are valid positions
+    let cursor = Cursor {
+        x: wire.cursor_x.clamp(0, cols as i64) as u16,
+        y: wire.cursor_y.clamp(0, rows as i64) as u16,
+    };
+
+    Ok(TerminalSnapshot {
+        session_id,
+        cols,
+        rows,
+        viewport_y: wire.viewport_y.clamp(0, u32::MAX as i64) as u32,
+        cursor,
+        cells,
+    })
+}
+
+/// `ws://` (or `wss://`) URL of the server's buffer socket
+pub fn buffers_url(server_url: &str) -> Result<String, String> {
+    let mut url = reqwest::Url::parse(&websocket_url(server_url, "")?)
+        .map_err(|e| format!("Invalid WebSocket URL: {}", e))?;
+    url.set_path("/buffers");
+    url.set_query(None);
+    Ok(url.to_string())
+}
+
+/// Subscribe to a session on `/buffers` and return the first snapshot the server sends for it
+pub async fn fetch_buffer_snapshot(server_url: &str, session_id: &str, timeout: Duration) -> Result<TerminalSnapshot, String> {
+    let mut request = buffers_url(server_url)?.into_client_request()
+        .map_err(|e| format!("Invalid WebSocket URL: {}", e))?;
+    request.headers_mut().insert(LOCAL_BYPASS_HEADER, HeaderValue::from_static("true"));
+
+    let fetch = async {
+        let (mut stream, _) = connect_async(request).await
+            .map_err(|e| format!("Failed to connect to the buffer socket: {}", e))?;
+        let subscribe = serde_json::json!({ "type": "subscribe", "sessionId": session_id }).to_string();
+        stream.send(Message::Text(subscribe)).await
+            .map_err(|e| format!("Failed to subscribe to session {}: {}", session_id, e))?;
+
+        // Welcome and subscription acks arrive as text frames and are skipped
+        while let Some(message) = stream.next().await {
+            match message {
+                Ok(Message::Binary(data)) if is_buffer_frame(&data) => {
+                    let snapshot = decode_buffer_frame(&data)?;
+                    if snapshot.session_id == session_id {
+                        let _ = stream.close(None).await;
+                        return Ok(snapshot);
+                    }
+                }
+                Ok(Message::Close(_)) => break,
+                Ok(_) => {}
+                Err(e) => return Err(format!("Buffer socket failed: {}", e)),
+            }
+        }
+        Err("Buffer socket closed before sending a snapshot".to_string())
+    };
+
+    tokio::time::timeout(timeout, fetch).await
+        .map_err(|_| format!("No buffer snapshot for session {} within {}ms", session_id, timeout.as_millis()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::{quickcheck, Arbitrary, Gen};
+
+    /// Frame layout of `BufferAggregator.BroadcastBuffer`, payload as `json.Marshal` writes it
+    fn encode_go_frame(snapshot: &TerminalSnapshot) -> Vec<u8> {
+        let cells: Vec<Vec<serde_json::Value>> = snapshot.cells.iter().map(|row| row.iter().map(|cell| {
+            serde_json::json!({
+                "char": cell.ch, "fgColor": cell.fg_color, "bgColor": cell.bg_color,
+                "bold": cell.attributes.bold, "italic": cell.attributes.italic,
+                "underline": cell.attributes.underline, "strikeout": cell.attributes.strikeout,
+                "inverse": cell.attributes.inverse, "dim": cell.attributes.dim, "blink": cell.attributes.blink,
+            })
+        }).collect()).collect();
+        let payload = serde_json::json!({
+            "cols": snapshot.cols, "rows": snapshot.rows, "viewportY": snapshot.viewport_y,
+            "cursorX": snapshot.cursor.x, "cursorY": snapshot.cursor.y, "cells": cells,
+        });
+
+        let mut frame = vec![BUFFER_MAGIC_BYTE];
+        frame.extend_from_slice(&(snapshot.session_id.len() as u32).to_le_bytes());
+        frame.extend_from_slice(snapshot.session_id.as_bytes());
+        frame.extend_from_slice(payload.to_string().as_bytes());
+        frame
+    }
+
+    impl Arbitrary for TerminalSnapshot {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let cols = u16::arbitrary(g) % 12;
+            let rows = u16::arbitrary(g) % 6;
+            let cells = (0..usize::arbitrary(g) % (rows as usize + 1)).map(|_| {
+                (0..usize::arbitrary(g) % (cols as usize + 1)).map(|_| BufferCell {
+                    ch: g.choose(&["", " ", "a", "é", "界", "🙂"]).unwrap().to_string(),
+                    fg_color: i32::arbitrary(g),
+                    bg_color: i32::arbitrary(g),
+                    attributes: CellAttributes {
+                        bold: bool::arbitrary(g),
+                        italic: bool::arbitrary(g),
+                        underline: bool::arbitrary(g),
+                        strikeout: bool::arbitrary(g),
+                        inverse: bool::arbitrary(g),
+                        dim: bool::arbitrary(g),
+                        blink: bool::arbitrary(g),
+                    },
+                }).collect()
+            }).collect();
+
+            TerminalSnapshot {
+                session_id: String::arbitrary(g),
+                cols,
+                rows,
+                viewport_y: u32::arbitrary(g),
+                cursor: Cursor { x: u16::arbitrary(g) % (cols + 1), y: u16::arbitrary(g) % (rows + 1) },
+                cells,
+            }
+        }
+    }
+
+    #[test]
+    fn test_decodes_go_frame() {
+        // BroadcastBuffer("a1", ...) for a 3x2 terminal showing a bold "$"
+        let mut frame = vec![0xBF, 2, 0, 0, 0, b'a', b'1'];
+        frame.extend_from_slice(concat!(
+            r#"{"cols":3,"rows":2,"viewportY":0,"cursorX":2,"cursorY":0,"cells":[[{"char":"$","fgColor":2,"bgColor":0,"#,
+            r#""bold":true,"italic":false,"underline":false,"strikeout":false,"inverse":false,"dim":false,"blink":false},"#,
+            r#"{"char":" ","fgColor":0,"bgColor":0,"bold":false,"italic":false,"underline":false,"strikeout":false,"#,
+            r#""inverse":false,"dim":false,"blink":false}]]}"#
+        ).as_bytes());
+
+        let snapshot = decode_buffer_frame(&frame).unwrap();
+        assert_eq!(snapshot.session_id, "a1");
+        assert_eq!((snapshot.cols, snapshot.rows, snapshot.cursor), (3, 2, Cursor { x: 2, y: 0 }));
+        assert!(snapshot.cell(0, 0).unwrap().attributes.bold);
+        assert_eq!(snapshot.cell(0, 0).unwrap().fg_color, 2);
+        assert_eq!(snapshot.cell(2, 1), None);
+        assert_eq!(snapshot.text(), "$");
+    }
+
+    #[test]
+    fn test_decodes_golden_frames_from_go_server() {
+        // Written by TestBufferAggregator_GoldenFrames through BufferAggregator.encodeFrame
+        let broadcast = decode_buffer_frame(include_bytes!("../../../../server/internal/buffer/testdata/broadcast.frame")).unwrap();
+        assert_eq!(broadcast.session_id, "test-session-broadcast");
+        assert_eq!((broadcast.cols, broadcast.rows, broadcast.cursor), (80, 24, Cursor { x: 5, y: 2 }));
+        assert_eq!(broadcast.cell(4, 0).unwrap().fg_color, 7);
+        assert_eq!(broadcast.text(), "Hello");
+
+        let no_cells = decode_buffer_frame(include_bytes!("../../../../server/internal/buffer/testdata/no_cells.frame")).unwrap();
+        assert_eq!(no_cells.session_id, "test-session-multi");
+        assert_eq!(no_cells.cursor, Cursor { x: 10, y: 5 });
+        assert!(no_cells.cells.is_empty());
+        assert_eq!(no_cells.text(), "");
+    }
+
+    #[test]
+    fn test_clamps_go_ints_out_of_range() {
+        let snapshot = decode_buffer_frame(include_bytes!("../../../../server/internal/buffer/testdata/out_of_range.frame")).unwrap();
+        assert_eq!((snapshot.cols, snapshot.rows), (4, 2));
+        assert_eq!(snapshot.viewport_y, u32::MAX);
+        assert_eq!(snapshot.cursor, Cursor { x: 0, y: 2 });
+
+        let oversized = br#"{"cols":100000,"rows":-3,"cells":[]}"#;
+        let snapshot = decode_buffer_frame(&[&[0xBF, 0, 0, 0, 0][..], oversized].concat()).unwrap();
+        assert_eq!((snapshot.cols, snapshot.rows), (u16::MAX, 0));
+    }
+
+    #[tokio::test]
+    async fn test_fetches_snapshot_from_buffer_socket() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            ws.send(Message::Text(r#"{"type":"connected"}"#.to_string())).await.unwrap();
+            let subscribe = ws.next().await.unwrap().unwrap().into_text().unwrap();
+            assert!(subscribe.contains(r#""sessionId":"test-session-broadcast""#));
+            // Frames for other sessions on the same socket are skipped
+            ws.send(Message::Binary(include_bytes!("../../../../server/internal/buffer/testdata/no_cells.frame").to_vec())).await.unwrap();
+            ws.send(Message::Binary(include_bytes!("../../../../server/internal/buffer/testdata/broadcast.frame").to_vec())).await.unwrap();
+            let _ = ws.next().await;
+        });
+
+        let snapshot = fetch_buffer_snapshot(&server_url, "test-session-broadcast", Duration::from_secs(5)).await.unwrap();
+        assert_eq!(snapshot.text(), "Hello");
+        assert_eq!(buffers_url("https://example.com:4021/api?x=1").unwrap(), "wss://example.com:4021/buffers");
+    }
+
+    #[test]
+    fn test_rejects_malformed_frames() {
+        assert!(decode_buffer_frame(&[]).is_err());
+        assert!(decode_buffer_frame(b"{\"type\":\"connected\"}").is_err());
+        assert!(decode_buffer_frame(&[0xBF, 0xFF, 0xFF, 0xFF, 0xFF, b'a']).is_err());
+        assert!(decode_buffer_frame(&[0xBF, 1, 0, 0, 0, b'a', b'{']).is_err());
+
+        let too_wide = br#"{"cols":1,"rows":1,"cells":[[{"char":"a"},{"char":"b"}]]}"#;
+        assert!(decode_buffer_frame(&[&[0xBF, 0, 0, 0, 0][..], too_wide].concat()).unwrap_err().contains("at most 1"));
+    }
+
+    #[test]
+    fn test_round_trips_go_frames() {
+        fn prop(snapshot: TerminalSnapshot) -> bool {
+            decode_buffer_frame(&encode_go_frame(&snapshot)).as_ref() == Ok(&snapshot)
+        }
+        quickcheck(prop as fn(TerminalSnapshot) -> bool);
+    }
+
+    #[test]
+    fn test_arbitrary_bytes_never_panic() {
+        fn prop(body: Vec<u8>, id_len: u8) -> bool {
+            let mut frame = vec![BUFFER_MAGIC_BYTE, id_len, 0, 0, 0];
+            frame.extend(body);
+            let _ = decode_buffer_frame(&frame);
+            true
+        }
+        quickcheck(prop as fn(Vec<u8>, u8) -> bool);
+    }
+}
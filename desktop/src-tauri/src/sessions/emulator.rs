@@ -0,0 +1,775 @@
+// Headless terminal emulator
+// Replays a session's PTY output into a VT100/xterm screen with scrollback, for previews without a webview
+
+use super::buffer::CellAttributes;
+use std::collections::VecDeque;
+use unicode_width::UnicodeWidthChar;
+use vte::{Params, ParamsIter, Parser, Perform};
+
+pub const DEFAULT_SCROLLBACK_LINES: usize = 1000;
+const TAB_WIDTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Color {
+    #[default]
+    Default,
+    /// 0-15 are the ANSI colors, 16-255 the xterm cube and grays
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Color,
+    pub bg: Color,
+    pub attributes: CellAttributes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    /// 2 for the first half of a wide character, 0 for its second half
+    pub width: u8,
+    pub style: Style,
+}
+
+impl Cell {
+    fn blank(style: Style) -> Self {
+        // Erased cells keep the current background, as xterm does
+        Self { ch: ' ', width: 1, style: Style { bg: style.bg, ..Style::default() } }
+    }
+}
+
+type Row = Vec<Cell>;
+
+fn blank_row(cols: usize, style: Style) -> Row {
+    vec![Cell::blank(style); cols]
+}
+
+fn row_text(row: &[Cell]) -> String {
+    let text: String = row.iter().filter(|cell| cell.width > 0).map(|cell| cell.ch).collect();
+    text.trim_end().to_string()
+}
+
+/// DEC special graphics, selected with `ESC ( 0` for line drawing
+fn dec_special_graphics(ch: char) -> char {
+    match ch {
+        '`' => '◆', 'a' => '▒', 'f' => '°', 'g' => '±', 'j' => '┘', 'k' => '┐', 'l' => '┌', 'm' => '└',
+        'n' => '┼', 'o' => '⎺', 'p' => '⎻', 'q' => '─', 'r' => '⎼', 's' => '⎽', 't' => '├', 'u' => '┤',
+        'v' => '┴', 'w' => '┬', 'x' => '│', 'y' => '≤', 'z' => '≥', '{' => 'π', '|' => '≠', '}' => '£',
+        '~' => '·',
+        other => other,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct SavedCursor {
+    x: usize,
+    y: usize,
+    style: Style,
+    origin_mode: bool,
+}
+
+/// Screen state driven by the parser
+struct Screen {
+    cols: usize,
+    rows: usize,
+    lines: Vec<Row>,
+    scrollback: VecDeque<Row>,
+    scrollback_limit: usize,
+    /// Primary screen and cursor while the alternate screen is shown
+    primary: Option<(Vec<Row>, SavedCursor)>,
+    x: usize,
+    y: usize,
+    /// Set after printing in the last column; the next character wraps first
+    wrap_pending: bool,
+    style: Style,
+    saved_cursor: Option<SavedCursor>,
+    scroll_top: usize,
+    scroll_bottom: usize,
+    cursor_visible: bool,
+    autowrap: bool,
+    insert_mode: bool,
+    origin_mode: bool,
+    /// Whether G0 and G1 hold DEC line drawing, and which of them is active
+    line_drawing: [bool; 2],
+    charset: usize,
+    last_char: Option<char>,
+    title: String,
+}
+
+impl Screen {
+    fn new(cols: usize, rows: usize, scrollback_limit: usize) -> Self {
+        Self {
+            cols,
+            rows,
+            lines: vec![blank_row(cols, Style::default()); rows],
+            scrollback: VecDeque::new(),
+            scrollback_limit,
+            primary: None,
+            x: 0,
+            y: 0,
+            wrap_pending: false,
+            style: Style::default(),
+            saved_cursor: None,
+            scroll_top: 0,
+            scroll_bottom: rows - 1,
+            cursor_visible: true,
+            autowrap: true,
+            insert_mode: false,
+            origin_mode: false,
+            line_drawing: [false; 2],
+            charset: 0,
+            last_char: None,
+            title: String::new(),
+        }
+    }
+
+    fn reset(&mut self) {
+        let title = std::mem::take(&mut self.title);
+        *self = Screen::new(self.cols, self.rows, self.scrollback_limit);
+        self.title = title;
+    }
+
+    fn resize(&mut self, cols: usize, rows: usize) {
+        let fit_row = |row: &mut Row| {
+            row.resize(cols, Cell::blank(Style::default()));
+            // A wide character cut in half by the new edge
+            if row.last().is_some_and(|cell| cell.width == 2) {
+                row[cols - 1] = Cell::blank(Style::default());
+            }
+        };
+
+        // Keep the cursor line visible: shrinking drops lines from the top into scrollback first
+        let overflow = (self.y + 1).saturating_sub(rows);
+        for row in self.lines.drain(..overflow).collect::<Vec<_>>() {
+            self.push_scrollback(row);
+        }
+        self.y -= overflow;
+        self.lines.resize(rows, blank_row(cols, Style::default()));
+        self.lines.iter_mut().for_each(fit_row);
+        self.scrollback.iter_mut().for_each(fit_row);
+
+        if let Some((lines, cursor)) = &mut self.primary {
+            lines.truncate(rows);
+            lines.resize(rows, blank_row(cols, Style::default()));
+            lines.iter_mut().for_each(fit_row);
+            cursor.x = cursor.x.min(cols - 1);
+            cursor.y = cursor.y.min(rows - 1);
+        }
+
+        self.cols = cols;
+        self.rows = rows;
+        self.scroll_top = 0;
+        self.scroll_bottom = rows - 1;
+        self.x = self.x.min(cols - 1);
+        self.wrap_pending = false;
+    }
+
+    fn push_scrollback(&mut self, row: Row) {
+        if self.primary.is_some() || self.scrollback_limit == 0 {
+            return;
+        }
+        if self.scrollback.len() == self.scrollback_limit {
+            self.scrollback.pop_front();
+        }
+        self.scrollback.push_back(row);
+    }
+
+    fn print_char(&mut self, ch: char) {
+        let ch = if self.line_drawing[self.charset] { dec_special_graphics(ch) } else { ch };
+        let width = match ch.width() {
+            Some(width @ 1..=2) => width,
+            // Combining marks and other zero-width characters are not tracked
+            _ => return,
+        };
+
+        if self.wrap_pending && self.autowrap {
+            self.x = 0;
+            self.linefeed();
+        }
+        self.wrap_pending = false;
+
+        if width == 2 && self.x == self.cols - 1 {
+            if !self.autowrap || self.cols < 2 {
+                return;
+            }
+            self.lines[self.y][self.x] = Cell::blank(self.style);
+            self.x = 0;
+            self.linefeed();
+        }
+
+        if self.insert_mode {
+            self.insert_blanks(width);
+        }
+
+        self.clear_wide_char_at(self.x);
+        if width == 2 {
+            self.clear_wide_char_at(self.x + 1);
+        }
+        let style = self.style;
+        let row = &mut self.lines[self.y];
+        row[self.x] = Cell { ch, width: width as u8, style };
+        if width == 2 {
+            row[self.x + 1] = Cell { ch: ' ', width: 0, style };
+        }
+        self.last_char = Some(ch);
+
+        if self.x + width >= self.cols {
+            self.x = self.cols - 1;
+            self.wrap_pending = self.autowrap;
+        } else {
+            self.x += width;
+        }
+    }
+
+    /// Blank both halves of a wide character that is about to be partly overwritten
+    fn clear_wide_char_at(&mut self, x: usize) {
+        let row = &mut self.lines[self.y];
+        match row.get(x).map(|cell| cell.width) {
+            Some(0) if x > 0 => {
+                row[x - 1] = Cell::blank(Style::default());
+                row[x] = Cell::blank(Style::default());
+            }
+            Some(2) if x + 1 < row.len() => {
+                row[x] = Cell::blank(Style::default());
+                row[x + 1] = Cell::blank(Style::default());
+            }
+            _ => {}
+        }
+    }
+
+    fn linefeed(&mut self) {
+        if self.y == self.scroll_bottom {
+            self.scroll_up(1);
+        } else if self.y < self.rows - 1 {
+            self.y += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        if self.y == self.scroll_top {
+            self.scroll_down(1);
+        } else {
+            self.y = self.y.saturating_sub(1);
+        }
+    }
+
+    fn scroll_up(&mut self, count: usize) {
+        let count = count.min(self.scroll_bottom - self.scroll_top + 1);
+        for _ in 0..count {
+            let row = self.lines.remove(self.scroll_top);
+            self.lines.insert(self.scroll_bottom, blank_row(self.cols, self.style));
+            if self.scroll_top == 0 {
+                self.push_scrollback(row);
+            }
+        }
+    }
+
+    fn scroll_down(&mut self, count: usize) {
+        let count = count.min(self.scroll_bottom - self.scroll_top + 1);
+        for _ in 0..count {
+            self.lines.remove(self.scroll_bottom);
+            self.lines.insert(self.scroll_top, blank_row(self.cols, self.style));
+        }
+    }
+
+    fn insert_blanks(&mut self, count: usize) {
+        let count = count.min(self.cols - self.x);
+        let blank = Cell::blank(self.style);
+        let row = &mut self.lines[self.y];
+        row.truncate(self.cols - count);
+        row.splice(self.x..self.x, std::iter::repeat_n(blank, count));
+    }
+
+    fn delete_chars(&mut self, count: usize) {
+        let count = count.min(self.cols - self.x);
+        let blank = Cell::blank(self.style);
+        let row = &mut self.lines[self.y];
+        row.drain(self.x..self.x + count);
+        row.resize(self.cols, blank);
+    }
+
+    /// Insert (positive) or delete lines at the cursor, within the scroll region
+    fn insert_lines(&mut self, count: usize, insert: bool) {
+        if self.y < self.scroll_top || self.y > self.scroll_bottom {
+            return;
+        }
+        let count = count.min(self.scroll_bottom - self.y + 1);
+        for _ in 0..count {
+            if insert {
+                self.lines.remove(self.scroll_bottom);
+                self.lines.insert(self.y, blank_row(self.cols, self.style));
+            } else {
+                self.lines.remove(self.y);
+                self.lines.insert(self.scroll_bottom, blank_row(self.cols, self.style));
+            }
+        }
+        self.x = 0;
+    }
+
+    fn erase_cells(&mut self, y: usize, from: usize, to: usize) {
+        let blank = Cell::blank(self.style);
+        let to = to.min(self.cols);
+        if from < to {
+            self.lines[y][from..to].fill(blank);
+        }
+    }
+
+    fn erase_display(&mut self, mode: u16) {
+        match mode {
+            0 => {
+                self.erase_cells(self.y, self.x, self.cols);
+                for y in self.y + 1..self.rows {
+                    self.erase_cells(y, 0, self.cols);
+                }
+            }
+            1 => {
+                for y in 0..self.y {
+                    self.erase_cells(y, 0, self.cols);
+                }
+                self.erase_cells(self.y, 0, self.x + 1);
+            }
+            2 => (0..self.rows).for_each(|y| self.erase_cells(y, 0, self.cols)),
+            3 => self.scrollback.clear(),
+            _ => {}
+        }
+    }
+
+    fn erase_line(&mut self, mode: u16) {
+        match mode {
+            0 => self.erase_cells(self.y, self.x, self.cols),
+            1 => self.erase_cells(self.y, 0, self.x + 1),
+            2 => self.erase_cells(self.y, 0, self.cols),
+            _ => {}
+        }
+    }
+
+    fn move_to(&mut self, x: usize, y: usize) {
+        let (top, bottom) = if self.origin_mode { (self.scroll_top, self.scroll_bottom) } else { (0, self.rows - 1) };
+        self.x = x.min(self.cols - 1);
+        self.y = (top + y).min(bottom);
+        self.wrap_pending = false;
+    }
+
+    /// Vertical moves stop at the scroll region edges when they start inside it
+    fn move_vertically(&mut self, delta: isize) {
+        let (top, bottom) = if self.y >= self.scroll_top && self.y <= self.scroll_bottom {
+            (self.scroll_top, self.scroll_bottom)
+        } else {
+            (0, self.rows - 1)
+        };
+        self.y = (self.y as isize + delta).clamp(top as isize, bottom as isize) as usize;
+        self.wrap_pending = false;
+    }
+
+    fn move_horizontally(&mut self, delta: isize) {
+        self.x = (self.x as isize + delta).clamp(0, self.cols as isize - 1) as usize;
+        self.wrap_pending = false;
+    }
+
+    fn tab(&mut self, count: usize) {
+        for _ in 0..count {
+            self.x = ((self.x / TAB_WIDTH + 1) * TAB_WIDTH).min(self.cols - 1);
+        }
+    }
+
+    fn back_tab(&mut self, count: usize) {
+        for _ in 0..count {
+            self.x = (self.x.saturating_sub(1) / TAB_WIDTH) * TAB_WIDTH;
+        }
+    }
+
+    fn save_cursor(&mut self) {
+        self.saved_cursor = Some(self.cursor_state());
+    }
+
+    fn restore_cursor(&mut self) {
+        let saved = self.saved_cursor.unwrap_or_default();
+        self.restore_cursor_state(saved);
+    }
+
+    fn cursor_state(&self) -> SavedCursor {
+        SavedCursor { x: self.x, y: self.y, style: self.style, origin_mode: self.origin_mode }
+    }
+
+    fn restore_cursor_state(&mut self, saved: SavedCursor) {
+        self.x = saved.x.min(self.cols - 1);
+        self.y = saved.y.min(self.rows - 1);
+        self.style = saved.style;
+        self.origin_mode = saved.origin_mode;
+        self.wrap_pending = false;
+    }
+
+    fn enter_alternate_screen(&mut self) {
+        if self.primary.is_none() {
+            let lines = std::mem::replace(&mut self.lines, vec![blank_row(self.cols, Style::default()); self.rows]);
+            self.primary = Some((lines, self.cursor_state()));
+        }
+    }
+
+    fn leave_alternate_screen(&mut self) {
+        if let Some((lines, cursor)) = self.primary.take() {
+            self.lines = lines;
+            self.restore_cursor_state(cursor);
+        }
+    }
+
+    fn set_mode(&mut self, mode: u16, private: bool, enabled: bool) {
+        match (private, mode) {
+            (false, 4) => self.insert_mode = enabled,
+            (true, 6) => {
+                self.origin_mode = enabled;
+                self.move_to(0, 0);
+            }
+            (true, 7) => self.autowrap = enabled,
+            (true, 25) => self.cursor_visible = enabled,
+            (true, 47 | 1047) if enabled => self.enter_alternate_screen(),
+            (true, 47 | 1047) => self.leave_alternate_screen(),
+            (true, 1049) if enabled => {
+                self.save_cursor();
+                self.enter_alternate_screen();
+            }
+            (true, 1049) => {
+                self.leave_alternate_screen();
+                self.restore_cursor();
+            }
+            _ => {}
+        }
+    }
+
+    fn set_scroll_region(&mut self, top: usize, bottom: usize) {
+        let bottom = if bottom == 0 { self.rows } else { bottom.min(self.rows) };
+        let top = top.max(1);
+        if top < bottom {
+            self.scroll_top = top - 1;
+            self.scroll_bottom = bottom - 1;
+            self.move_to(0, 0);
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, params: &Params) {
+        if params.is_empty() {
+            self.style = Style::default();
+            return;
+        }
+
+        let mut iter = params.iter();
+        while let Some(param) = iter.next() {
+            let attributes = &mut self.style.attributes;
+            match param {
+                [0] => self.style = Style::default(),
+                [1] => attributes.bold = true,
+                [2] => attributes.dim = true,
+                [3] => attributes.italic = true,
+                [4, 0] => attributes.underline = false,
+                [4, ..] | [21] => attributes.underline = true,
+                [5] | [6] => attributes.blink = true,
+                [7] => attributes.inverse = true,
+                [9] => attributes.strikeout = true,
+                [22] => {
+                    attributes.bold = false;
+                    attributes.dim = false;
+                }
+                [23] => attributes.italic = false,
+                [24] => attributes.underline = false,
+                [25] => attributes.blink = false,
+                [27] => attributes.inverse = false,
+                [29] => attributes.strikeout = false,
+                [n @ 30..=37] => self.style.fg = Color::Indexed((n - 30) as u8),
+                [38, ..] => self.style.fg = extended_color(param, &mut iter).unwrap_or(self.style.fg),
+                [39] => self.style.fg = Color::Default,
+                [n @ 40..=47] => self.style.bg = Color::Indexed((n - 40) as u8),
+                [48, ..] => self.style.bg = extended_color(param, &mut iter).unwrap_or(self.style.bg),
+                [49] => self.style.bg = Color::Default,
+                [n @ 90..=97] => self.style.fg = Color::Indexed((n - 90 + 8) as u8),
+                [n @ 100..=107] => self.style.bg = Color::Indexed((n - 100 + 8) as u8),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// `38;5;n` / `38;2;r;g;b`, or the same with colons as sub-parameters
+fn extended_color(param: &[u16], iter: &mut ParamsIter) -> Option<Color> {
+    let component = |value: u16| value.min(255) as u8;
+
+    if param.len() > 1 {
+        return match param[1..] {
+            [5, index] => Some(Color::Indexed(component(index))),
+            [2, r, g, b] | [2, _, r, g, b, ..] => Some(Color::Rgb(component(r), component(g), component(b))),
+            _ => None,
+        };
+    }
+
+    let mut next = || iter.next().and_then(|param| param.first().copied());
+    match next()? {
+        5 => Some(Color::Indexed(component(next()?))),
+        2 => Some(Color::Rgb(component(next()?), component(next()?), component(next()?))),
+        _ => None,
+    }
+}
+
+impl Perform for Screen {
+    fn print(&mut self, ch: char) {
+        self.print_char(ch);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            0x08 => self.move_horizontally(-1),
+            0x09 => self.tab(1),
+            0x0A..=0x0C => self.linefeed(),
+            0x0D => {
+                self.x = 0;
+                self.wrap_pending = false;
+            }
+            0x0E => self.charset = 1,
+            0x0F => self.charset = 0,
+            _ => {}
+        }
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        if let [b"0" | b"2", title @ ..] = params {
+            let title: Vec<String> = title.iter().map(|part| String::from_utf8_lossy(part).into_owned()).collect();
+            self.title = title.join(";");
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        if ignore {
+            return;
+        }
+
+        let values: Vec<u16> = params.iter().map(|param| param[0]).collect();
+        // Missing and zero parameters both mean the default
+        let arg = |index: usize, default: usize| match values.get(index) {
+            Some(&value) if value > 0 => value as usize,
+            _ => default,
+        };
+        let count = arg(0, 1);
+
+        match (intermediates, action) {
+            ([], 'A') => self.move_vertically(-(count as isize)),
+            ([], 'B' | 'e') => self.move_vertically(count as isize),
+            ([], 'C' | 'a') => self.move_horizontally(count as isize),
+            ([], 'D') => self.move_horizontally(-(count as isize)),
+            ([], 'E') => {
+                self.move_vertically(count as isize);
+                self.x = 0;
+            }
+            ([], 'F') => {
+                self.move_vertically(-(count as isize));
+                self.x = 0;
+            }
+            ([], 'G' | '`') => self.move_horizontally(count as isize - 1 - self.x as isize),
+            ([], 'H' | 'f') => self.move_to(arg(1, 1) - 1, count - 1),
+            ([], 'd') => {
+                let x = self.x;
+                self.move_to(x, count - 1);
+            }
+            ([], 'I') => self.tab(count),
+            ([], 'Z') => self.back_tab(count),
+            ([] | [b'?'], 'J') => self.erase_display(arg(0, 0) as u16),
+            ([] | [b'?'], 'K') => self.erase_line(arg(0, 0) as u16),
+            ([], 'X') => self.erase_cells(self.y, self.x, self.x + count),
+            ([], 'L') => self.insert_lines(count, true),
+            ([], 'M') => self.insert_lines(count, false),
+            ([], '@') => self.insert_blanks(count),
+            ([], 'P') => self.delete_chars(count),
+            ([], 'S') => self.scroll_up(count),
+            // With more parameters this is mouse highlight tracking
+            ([], 'T') if values.len() <= 1 => self.scroll_down(count),
+            ([], 'b') => {
+                if let Some(ch) = self.last_char {
+                    (0..count).for_each(|_| self.print_char(ch));
+                }
+            }
+            ([], 'm') => self.select_graphic_rendition(params),
+            ([], 'r') => self.set_scroll_region(arg(0, 1), arg(1, 0)),
+            ([], 's') => self.save_cursor(),
+            ([], 'u') => self.restore_cursor(),
+            ([], 'h') => values.iter().for_each(|&mode| self.set_mode(mode, false, true)),
+            ([], 'l') => values.iter().for_each(|&mode| self.set_mode(mode, false, false)),
+            ([b'?'], 'h') => values.iter().for_each(|&mode| self.set_mode(mode, true, true)),
+            ([b'?'], 'l') => values.iter().for_each(|&mode| self.set_mode(mode, true, false)),
+            // Device status and attribute queries would need a reply; this emulator only watches
+            _ => {}
+        }
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, byte: u8) {
+        match (intermediates, byte) {
+            ([], b'7') => self.save_cursor(),
+            ([], b'8') => self.restore_cursor(),
+            ([], b'D') => self.linefeed(),
+            ([], b'E') => {
+                self.x = 0;
+                self.linefeed();
+            }
+            ([], b'M') => self.reverse_index(),
+            ([], b'c') => self.reset(),
+            ([b'('], charset) => self.line_drawing[0] = charset == b'0',
+            ([b')'], charset) => self.line_drawing[1] = charset == b'0',
+            _ => {}
+        }
+    }
+}
+
+/// A terminal screen fed with raw output; no input side and no rendering of its own
+pub struct Terminal {
+    parser: Parser,
+    screen: Screen,
+}
+
+impl Terminal {
+    pub fn new(cols: u16, rows: u16) -> Self {
+        Self::with_scrollback(cols, rows, DEFAULT_SCROLLBACK_LINES)
+    }
+
+    pub fn with_scrollback(cols: u16, rows: u16, scrollback_lines: usize) -> Self {
+        Self {
+            parser: Parser::new(),
+            screen: Screen::new(cols.max(1) as usize, rows.max(1) as usize, scrollback_lines),
+        }
+    }
+
+    pub fn process(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.parser.advance(&mut self.screen, byte);
+        }
+    }
+
+    /// Change the size without reflowing; lines pushed off the top go to scrollback
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        let (cols, rows) = (cols.max(1) as usize, rows.max(1) as usize);
+        if (cols, rows) != (self.screen.cols, self.screen.rows) {
+            self.screen.resize(cols, rows);
+        }
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        (self.screen.cols as u16, self.screen.rows as u16)
+    }
+
+    /// Window title from the last OSC 0 or 2
+    pub fn title(&self) -> &str {
+        &self.screen.title
+    }
+
+    pub fn cursor(&self) -> (u16, u16) {
+        (self.screen.x as u16, self.screen.y as u16)
+    }
+
+    pub fn cursor_visible(&self) -> bool {
+        self.screen.cursor_visible
+    }
+
+    pub fn is_alternate_screen(&self) -> bool {
+        self.screen.primary.is_some()
+    }
+
+    pub fn cell(&self, x: u16, y: u16) -> Option<&Cell> {
+        self.screen.lines.get(y as usize)?.get(x as usize)
+    }
+
+    pub fn scrollback_len(&self) -> usize {
+        self.screen.scrollback.len()
+    }
+
+    /// Visible rows as text, without trailing blanks
+    pub fn screen_lines(&self) -> Vec<String> {
+        self.screen.lines.iter().map(|row| row_text(row)).collect()
+    }
+
+    /// Visible screen as text, without the blank lines below the last output
+    pub fn screen_text(&self) -> String {
+        let mut lines = self.screen_lines();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+
+    /// Up to `count` lines of scrollback and screen, ending at the last line with output
+    pub fn last_lines(&self, count: usize) -> Vec<String> {
+        let mut lines: Vec<String> = self.screen.scrollback.iter()
+            .chain(self.screen.lines.iter())
+            .map(|row| row_text(row))
+            .collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        lines.split_off(lines.len().saturating_sub(count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prints_wraps_and_scrolls_into_scrollback() {
+        let mut terminal = Terminal::with_scrollback(5, 2, 10);
+        terminal.process("ab\r\ncdefgh\r\n界x".as_bytes());
+
+        assert_eq!(terminal.screen_lines(), vec!["h", "界x"]);
+        assert_eq!(terminal.last_lines(10), vec!["ab", "cdefg", "h", "界x"]);
+        assert_eq!(terminal.last_lines(2), vec!["h", "界x"]);
+        assert_eq!(terminal.cell(0, 1).unwrap().width, 2);
+        assert_eq!(terminal.cursor(), (3, 1));
+    }
+
+    #[test]
+    fn test_cursor_movement_and_erasing() {
+        let mut terminal = Terminal::new(10, 3);
+        terminal.process(b"hello\x1b[2;3Hworld\x1b[1;2H\x1b[K\x1b[3;1Hxyz\x1b[2D\x1b[1P\x1b[2@!");
+
+        assert_eq!(terminal.screen_lines(), vec!["h", "  world", "x! z"]);
+
+        terminal.process(b"\x1b[2J");
+        assert_eq!(terminal.screen_text(), "");
+    }
+
+    #[test]
+    fn test_sgr_colors_and_title() {
+        let mut terminal = Terminal::new(10, 2);
+        terminal.process(b"\x1b]0;build: make\x07\x1b[1;31ma\x1b[38;5;208mb\x1b[48;2;1;2;3mc\x1b[38:2::10:20:30md\x1b[0me");
+
+        assert_eq!(terminal.title(), "build: make");
+        let style = |x| terminal.cell(x, 0).unwrap().style;
+        assert!(style(0).attributes.bold);
+        assert_eq!(style(0).fg, Color::Indexed(1));
+        assert_eq!(style(1).fg, Color::Indexed(208));
+        assert_eq!(style(2).bg, Color::Rgb(1, 2, 3));
+        assert_eq!(style(3).fg, Color::Rgb(10, 20, 30));
+        assert_eq!(style(4), Style::default());
+    }
+
+    #[test]
+    fn test_alternate_screen_and_scroll_region() {
+        let mut terminal = Terminal::new(6, 4);
+        terminal.process(b"$ top\r\n\x1b[?1049h\x1b[2;3rA\r\nB\x1b[3;1HC\nD\x1b(0lqk");
+
+        assert!(terminal.is_alternate_screen());
+        assert_eq!(terminal.screen_lines(), vec!["A", "C", " D┌─┐", ""]);
+        assert_eq!(terminal.scrollback_len(), 0);
+
+        terminal.process(b"\x1b[?1049l");
+        assert!(!terminal.is_alternate_screen());
+        assert_eq!(terminal.screen_text(), "$ top");
+        assert_eq!(terminal.cursor(), (0, 1));
+    }
+
+    #[test]
+    fn test_resize_keeps_cursor_line() {
+        let mut terminal = Terminal::new(8, 4);
+        terminal.process(b"1\r\n2\r\n3\r\n4");
+        terminal.resize(3, 2);
+
+        assert_eq!(terminal.screen_lines(), vec!["3", "4"]);
+        assert_eq!(terminal.last_lines(4), vec!["1", "2", "3", "4"]);
+        assert_eq!(terminal.cursor(), (1, 1));
+    }
+}
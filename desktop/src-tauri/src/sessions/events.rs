@@ -0,0 +1,407 @@
+// Server-Sent Events session feed
+// Follows /api/events for session changes and falls back to polling while the stream is down
+
+use super::{SessionEvent, SessionEventKind, SessionManager, SessionMonitor, LOCAL_BYPASS_HEADER};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::{broadcast, watch};
+use tokio::time::{interval_at, sleep, timeout, Duration, Instant};
+use log::{debug, info, warn};
+
+const CONNECT_TIMEOUT_SECS: u64 = 5;
+const INITIAL_BACKOFF_MS: u64 = 1000;
+const MAX_BACKOFF_MS: u64 = 30_000;
+/// The server writes a heartbeat comment every 30s, so this much silence means a dead connection
+const IDLE_TIMEOUT_SECS: u64 = 90;
+/// The server only broadcasts exits of deleted sessions, so the list is still reconciled now and then
+const RECONCILE_INTERVAL_SECS: u64 = 60;
+
+/// An event from the server's `/api/events` stream (`types.ServerEvent` in the Go server)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub session_name: Option<String>,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Milliseconds
+    #[serde(default)]
+    pub duration: Option<u64>,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub timestamp: String,
+}
+
+impl ServerEvent {
+    /// Session start and exit change the session list; it is refetched so the cache diff reports them
+    pub fn changes_session_list(&self) -> bool {
+        matches!(self.event_type.as_str(), "session-start" | "session-exit")
+    }
+}
+
+/// The typed session event for a server event; session start shows up through the list refresh instead
+fn session_event_from_server(event: &ServerEvent) -> Option<SessionEvent> {
+    let kind = match event.event_type.as_str() {
+        "session-exit" => SessionEventKind::Exited { exit_code: event.exit_code },
+        "command-finished" => SessionEventKind::CommandFinished {
+            command: event.command.clone(),
+            exit_code: event.exit_code,
+            duration_ms: event.duration,
+        },
+        "command-error" => SessionEventKind::CommandError {
+            command: event.command.clone(),
+            exit_code: event.exit_code,
+            message: event.message.clone(),
+        },
+        "bell" => SessionEventKind::Bell,
+        "claude-turn" => SessionEventKind::ClaudeTurn,
+        _ => return None,
+    };
+
+    Some(SessionEvent::new(event.session_id.as_deref()?, kind))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseMessage {
+    pub id: Option<String>,
+    pub event: String,
+    pub data: String,
+}
+
+/// Incremental `text/event-stream` parser; chunks may split lines or UTF-8 sequences anywhere
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Option<String>,
+    pub last_event_id: Option<String>,
+    pub retry_ms: Option<u64>,
+}
+
+impl SseParser {
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseMessage> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut messages = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if let Some(data) = self.data.take() {
+                    messages.push(SseMessage {
+                        id: self.last_event_id.clone(),
+                        event: self.event.take().unwrap_or_else(|| "message".to_string()),
+                        data,
+                    });
+                }
+                self.event = None;
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some(("", _)) => continue, // Comment, e.g. the server's heartbeat
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+
+            match field {
+                "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+                "event" => self.event = Some(value.to_string()),
+                "data" => match &mut self.data {
+                    Some(data) => {
+                        data.push('\n');
+                        data.push_str(value);
+                    }
+                    None => self.data = Some(value.to_string()),
+                },
+                "retry" => {
+                    if let Ok(retry_ms) = value.parse() {
+                        self.retry_ms = Some(retry_ms);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        messages
+    }
+}
+
+/// Client for long-lived SSE responses: no overall timeout, since the body is meant to stay open
+pub(super) fn streaming_client() -> reqwest::Client {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(LOCAL_BYPASS_HEADER, reqwest::header::HeaderValue::from_static("true"));
+    reqwest::Client::builder()
+        .default_headers(headers)
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
+/// Exponential reconnect delay, starting from the server's `retry:` hint when it sent one
+#[derive(Debug)]
+pub(super) struct Backoff {
+    initial_ms: u64,
+    next_ms: u64,
+}
+
+impl Backoff {
+    pub(super) fn new() -> Self {
+        Self { initial_ms: INITIAL_BACKOFF_MS, next_ms: INITIAL_BACKOFF_MS }
+    }
+
+    pub(super) fn next_delay(&mut self) -> Duration {
+        let delay = self.next_ms;
+        self.next_ms = (delay * 2).min(MAX_BACKOFF_MS);
+        Duration::from_millis(delay)
+    }
+
+    pub(super) fn reset(&mut self, retry_hint_ms: Option<u64>) {
+        if let Some(retry_ms) = retry_hint_ms {
+            self.initial_ms = retry_ms.min(MAX_BACKOFF_MS);
+        }
+        self.next_ms = self.initial_ms;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SessionStreamEvent {
+    Connected,
+    Disconnected { error: String },
+    Server(ServerEvent),
+    Stopped,
+}
+
+/// Long-lived SSE connection to the server. While it is down the `SessionMonitor` polls instead.
+#[derive(Clone)]
+pub struct SessionEventStream {
+    session_manager: SessionManager,
+    monitor: SessionMonitor,
+    client: reqwest::Client,
+    // Dropping the sender stops the running connection loop
+    shutdown: Arc<Mutex<Option<watch::Sender<()>>>>,
+    connected: Arc<AtomicBool>,
+    last_event_id: Arc<Mutex<Option<String>>>,
+    sender: broadcast::Sender<SessionStreamEvent>,
+}
+
+impl SessionEventStream {
+    pub fn new(session_manager: SessionManager, monitor: SessionMonitor) -> Self {
+        let (sender, _) = broadcast::channel(100);
+
+        Self {
+            session_manager,
+            monitor,
+            client: streaming_client(),
+            shutdown: Arc::new(Mutex::new(None)),
+            connected: Arc::new(AtomicBool::new(false)),
+            last_event_id: Arc::new(Mutex::new(None)),
+            sender,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionStreamEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.shutdown.lock().unwrap().is_some()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    pub fn start(&self) -> Result<(), String> {
+        let receiver = {
+            let mut shutdown = self.shutdown.lock().unwrap();
+            if shutdown.is_some() {
+                return Err("Session event stream is already running".to_string());
+            }
+            let (sender, receiver) = watch::channel(());
+            *shutdown = Some(sender);
+            receiver
+        };
+
+        info!("Starting session event stream");
+        let stream = self.clone();
+        tauri::async_runtime::spawn(async move { stream.run(receiver).await });
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if self.shutdown.lock().unwrap().take().is_none() {
+            return;
+        }
+
+        info!("Stopping session event stream");
+        self.connected.store(false, Ordering::SeqCst);
+        self.monitor.stop_monitoring();
+        let _ = self.sender.send(SessionStreamEvent::Stopped);
+    }
+
+    async fn run(self, mut shutdown: watch::Receiver<()>) {
+        let mut backoff = Backoff::new();
+
+        loop {
+            let error = tokio::select! {
+                error = self.follow(&mut backoff) => error,
+                _ = shutdown.changed() => break,
+            };
+
+            self.connected.store(false, Ordering::SeqCst);
+            let delay = backoff.next_delay();
+            debug!("Session event stream unavailable ({}), retrying in {:?}", error, delay);
+            let _ = self.sender.send(SessionStreamEvent::Disconnected { error });
+
+            // Poll until the stream is back; an error only means polling is already running
+            let _ = self.monitor.start_monitoring();
+
+            tokio::select! {
+                _ = sleep(delay) => {}
+                _ = shutdown.changed() => break,
+            }
+        }
+    }
+
+    /// Connect and dispatch events until the connection fails, returning why it ended
+    async fn follow(&self, backoff: &mut Backoff) -> String {
+        let url = format!("{}/api/events", self.session_manager.server_url());
+        let mut request = self.client.get(&url).header(reqwest::header::ACCEPT, "text/event-stream");
+        if let Some(last_event_id) = self.last_event_id.lock().unwrap().clone() {
+            request = request.header("Last-Event-ID", last_event_id);
+        }
+
+        let mut response = match request.send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => return format!("HTTP {}", response.status()),
+            Err(e) => return format!("Failed to connect: {}", e),
+        };
+
+        info!("Connected to session event stream");
+        self.connected.store(true, Ordering::SeqCst);
+        self.monitor.stop_monitoring();
+        let _ = self.sender.send(SessionStreamEvent::Connected);
+
+        let mut parser = SseParser::default();
+        backoff.reset(None);
+
+        // The server does not replay missed events, so catch up on whatever changed meanwhile
+        self.refresh_sessions().await;
+
+        let period = Duration::from_secs(RECONCILE_INTERVAL_SECS);
+        let mut reconcile = interval_at(Instant::now() + period, period);
+
+        loop {
+            tokio::select! {
+                chunk = timeout(Duration::from_secs(IDLE_TIMEOUT_SECS), response.chunk()) => {
+                    let chunk = match chunk {
+                        Ok(Ok(Some(chunk))) => chunk,
+                        Ok(Ok(None)) => return "Server closed the event stream".to_string(),
+                        Ok(Err(e)) => return format!("Event stream failed: {}", e),
+                        Err(_) => return format!("No events or heartbeats for {}s", IDLE_TIMEOUT_SECS),
+                    };
+
+                    for message in parser.feed(&chunk) {
+                        self.dispatch(message).await;
+                    }
+                    *self.last_event_id.lock().unwrap() = parser.last_event_id.clone();
+                    backoff.reset(parser.retry_ms);
+                }
+                _ = reconcile.tick() => self.refresh_sessions().await,
+            }
+        }
+    }
+
+    async fn dispatch(&self, message: SseMessage) {
+        let event: ServerEvent = match serde_json::from_str(&message.data) {
+            Ok(event) => event,
+            Err(e) => {
+                warn!("Ignoring malformed {} event: {}", message.event, e);
+                return;
+            }
+        };
+
+        if let Some(session_event) = session_event_from_server(&event) {
+            self.session_manager.publish_server_event(session_event);
+        }
+        if event.changes_session_list() {
+            self.refresh_sessions().await;
+        }
+
+        let _ = self.sender.send(SessionStreamEvent::Server(event));
+    }
+
+    async fn refresh_sessions(&self) {
+        if let Err(e) = self.session_manager.fetch_sessions().await {
+            warn!("Failed to refresh sessions: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_split_chunks() {
+        let mut parser = SseParser::default();
+
+        assert!(parser.feed(b":heartbeat\n\nid: 1\nevent: session-st").is_empty());
+        let messages = parser.feed(b"art\r\ndata: {\"type\":\"session-start\",\r\ndata: \"sessionId\":\"a1\"}\n\n");
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].id.as_deref(), Some("1"));
+        assert_eq!(messages[0].event, "session-start");
+        assert_eq!(messages[0].data, "{\"type\":\"session-start\",\n\"sessionId\":\"a1\"}");
+
+        parser.feed(b"retry: 5000\n\n");
+        assert_eq!(parser.retry_ms, Some(5000));
+        assert_eq!(parser.last_event_id.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_maps_server_events() {
+        let finished: ServerEvent = serde_json::from_str(
+            r#"{"type":"command-finished","sessionId":"a1","command":"make","exitCode":0,"duration":1500,"timestamp":""}"#,
+        ).unwrap();
+        assert!(!finished.changes_session_list());
+
+        let session_event = session_event_from_server(&finished).unwrap();
+        assert_eq!(session_event.session_id, "a1");
+        assert_eq!(session_event.kind, SessionEventKind::CommandFinished {
+            command: Some("make".to_string()),
+            exit_code: Some(0),
+            duration_ms: Some(1500),
+        });
+
+        let exit: ServerEvent = serde_json::from_str(r#"{"type":"session-exit","sessionId":"a1","exitCode":2,"timestamp":""}"#).unwrap();
+        assert!(exit.changes_session_list());
+        assert_eq!(session_event_from_server(&exit).unwrap().kind, SessionEventKind::Exited { exit_code: Some(2) });
+
+        let start: ServerEvent = serde_json::from_str(r#"{"type":"session-start","sessionId":"a1","timestamp":""}"#).unwrap();
+        assert!(session_event_from_server(&start).is_none());
+    }
+
+    #[test]
+    fn test_backoff_doubles_caps_and_honors_retry_hint() {
+        let mut backoff = Backoff::new();
+        let delays: Vec<u64> = (0..7).map(|_| backoff.next_delay().as_millis() as u64).collect();
+        assert_eq!(delays, vec![1000, 2000, 4000, 8000, 16000, 30000, 30000]);
+
+        backoff.reset(Some(5000));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(5000));
+        backoff.reset(None);
+        assert_eq!(backoff.next_delay(), Duration::from_millis(5000));
+    }
+}
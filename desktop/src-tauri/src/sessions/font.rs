@@ -0,0 +1,434 @@
+// 6x10 bitmap font for screen thumbnails
+// Glyphs from the public-domain X11 misc-fixed 6x10 font (ASCII, Latin-1, punctuation, arrows, box drawing, blocks)
+
+pub const GLYPH_WIDTH: usize = 6;
+pub const GLYPH_HEIGHT: usize = 10;
+
+/// Rows of the glyph for `ch`, top to bottom, most significant bit leftmost; `None` when not bundled
+pub fn glyph(ch: char) -> Option<&'static [u8; GLYPH_HEIGHT]> {
+    GLYPHS.binary_search_by_key(&(ch as u32), |&(code, _)| code).ok().map(|index| &GLYPHS[index].1)
+}
+
+/// Drawn for characters the font lacks
+pub fn fallback_glyph() -> &'static [u8; GLYPH_HEIGHT] {
+    &GLYPHS[0].1
+}
+
+/// Sorted by code point; entry 0 is the font's default character
+static GLYPHS: [(u32, [u8; GLYPH_HEIGHT]); 401] = [
+    (0x0000, [0x00, 0xA8, 0x00, 0x88, 0x00, 0x88, 0x00, 0xA8, 0x00, 0x00]),
+    (0x0020, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x0021, [0x00, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, 0x20, 0x00, 0x00]),
+    (0x0022, [0x00, 0x50, 0x50, 0x50, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x0023, [0x00, 0x50, 0x50, 0xF8, 0x50, 0xF8, 0x50, 0x50, 0x00, 0x00]),
+    (0x0024, [0x00, 0x20, 0x70, 0xA0, 0x70, 0x28, 0x70, 0x20, 0x00, 0x00]),
+    (0x0025, [0x00, 0x48, 0xA8, 0x50, 0x20, 0x50, 0xA8, 0x90, 0x00, 0x00]),
+    (0x0026, [0x00, 0x40, 0xA0, 0xA0, 0x40, 0xA8, 0x90, 0x68, 0x00, 0x00]),
+    (0x0027, [0x00, 0x20, 0x20, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x0028, [0x00, 0x10, 0x20, 0x40, 0x40, 0x40, 0x20, 0x10, 0x00, 0x00]),
+    (0x0029, [0x00, 0x40, 0x20, 0x10, 0x10, 0x10, 0x20, 0x40, 0x00, 0x00]),
+    (0x002A, [0x00, 0x00, 0x88, 0x50, 0xF8, 0x50, 0x88, 0x00, 0x00, 0x00]),
+    (0x002B, [0x00, 0x00, 0x20, 0x20, 0xF8, 0x20, 0x20, 0x00, 0x00, 0x00]),
+    (0x002C, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x20, 0x40, 0x00]),
+    (0x002D, [0x00, 0x00, 0x00, 0x00, 0xF8, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x002E, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20, 0x70, 0x20, 0x00]),
+    (0x002F, [0x00, 0x08, 0x08, 0x10, 0x20, 0x40, 0x80, 0x80, 0x00, 0x00]),
+    (0x0030, [0x00, 0x20, 0x50, 0x88, 0x88, 0x88, 0x50, 0x20, 0x00, 0x00]),
+    (0x0031, [0x00, 0x20, 0x60, 0xA0, 0x20, 0x20, 0x20, 0xF8, 0x00, 0x00]),
+    (0x0032, [0x00, 0x70, 0x88, 0x08, 0x30, 0x40, 0x80, 0xF8, 0x00, 0x00]),
+    (0x0033, [0x00, 0xF8, 0x08, 0x10, 0x30, 0x08, 0x88, 0x70, 0x00, 0x00]),
+    (0x0034, [0x00, 0x10, 0x30, 0x50, 0x90, 0xF8, 0x10, 0x10, 0x00, 0x00]),
+    (0x0035, [0x00, 0xF8, 0x80, 0xB0, 0xC8, 0x08, 0x88, 0x70, 0x00, 0x00]),
+    (0x0036, [0x00, 0x30, 0x40, 0x80, 0xB0, 0xC8, 0x88, 0x70, 0x00, 0x00]),
+    (0x0037, [0x00, 0xF8, 0x08, 0x10, 0x10, 0x20, 0x40, 0x40, 0x00, 0x00]),
+    (0x0038, [0x00, 0x70, 0x88, 0x88, 0x70, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x0039, [0x00, 0x70, 0x88, 0x98, 0x68, 0x08, 0x10, 0x60, 0x00, 0x00]),
+    (0x003A, [0x00, 0x00, 0x20, 0x70, 0x20, 0x00, 0x20, 0x70, 0x20, 0x00]),
+    (0x003B, [0x00, 0x00, 0x20, 0x70, 0x20, 0x00, 0x30, 0x20, 0x40, 0x00]),
+    (0x003C, [0x00, 0x08, 0x10, 0x20, 0x40, 0x20, 0x10, 0x08, 0x00, 0x00]),
+    (0x003D, [0x00, 0x00, 0x00, 0xF8, 0x00, 0xF8, 0x00, 0x00, 0x00, 0x00]),
+    (0x003E, [0x00, 0x40, 0x20, 0x10, 0x08, 0x10, 0x20, 0x40, 0x00, 0x00]),
+    (0x003F, [0x00, 0x70, 0x88, 0x10, 0x20, 0x20, 0x00, 0x20, 0x00, 0x00]),
+    (0x0040, [0x00, 0x70, 0x88, 0x98, 0xA8, 0xB0, 0x80, 0x70, 0x00, 0x00]),
+    (0x0041, [0x00, 0x20, 0x50, 0x88, 0x88, 0xF8, 0x88, 0x88, 0x00, 0x00]),
+    (0x0042, [0x00, 0xF0, 0x48, 0x48, 0x70, 0x48, 0x48, 0xF0, 0x00, 0x00]),
+    (0x0043, [0x00, 0x70, 0x88, 0x80, 0x80, 0x80, 0x88, 0x70, 0x00, 0x00]),
+    (0x0044, [0x00, 0xF0, 0x48, 0x48, 0x48, 0x48, 0x48, 0xF0, 0x00, 0x00]),
+    (0x0045, [0x00, 0xF8, 0x80, 0x80, 0xF0, 0x80, 0x80, 0xF8, 0x00, 0x00]),
+    (0x0046, [0x00, 0xF8, 0x80, 0x80, 0xF0, 0x80, 0x80, 0x80, 0x00, 0x00]),
+    (0x0047, [0x00, 0x70, 0x88, 0x80, 0x80, 0x98, 0x88, 0x70, 0x00, 0x00]),
+    (0x0048, [0x00, 0x88, 0x88, 0x88, 0xF8, 0x88, 0x88, 0x88, 0x00, 0x00]),
+    (0x0049, [0x00, 0x70, 0x20, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00]),
+    (0x004A, [0x00, 0x38, 0x10, 0x10, 0x10, 0x10, 0x90, 0x60, 0x00, 0x00]),
+    (0x004B, [0x00, 0x88, 0x90, 0xA0, 0xC0, 0xA0, 0x90, 0x88, 0x00, 0x00]),
+    (0x004C, [0x00, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0xF8, 0x00, 0x00]),
+    (0x004D, [0x00, 0x88, 0x88, 0xD8, 0xA8, 0x88, 0x88, 0x88, 0x00, 0x00]),
+    (0x004E, [0x00, 0x88, 0x88, 0xC8, 0xA8, 0x98, 0x88, 0x88, 0x00, 0x00]),
+    (0x004F, [0x00, 0x70, 0x88, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x0050, [0x00, 0xF0, 0x88, 0x88, 0xF0, 0x80, 0x80, 0x80, 0x00, 0x00]),
+    (0x0051, [0x00, 0x70, 0x88, 0x88, 0x88, 0x88, 0xA8, 0x70, 0x08, 0x00]),
+    (0x0052, [0x00, 0xF0, 0x88, 0x88, 0xF0, 0xA0, 0x90, 0x88, 0x00, 0x00]),
+    (0x0053, [0x00, 0x70, 0x88, 0x80, 0x70, 0x08, 0x88, 0x70, 0x00, 0x00]),
+    (0x0054, [0x00, 0xF8, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00]),
+    (0x0055, [0x00, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x0056, [0x00, 0x88, 0x88, 0x88, 0x50, 0x50, 0x50, 0x20, 0x00, 0x00]),
+    (0x0057, [0x00, 0x88, 0x88, 0x88, 0xA8, 0xA8, 0xD8, 0x88, 0x00, 0x00]),
+    (0x0058, [0x00, 0x88, 0x88, 0x50, 0x20, 0x50, 0x88, 0x88, 0x00, 0x00]),
+    (0x0059, [0x00, 0x88, 0x88, 0x50, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00]),
+    (0x005A, [0x00, 0xF8, 0x08, 0x10, 0x20, 0x40, 0x80, 0xF8, 0x00, 0x00]),
+    (0x005B, [0x00, 0x70, 0x40, 0x40, 0x40, 0x40, 0x40, 0x70, 0x00, 0x00]),
+    (0x005C, [0x00, 0x80, 0x80, 0x40, 0x20, 0x10, 0x08, 0x08, 0x00, 0x00]),
+    (0x005D, [0x00, 0x70, 0x10, 0x10, 0x10, 0x10, 0x10, 0x70, 0x00, 0x00]),
+    (0x005E, [0x00, 0x20, 0x50, 0x88, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x005F, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xF8, 0x00]),
+    (0x0060, [0x20, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x0061, [0x00, 0x00, 0x00, 0x70, 0x08, 0x78, 0x88, 0x78, 0x00, 0x00]),
+    (0x0062, [0x00, 0x80, 0x80, 0xB0, 0xC8, 0x88, 0xC8, 0xB0, 0x00, 0x00]),
+    (0x0063, [0x00, 0x00, 0x00, 0x70, 0x88, 0x80, 0x88, 0x70, 0x00, 0x00]),
+    (0x0064, [0x00, 0x08, 0x08, 0x68, 0x98, 0x88, 0x98, 0x68, 0x00, 0x00]),
+    (0x0065, [0x00, 0x00, 0x00, 0x70, 0x88, 0xF8, 0x80, 0x70, 0x00, 0x00]),
+    (0x0066, [0x00, 0x30, 0x48, 0x40, 0xF0, 0x40, 0x40, 0x40, 0x00, 0x00]),
+    (0x0067, [0x00, 0x00, 0x00, 0x78, 0x88, 0x88, 0x78, 0x08, 0x88, 0x70]),
+    (0x0068, [0x00, 0x80, 0x80, 0xB0, 0xC8, 0x88, 0x88, 0x88, 0x00, 0x00]),
+    (0x0069, [0x00, 0x20, 0x00, 0x60, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00]),
+    (0x006A, [0x00, 0x08, 0x00, 0x18, 0x08, 0x08, 0x08, 0x48, 0x48, 0x30]),
+    (0x006B, [0x00, 0x80, 0x80, 0x88, 0x90, 0xE0, 0x90, 0x88, 0x00, 0x00]),
+    (0x006C, [0x00, 0x60, 0x20, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00]),
+    (0x006D, [0x00, 0x00, 0x00, 0xD0, 0xA8, 0xA8, 0xA8, 0x88, 0x00, 0x00]),
+    (0x006E, [0x00, 0x00, 0x00, 0xB0, 0xC8, 0x88, 0x88, 0x88, 0x00, 0x00]),
+    (0x006F, [0x00, 0x00, 0x00, 0x70, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x0070, [0x00, 0x00, 0x00, 0xB0, 0xC8, 0x88, 0xC8, 0xB0, 0x80, 0x80]),
+    (0x0071, [0x00, 0x00, 0x00, 0x68, 0x98, 0x88, 0x98, 0x68, 0x08, 0x08]),
+    (0x0072, [0x00, 0x00, 0x00, 0xB0, 0xC8, 0x80, 0x80, 0x80, 0x00, 0x00]),
+    (0x0073, [0x00, 0x00, 0x00, 0x70, 0x80, 0x70, 0x08, 0xF0, 0x00, 0x00]),
+    (0x0074, [0x00, 0x40, 0x40, 0xF0, 0x40, 0x40, 0x48, 0x30, 0x00, 0x00]),
+    (0x0075, [0x00, 0x00, 0x00, 0x88, 0x88, 0x88, 0x98, 0x68, 0x00, 0x00]),
+    (0x0076, [0x00, 0x00, 0x00, 0x88, 0x88, 0x50, 0x50, 0x20, 0x00, 0x00]),
+    (0x0077, [0x00, 0x00, 0x00, 0x88, 0x88, 0xA8, 0xA8, 0x50, 0x00, 0x00]),
+    (0x0078, [0x00, 0x00, 0x00, 0x88, 0x50, 0x20, 0x50, 0x88, 0x00, 0x00]),
+    (0x0079, [0x00, 0x00, 0x00, 0x88, 0x88, 0x98, 0x68, 0x08, 0x88, 0x70]),
+    (0x007A, [0x00, 0x00, 0x00, 0xF8, 0x10, 0x20, 0x40, 0xF8, 0x00, 0x00]),
+    (0x007B, [0x00, 0x18, 0x20, 0x10, 0x60, 0x10, 0x20, 0x18, 0x00, 0x00]),
+    (0x007C, [0x00, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00]),
+    (0x007D, [0x00, 0x60, 0x10, 0x20, 0x18, 0x20, 0x10, 0x60, 0x00, 0x00]),
+    (0x007E, [0x00, 0x48, 0xA8, 0x90, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x00A0, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x00A1, [0x00, 0x20, 0x00, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00]),
+    (0x00A2, [0x00, 0x00, 0x20, 0x78, 0xA0, 0xA0, 0xA0, 0x78, 0x20, 0x00]),
+    (0x00A3, [0x00, 0x30, 0x48, 0x40, 0xE0, 0x40, 0x48, 0xB0, 0x00, 0x00]),
+    (0x00A4, [0x00, 0x00, 0x00, 0x88, 0x70, 0x50, 0x70, 0x88, 0x00, 0x00]),
+    (0x00A5, [0x00, 0x88, 0x88, 0x50, 0x20, 0xF8, 0x20, 0x20, 0x20, 0x00]),
+    (0x00A6, [0x00, 0x20, 0x20, 0x20, 0x00, 0x20, 0x20, 0x20, 0x00, 0x00]),
+    (0x00A7, [0x00, 0x70, 0x80, 0xE0, 0x90, 0x48, 0x38, 0x08, 0x70, 0x00]),
+    (0x00A8, [0x50, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x00A9, [0x00, 0x70, 0x88, 0xA8, 0xC8, 0xA8, 0x88, 0x70, 0x00, 0x00]),
+    (0x00AA, [0x00, 0x38, 0x48, 0x58, 0x28, 0x00, 0x78, 0x00, 0x00, 0x00]),
+    (0x00AB, [0x00, 0x00, 0x00, 0x24, 0x48, 0x90, 0x48, 0x24, 0x00, 0x00]),
+    (0x00AC, [0x00, 0x00, 0x00, 0x00, 0x78, 0x08, 0x00, 0x00, 0x00, 0x00]),
+    (0x00AD, [0x00, 0x00, 0x00, 0x00, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x00AE, [0x00, 0x70, 0x88, 0xE8, 0xC8, 0xC8, 0x88, 0x70, 0x00, 0x00]),
+    (0x00AF, [0xF8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x00B0, [0x00, 0x20, 0x50, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x00B1, [0x00, 0x00, 0x20, 0x20, 0xF8, 0x20, 0x20, 0xF8, 0x00, 0x00]),
+    (0x00B2, [0x30, 0x48, 0x10, 0x20, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x00B3, [0x70, 0x08, 0x30, 0x08, 0x70, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x00B4, [0x10, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x00B5, [0x00, 0x00, 0x00, 0x88, 0x88, 0x88, 0xC8, 0xB0, 0x80, 0x00]),
+    (0x00B6, [0x00, 0x78, 0xE8, 0xE8, 0x68, 0x28, 0x28, 0x28, 0x00, 0x00]),
+    (0x00B7, [0x00, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x00B8, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x20]),
+    (0x00B9, [0x20, 0x60, 0x20, 0x20, 0x70, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x00BA, [0x00, 0x30, 0x48, 0x48, 0x30, 0x00, 0x78, 0x00, 0x00, 0x00]),
+    (0x00BB, [0x00, 0x00, 0x00, 0x90, 0x48, 0x24, 0x48, 0x90, 0x00, 0x00]),
+    (0x00BC, [0x40, 0xC0, 0x40, 0x40, 0xE4, 0x0C, 0x14, 0x3C, 0x04, 0x00]),
+    (0x00BD, [0x40, 0xC0, 0x40, 0x40, 0xE8, 0x14, 0x04, 0x08, 0x1C, 0x00]),
+    (0x00BE, [0xC0, 0x20, 0x40, 0x20, 0xC8, 0x18, 0x28, 0x78, 0x08, 0x00]),
+    (0x00BF, [0x00, 0x20, 0x00, 0x20, 0x20, 0x40, 0x88, 0x70, 0x00, 0x00]),
+    (0x00C0, [0x40, 0x20, 0x70, 0x88, 0x88, 0xF8, 0x88, 0x88, 0x00, 0x00]),
+    (0x00C1, [0x10, 0x20, 0x70, 0x88, 0x88, 0xF8, 0x88, 0x88, 0x00, 0x00]),
+    (0x00C2, [0x20, 0x50, 0x70, 0x88, 0x88, 0xF8, 0x88, 0x88, 0x00, 0x00]),
+    (0x00C3, [0x48, 0xB0, 0x70, 0x88, 0x88, 0xF8, 0x88, 0x88, 0x00, 0x00]),
+    (0x00C4, [0x50, 0x00, 0x70, 0x88, 0x88, 0xF8, 0x88, 0x88, 0x00, 0x00]),
+    (0x00C5, [0x20, 0x50, 0x70, 0x88, 0x88, 0xF8, 0x88, 0x88, 0x00, 0x00]),
+    (0x00C6, [0x00, 0x3C, 0x50, 0x90, 0x9C, 0xF0, 0x90, 0x9C, 0x00, 0x00]),
+    (0x00C7, [0x00, 0x70, 0x88, 0x80, 0x80, 0x80, 0x88, 0x70, 0x20, 0x40]),
+    (0x00C8, [0x40, 0xF8, 0x80, 0x80, 0xF0, 0x80, 0x80, 0xF8, 0x00, 0x00]),
+    (0x00C9, [0x10, 0xF8, 0x80, 0x80, 0xF0, 0x80, 0x80, 0xF8, 0x00, 0x00]),
+    (0x00CA, [0x20, 0xF8, 0x80, 0x80, 0xF0, 0x80, 0x80, 0xF8, 0x00, 0x00]),
+    (0x00CB, [0x50, 0xF8, 0x80, 0x80, 0xF0, 0x80, 0x80, 0xF8, 0x00, 0x00]),
+    (0x00CC, [0x40, 0x20, 0x70, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00]),
+    (0x00CD, [0x10, 0x20, 0x70, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00]),
+    (0x00CE, [0x20, 0x50, 0x70, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00]),
+    (0x00CF, [0x50, 0x00, 0x70, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00]),
+    (0x00D0, [0x00, 0xF0, 0x48, 0x48, 0xE8, 0x48, 0x48, 0xF0, 0x00, 0x00]),
+    (0x00D1, [0x28, 0x50, 0x88, 0xC8, 0xA8, 0x98, 0x88, 0x88, 0x00, 0x00]),
+    (0x00D2, [0x40, 0x20, 0x70, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00D3, [0x10, 0x20, 0x70, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00D4, [0x20, 0x50, 0x70, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00D5, [0x28, 0x50, 0x70, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00D6, [0x50, 0x00, 0x70, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00D7, [0x00, 0x00, 0x00, 0x88, 0x50, 0x20, 0x50, 0x88, 0x00, 0x00]),
+    (0x00D8, [0x00, 0x70, 0x98, 0x98, 0xA8, 0xC8, 0xC8, 0x70, 0x00, 0x00]),
+    (0x00D9, [0x40, 0x20, 0x88, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00DA, [0x10, 0x20, 0x88, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00DB, [0x20, 0x50, 0x00, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00DC, [0x50, 0x00, 0x88, 0x88, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00DD, [0x10, 0x20, 0x88, 0x88, 0x50, 0x20, 0x20, 0x20, 0x00, 0x00]),
+    (0x00DE, [0x00, 0x80, 0xF0, 0x88, 0xF0, 0x80, 0x80, 0x80, 0x00, 0x00]),
+    (0x00DF, [0x00, 0x70, 0x88, 0x90, 0xA0, 0x90, 0x88, 0xB0, 0x00, 0x00]),
+    (0x00E0, [0x40, 0x20, 0x00, 0x70, 0x08, 0x78, 0x88, 0x78, 0x00, 0x00]),
+    (0x00E1, [0x10, 0x20, 0x00, 0x70, 0x08, 0x78, 0x88, 0x78, 0x00, 0x00]),
+    (0x00E2, [0x20, 0x50, 0x00, 0x70, 0x08, 0x78, 0x88, 0x78, 0x00, 0x00]),
+    (0x00E3, [0x28, 0x50, 0x00, 0x70, 0x08, 0x78, 0x88, 0x78, 0x00, 0x00]),
+    (0x00E4, [0x00, 0x50, 0x00, 0x70, 0x08, 0x78, 0x88, 0x78, 0x00, 0x00]),
+    (0x00E5, [0x20, 0x50, 0x20, 0x70, 0x08, 0x78, 0x88, 0x78, 0x00, 0x00]),
+    (0x00E6, [0x00, 0x00, 0x00, 0x78, 0x14, 0x7C, 0x90, 0x7C, 0x00, 0x00]),
+    (0x00E7, [0x00, 0x00, 0x00, 0x70, 0x88, 0x80, 0x88, 0x70, 0x20, 0x40]),
+    (0x00E8, [0x40, 0x20, 0x00, 0x70, 0x88, 0xF8, 0x80, 0x70, 0x00, 0x00]),
+    (0x00E9, [0x10, 0x20, 0x00, 0x70, 0x88, 0xF8, 0x80, 0x70, 0x00, 0x00]),
+    (0x00EA, [0x20, 0x50, 0x00, 0x70, 0x88, 0xF8, 0x80, 0x70, 0x00, 0x00]),
+    (0x00EB, [0x00, 0x50, 0x00, 0x70, 0x88, 0xF8, 0x80, 0x70, 0x00, 0x00]),
+    (0x00EC, [0x40, 0x20, 0x00, 0x60, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00]),
+    (0x00ED, [0x20, 0x40, 0x00, 0x60, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00]),
+    (0x00EE, [0x20, 0x50, 0x00, 0x60, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00]),
+    (0x00EF, [0x00, 0x50, 0x00, 0x60, 0x20, 0x20, 0x20, 0x70, 0x00, 0x00]),
+    (0x00F0, [0x00, 0xC0, 0x30, 0x70, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00F1, [0x28, 0x50, 0x00, 0xB0, 0xC8, 0x88, 0x88, 0x88, 0x00, 0x00]),
+    (0x00F2, [0x40, 0x20, 0x00, 0x70, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00F3, [0x10, 0x20, 0x00, 0x70, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00F4, [0x20, 0x50, 0x00, 0x70, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00F5, [0x28, 0x50, 0x00, 0x70, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00F6, [0x00, 0x50, 0x00, 0x70, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x00F7, [0x00, 0x00, 0x20, 0x00, 0xF8, 0x00, 0x20, 0x00, 0x00, 0x00]),
+    (0x00F8, [0x00, 0x00, 0x00, 0x78, 0x98, 0xA8, 0xC8, 0xF0, 0x00, 0x00]),
+    (0x00F9, [0x40, 0x20, 0x00, 0x88, 0x88, 0x88, 0x98, 0x68, 0x00, 0x00]),
+    (0x00FA, [0x10, 0x20, 0x00, 0x88, 0x88, 0x88, 0x98, 0x68, 0x00, 0x00]),
+    (0x00FB, [0x20, 0x50, 0x00, 0x88, 0x88, 0x88, 0x98, 0x68, 0x00, 0x00]),
+    (0x00FC, [0x00, 0x50, 0x00, 0x88, 0x88, 0x88, 0x98, 0x68, 0x00, 0x00]),
+    (0x00FD, [0x00, 0x10, 0x20, 0x88, 0x88, 0x98, 0x68, 0x08, 0x88, 0x70]),
+    (0x00FE, [0x00, 0x00, 0x80, 0xF0, 0x88, 0x88, 0x88, 0xF0, 0x80, 0x80]),
+    (0x00FF, [0x00, 0x50, 0x00, 0x88, 0x88, 0x98, 0x68, 0x08, 0x88, 0x70]),
+    (0x2010, [0x00, 0x00, 0x00, 0x00, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2011, [0x00, 0x00, 0x00, 0x00, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2012, [0x00, 0x00, 0x00, 0x00, 0xF8, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2013, [0x00, 0x00, 0x00, 0x00, 0xF8, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2014, [0x00, 0x00, 0x00, 0x00, 0xFC, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2015, [0x00, 0x00, 0x00, 0x00, 0xFC, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2016, [0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x00, 0x00]),
+    (0x2017, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFC, 0x00, 0xFC]),
+    (0x2018, [0x00, 0x10, 0x20, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2019, [0x00, 0x30, 0x10, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x201A, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x10, 0x20, 0x00]),
+    (0x201B, [0x00, 0x30, 0x20, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x201C, [0x00, 0x48, 0x90, 0xD8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x201D, [0x00, 0xD8, 0x48, 0x90, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x201E, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xD8, 0x48, 0x90, 0x00]),
+    (0x201F, [0x00, 0xD8, 0x90, 0x48, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2020, [0x00, 0x20, 0x20, 0xF8, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00]),
+    (0x2021, [0x00, 0x20, 0x20, 0xF8, 0x20, 0xF8, 0x20, 0x20, 0x00, 0x00]),
+    (0x2022, [0x00, 0x00, 0x00, 0x30, 0x78, 0x78, 0x30, 0x00, 0x00, 0x00]),
+    (0x2023, [0x00, 0x00, 0x00, 0x20, 0x70, 0xF8, 0x00, 0x00, 0x00, 0x00]),
+    (0x2024, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00]),
+    (0x2025, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x88, 0x00, 0x00]),
+    (0x2026, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xA8, 0x00, 0x00]),
+    (0x2027, [0x00, 0x00, 0x00, 0x00, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2190, [0x00, 0x00, 0x00, 0x20, 0x40, 0xFC, 0x40, 0x20, 0x00, 0x00]),
+    (0x2191, [0x00, 0x20, 0x70, 0xA8, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00]),
+    (0x2192, [0x00, 0x00, 0x00, 0x10, 0x08, 0xFC, 0x08, 0x10, 0x00, 0x00]),
+    (0x2193, [0x00, 0x20, 0x20, 0x20, 0x20, 0xA8, 0x70, 0x20, 0x00, 0x00]),
+    (0x2194, [0x00, 0x00, 0x00, 0x00, 0x48, 0xFC, 0x48, 0x00, 0x00, 0x00]),
+    (0x2195, [0x00, 0x20, 0x70, 0xA8, 0x20, 0x20, 0xA8, 0x70, 0x20, 0x00]),
+    (0x2500, [0x00, 0x00, 0x00, 0x00, 0x00, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x2501, [0x00, 0x00, 0x00, 0x00, 0xFC, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x2502, [0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20]),
+    (0x2503, [0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30]),
+    (0x2504, [0x00, 0x00, 0x00, 0x00, 0x00, 0xA8, 0x00, 0x00, 0x00, 0x00]),
+    (0x2505, [0x00, 0x00, 0x00, 0x00, 0xA8, 0xA8, 0x00, 0x00, 0x00, 0x00]),
+    (0x2506, [0x20, 0x20, 0x00, 0x20, 0x20, 0x20, 0x00, 0x20, 0x20, 0x00]),
+    (0x2507, [0x30, 0x30, 0x00, 0x30, 0x30, 0x30, 0x00, 0x30, 0x30, 0x00]),
+    (0x2508, [0x00, 0x00, 0x00, 0x00, 0x00, 0xA8, 0x00, 0x00, 0x00, 0x00]),
+    (0x2509, [0x00, 0x00, 0x00, 0x00, 0xA8, 0xA8, 0x00, 0x00, 0x00, 0x00]),
+    (0x250A, [0x20, 0x20, 0x00, 0x20, 0x00, 0x20, 0x20, 0x00, 0x20, 0x00]),
+    (0x250B, [0x30, 0x30, 0x00, 0x30, 0x00, 0x30, 0x30, 0x00, 0x30, 0x00]),
+    (0x250C, [0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x20, 0x20, 0x20, 0x20]),
+    (0x250D, [0x00, 0x00, 0x00, 0x00, 0x3C, 0x3C, 0x20, 0x20, 0x20, 0x20]),
+    (0x250E, [0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x30, 0x30, 0x30, 0x30]),
+    (0x250F, [0x00, 0x00, 0x00, 0x00, 0x3C, 0x3C, 0x30, 0x30, 0x30, 0x30]),
+    (0x2510, [0x00, 0x00, 0x00, 0x00, 0x00, 0xE0, 0x20, 0x20, 0x20, 0x20]),
+    (0x2511, [0x00, 0x00, 0x00, 0x00, 0xE0, 0xE0, 0x20, 0x20, 0x20, 0x20]),
+    (0x2512, [0x00, 0x00, 0x00, 0x00, 0x00, 0xF0, 0x30, 0x30, 0x30, 0x30]),
+    (0x2513, [0x00, 0x00, 0x00, 0x00, 0xF0, 0xF0, 0x30, 0x30, 0x30, 0x30]),
+    (0x2514, [0x20, 0x20, 0x20, 0x20, 0x20, 0x3C, 0x00, 0x00, 0x00, 0x00]),
+    (0x2515, [0x20, 0x20, 0x20, 0x20, 0x3C, 0x3C, 0x00, 0x00, 0x00, 0x00]),
+    (0x2516, [0x30, 0x30, 0x30, 0x30, 0x30, 0x3C, 0x00, 0x00, 0x00, 0x00]),
+    (0x2517, [0x30, 0x30, 0x30, 0x30, 0x3C, 0x3C, 0x00, 0x00, 0x00, 0x00]),
+    (0x2518, [0x20, 0x20, 0x20, 0x20, 0x20, 0xE0, 0x00, 0x00, 0x00, 0x00]),
+    (0x2519, [0x20, 0x20, 0x20, 0x20, 0xE0, 0xE0, 0x00, 0x00, 0x00, 0x00]),
+    (0x251A, [0x30, 0x30, 0x30, 0x30, 0x30, 0xF0, 0x00, 0x00, 0x00, 0x00]),
+    (0x251B, [0x30, 0x30, 0x30, 0x30, 0xF0, 0xF0, 0x00, 0x00, 0x00, 0x00]),
+    (0x251C, [0x20, 0x20, 0x20, 0x20, 0x20, 0x3C, 0x20, 0x20, 0x20, 0x20]),
+    (0x251D, [0x20, 0x20, 0x20, 0x20, 0x3C, 0x3C, 0x20, 0x20, 0x20, 0x20]),
+    (0x251E, [0x30, 0x30, 0x30, 0x30, 0x30, 0x3C, 0x20, 0x20, 0x20, 0x20]),
+    (0x251F, [0x20, 0x20, 0x20, 0x20, 0x20, 0x3C, 0x30, 0x30, 0x30, 0x30]),
+    (0x2520, [0x30, 0x30, 0x30, 0x30, 0x30, 0x3C, 0x30, 0x30, 0x30, 0x30]),
+    (0x2521, [0x30, 0x30, 0x30, 0x30, 0x3C, 0x3C, 0x20, 0x20, 0x20, 0x20]),
+    (0x2522, [0x20, 0x20, 0x20, 0x20, 0x3C, 0x3C, 0x30, 0x30, 0x30, 0x30]),
+    (0x2523, [0x30, 0x30, 0x30, 0x30, 0x3C, 0x3C, 0x30, 0x30, 0x30, 0x30]),
+    (0x2524, [0x20, 0x20, 0x20, 0x20, 0x20, 0xE0, 0x20, 0x20, 0x20, 0x20]),
+    (0x2525, [0x20, 0x20, 0x20, 0x20, 0xE0, 0xE0, 0x20, 0x20, 0x20, 0x20]),
+    (0x2526, [0x30, 0x30, 0x30, 0x30, 0x30, 0xE0, 0x20, 0x20, 0x20, 0x20]),
+    (0x2527, [0x20, 0x20, 0x20, 0x20, 0x20, 0xF0, 0x30, 0x30, 0x30, 0x30]),
+    (0x2528, [0x30, 0x30, 0x30, 0x30, 0x30, 0xF0, 0x30, 0x30, 0x30, 0x30]),
+    (0x2529, [0x30, 0x30, 0x30, 0x30, 0xF0, 0xE0, 0x20, 0x20, 0x20, 0x20]),
+    (0x252A, [0x20, 0x20, 0x20, 0x20, 0xE0, 0xF0, 0x30, 0x30, 0x30, 0x30]),
+    (0x252B, [0x30, 0x30, 0x30, 0x30, 0xF0, 0xF0, 0x30, 0x30, 0x30, 0x30]),
+    (0x252C, [0x00, 0x00, 0x00, 0x00, 0x00, 0xFC, 0x20, 0x20, 0x20, 0x20]),
+    (0x252D, [0x00, 0x00, 0x00, 0x00, 0xE0, 0xFC, 0x20, 0x20, 0x20, 0x20]),
+    (0x252E, [0x00, 0x00, 0x00, 0x00, 0x1C, 0xFC, 0x20, 0x20, 0x20, 0x20]),
+    (0x252F, [0x00, 0x00, 0x00, 0x00, 0xFC, 0xFC, 0x20, 0x20, 0x20, 0x20]),
+    (0x2530, [0x00, 0x00, 0x00, 0x00, 0x00, 0xFC, 0x30, 0x30, 0x30, 0x30]),
+    (0x2531, [0x00, 0x00, 0x00, 0x00, 0xE0, 0xFC, 0x30, 0x30, 0x30, 0x30]),
+    (0x2532, [0x00, 0x00, 0x00, 0x00, 0x1C, 0xFC, 0x30, 0x30, 0x30, 0x30]),
+    (0x2533, [0x00, 0x00, 0x00, 0x00, 0xFC, 0xFC, 0x30, 0x30, 0x30, 0x30]),
+    (0x2534, [0x20, 0x20, 0x20, 0x20, 0x20, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x2535, [0x20, 0x20, 0x20, 0x20, 0xE0, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x2536, [0x20, 0x20, 0x20, 0x20, 0x3C, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x2537, [0x20, 0x20, 0x20, 0x20, 0xFC, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x2538, [0x30, 0x30, 0x30, 0x30, 0x30, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x2539, [0x30, 0x30, 0x30, 0x30, 0xF0, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x253A, [0x30, 0x30, 0x30, 0x30, 0x3C, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x253B, [0x30, 0x30, 0x30, 0x30, 0xFC, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x253C, [0x20, 0x20, 0x20, 0x20, 0x20, 0xFC, 0x20, 0x20, 0x20, 0x20]),
+    (0x253D, [0x20, 0x20, 0x20, 0x20, 0xE0, 0xFC, 0x20, 0x20, 0x20, 0x20]),
+    (0x253E, [0x20, 0x20, 0x20, 0x20, 0x3C, 0xFC, 0x20, 0x20, 0x20, 0x20]),
+    (0x253F, [0x20, 0x20, 0x20, 0x20, 0xFC, 0xFC, 0x20, 0x20, 0x20, 0x20]),
+    (0x2540, [0x30, 0x30, 0x30, 0x30, 0x30, 0xFC, 0x20, 0x20, 0x20, 0x20]),
+    (0x2541, [0x20, 0x20, 0x20, 0x20, 0x20, 0xFC, 0x30, 0x30, 0x30, 0x30]),
+    (0x2542, [0x30, 0x30, 0x30, 0x30, 0x30, 0xFC, 0x30, 0x30, 0x30, 0x30]),
+    (0x2543, [0x30, 0x30, 0x30, 0x30, 0xF0, 0xFC, 0x20, 0x20, 0x20, 0x20]),
+    (0x2544, [0x30, 0x30, 0x30, 0x30, 0x3C, 0xFC, 0x20, 0x20, 0x20, 0x20]),
+    (0x2545, [0x20, 0x20, 0x20, 0x20, 0xE0, 0xFC, 0x30, 0x30, 0x30, 0x30]),
+    (0x2546, [0x20, 0x20, 0x20, 0x20, 0x3C, 0xFC, 0x30, 0x30, 0x30, 0x30]),
+    (0x2547, [0x30, 0x30, 0x30, 0x30, 0xFC, 0xFC, 0x20, 0x20, 0x20, 0x20]),
+    (0x2548, [0x20, 0x20, 0x20, 0x20, 0xFC, 0xFC, 0x30, 0x30, 0x30, 0x30]),
+    (0x2549, [0x30, 0x30, 0x30, 0x30, 0xF0, 0xFC, 0x30, 0x30, 0x30, 0x30]),
+    (0x254A, [0x30, 0x30, 0x30, 0x30, 0x3C, 0xFC, 0x30, 0x30, 0x30, 0x30]),
+    (0x254B, [0x30, 0x30, 0x30, 0x30, 0xFC, 0xFC, 0x30, 0x30, 0x30, 0x30]),
+    (0x254C, [0x00, 0x00, 0x00, 0x00, 0x00, 0xD8, 0x00, 0x00, 0x00, 0x00]),
+    (0x254D, [0x00, 0x00, 0x00, 0x00, 0xD8, 0xD8, 0x00, 0x00, 0x00, 0x00]),
+    (0x254E, [0x20, 0x20, 0x20, 0x20, 0x00, 0x20, 0x20, 0x20, 0x20, 0x00]),
+    (0x254F, [0x30, 0x30, 0x30, 0x30, 0x00, 0x30, 0x30, 0x30, 0x30, 0x00]),
+    (0x2550, [0x00, 0x00, 0x00, 0x00, 0xFC, 0x00, 0xFC, 0x00, 0x00, 0x00]),
+    (0x2551, [0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50, 0x50]),
+    (0x2552, [0x00, 0x00, 0x00, 0x00, 0x3C, 0x20, 0x3C, 0x20, 0x20, 0x20]),
+    (0x2553, [0x00, 0x00, 0x00, 0x00, 0x00, 0x7C, 0x50, 0x50, 0x50, 0x50]),
+    (0x2554, [0x00, 0x00, 0x00, 0x00, 0x7C, 0x40, 0x5C, 0x50, 0x50, 0x50]),
+    (0x2555, [0x00, 0x00, 0x00, 0x00, 0xE0, 0x20, 0xE0, 0x20, 0x20, 0x20]),
+    (0x2556, [0x00, 0x00, 0x00, 0x00, 0x00, 0xF0, 0x50, 0x50, 0x50, 0x50]),
+    (0x2557, [0x00, 0x00, 0x00, 0x00, 0xF0, 0x10, 0xD0, 0x50, 0x50, 0x50]),
+    (0x2558, [0x20, 0x20, 0x20, 0x20, 0x3C, 0x20, 0x3C, 0x00, 0x00, 0x00]),
+    (0x2559, [0x50, 0x50, 0x50, 0x50, 0x50, 0x7C, 0x00, 0x00, 0x00, 0x00]),
+    (0x255A, [0x50, 0x50, 0x50, 0x50, 0x5C, 0x40, 0x7C, 0x00, 0x00, 0x00]),
+    (0x255B, [0x20, 0x20, 0x20, 0x20, 0xE0, 0x20, 0xE0, 0x00, 0x00, 0x00]),
+    (0x255C, [0x50, 0x50, 0x50, 0x50, 0x50, 0xF0, 0x00, 0x00, 0x00, 0x00]),
+    (0x255D, [0x50, 0x50, 0x50, 0x50, 0xD0, 0x10, 0xF0, 0x00, 0x00, 0x00]),
+    (0x255E, [0x20, 0x20, 0x20, 0x20, 0x3C, 0x20, 0x3C, 0x20, 0x20, 0x20]),
+    (0x255F, [0x50, 0x50, 0x50, 0x50, 0x50, 0x5C, 0x50, 0x50, 0x50, 0x50]),
+    (0x2560, [0x50, 0x50, 0x50, 0x50, 0x5C, 0x40, 0x5C, 0x50, 0x50, 0x50]),
+    (0x2561, [0x20, 0x20, 0x20, 0x20, 0xE0, 0x20, 0xE0, 0x20, 0x20, 0x20]),
+    (0x2562, [0x50, 0x50, 0x50, 0x50, 0x50, 0xD0, 0x50, 0x50, 0x50, 0x50]),
+    (0x2563, [0x50, 0x50, 0x50, 0x50, 0xD0, 0x10, 0xD0, 0x50, 0x50, 0x50]),
+    (0x2564, [0x00, 0x00, 0x00, 0x00, 0xFC, 0x00, 0xFC, 0x20, 0x20, 0x20]),
+    (0x2565, [0x00, 0x00, 0x00, 0x00, 0x00, 0xFC, 0x50, 0x50, 0x50, 0x50]),
+    (0x2566, [0x00, 0x00, 0x00, 0x00, 0xFC, 0x00, 0xDC, 0x50, 0x50, 0x50]),
+    (0x2567, [0x20, 0x20, 0x20, 0x20, 0xFC, 0x00, 0xFC, 0x00, 0x00, 0x00]),
+    (0x2568, [0x50, 0x50, 0x50, 0x50, 0x50, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x2569, [0x50, 0x50, 0x50, 0x50, 0xDC, 0x00, 0xFC, 0x00, 0x00, 0x00]),
+    (0x256A, [0x20, 0x20, 0x20, 0x20, 0xFC, 0x20, 0xFC, 0x20, 0x20, 0x20]),
+    (0x256B, [0x50, 0x50, 0x50, 0x50, 0x50, 0xFC, 0x50, 0x50, 0x50, 0x50]),
+    (0x256C, [0x50, 0x50, 0x50, 0x50, 0xDC, 0x00, 0xDC, 0x50, 0x50, 0x50]),
+    (0x256D, [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x10, 0x20, 0x20, 0x20]),
+    (0x256E, [0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x40, 0x20, 0x20, 0x20]),
+    (0x256F, [0x20, 0x20, 0x20, 0x20, 0x40, 0x80, 0x00, 0x00, 0x00, 0x00]),
+    (0x2570, [0x20, 0x20, 0x20, 0x20, 0x10, 0x0C, 0x00, 0x00, 0x00, 0x00]),
+    (0x2571, [0x04, 0x04, 0x08, 0x10, 0x10, 0x20, 0x20, 0x40, 0x80, 0x80]),
+    (0x2572, [0x80, 0x80, 0x40, 0x20, 0x20, 0x10, 0x10, 0x08, 0x04, 0x04]),
+    (0x2573, [0x84, 0x84, 0x48, 0x30, 0x30, 0x30, 0x30, 0x48, 0x84, 0x84]),
+    (0x2574, [0x00, 0x00, 0x00, 0x00, 0x00, 0xE0, 0x00, 0x00, 0x00, 0x00]),
+    (0x2575, [0x20, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2576, [0x00, 0x00, 0x00, 0x00, 0x00, 0x1C, 0x00, 0x00, 0x00, 0x00]),
+    (0x2577, [0x00, 0x00, 0x00, 0x00, 0x00, 0x20, 0x20, 0x20, 0x20, 0x20]),
+    (0x2578, [0x00, 0x00, 0x00, 0x00, 0xE0, 0xE0, 0x00, 0x00, 0x00, 0x00]),
+    (0x2579, [0x30, 0x30, 0x30, 0x30, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x257A, [0x00, 0x00, 0x00, 0x00, 0x1C, 0x1C, 0x00, 0x00, 0x00, 0x00]),
+    (0x257B, [0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x30, 0x30, 0x30, 0x30]),
+    (0x257C, [0x00, 0x00, 0x00, 0x00, 0x1C, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x257D, [0x20, 0x20, 0x20, 0x20, 0x20, 0x30, 0x30, 0x30, 0x30, 0x30]),
+    (0x257E, [0x00, 0x00, 0x00, 0x00, 0xE0, 0xFC, 0x00, 0x00, 0x00, 0x00]),
+    (0x257F, [0x30, 0x30, 0x30, 0x30, 0x30, 0x20, 0x20, 0x20, 0x20, 0x20]),
+    (0x2580, [0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2581, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFC]),
+    (0x2582, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFC, 0xFC]),
+    (0x2583, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFC, 0xFC, 0xFC, 0xFC]),
+    (0x2584, [0x00, 0x00, 0x00, 0x00, 0x00, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC]),
+    (0x2585, [0x00, 0x00, 0x00, 0x00, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC]),
+    (0x2586, [0x00, 0x00, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC]),
+    (0x2587, [0x00, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC]),
+    (0x2588, [0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC]),
+    (0x2589, [0xF8, 0xF8, 0xF8, 0xF8, 0xF8, 0xF8, 0xF8, 0xF8, 0xF8, 0xF8]),
+    (0x258A, [0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0]),
+    (0x258B, [0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0]),
+    (0x258C, [0xE0, 0xE0, 0xE0, 0xE0, 0xE0, 0xE0, 0xE0, 0xE0, 0xE0, 0xE0]),
+    (0x258D, [0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0]),
+    (0x258E, [0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0]),
+    (0x258F, [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80]),
+    (0x2590, [0x1C, 0x1C, 0x1C, 0x1C, 0x1C, 0x1C, 0x1C, 0x1C, 0x1C, 0x1C]),
+    (0x2591, [0xA8, 0x00, 0x54, 0x00, 0xA8, 0x00, 0x54, 0x00, 0xA8, 0x00]),
+    (0x2592, [0xA8, 0x54, 0xA8, 0x54, 0xA8, 0x54, 0xA8, 0x54, 0xA8, 0x54]),
+    (0x2593, [0xA8, 0xFC, 0x54, 0xFC, 0xA8, 0xFC, 0x54, 0xFC, 0xA8, 0xFC]),
+    (0x2594, [0xFC, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2595, [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+    (0x2596, [0x00, 0x00, 0x00, 0x00, 0x00, 0xE0, 0xE0, 0xE0, 0xE0, 0xE0]),
+    (0x2597, [0x00, 0x00, 0x00, 0x00, 0x00, 0x1C, 0x1C, 0x1C, 0x1C, 0x1C]),
+    (0x2598, [0xE0, 0xE0, 0xE0, 0xE0, 0xE0, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x2599, [0xE0, 0xE0, 0xE0, 0xE0, 0xE0, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC]),
+    (0x259A, [0xE0, 0xE0, 0xE0, 0xE0, 0xE0, 0x1C, 0x1C, 0x1C, 0x1C, 0x1C]),
+    (0x259B, [0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xE0, 0xE0, 0xE0, 0xE0, 0xE0]),
+    (0x259C, [0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0x1C, 0x1C, 0x1C, 0x1C, 0x1C]),
+    (0x259D, [0x1C, 0x1C, 0x1C, 0x1C, 0x1C, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (0x259E, [0x1C, 0x1C, 0x1C, 0x1C, 0x1C, 0xE0, 0xE0, 0xE0, 0xE0, 0xE0]),
+    (0x259F, [0x1C, 0x1C, 0x1C, 0x1C, 0x1C, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC]),
+    (0x25A0, [0x00, 0x00, 0x00, 0xF8, 0xF8, 0xF8, 0xF8, 0xF8, 0x00, 0x00]),
+    (0x25A1, [0x00, 0x00, 0x00, 0xF8, 0x88, 0x88, 0x88, 0xF8, 0x00, 0x00]),
+    (0x25AA, [0x00, 0x00, 0x00, 0x00, 0x78, 0x78, 0x78, 0x78, 0x00, 0x00]),
+    (0x25AB, [0x00, 0x00, 0x00, 0x00, 0x78, 0x48, 0x48, 0x78, 0x00, 0x00]),
+    (0x25AC, [0x00, 0x00, 0x00, 0x00, 0xFC, 0xFC, 0xFC, 0xFC, 0x00, 0x00]),
+    (0x25AE, [0x00, 0x00, 0x00, 0x78, 0x78, 0x78, 0x78, 0x78, 0x78, 0x00]),
+    (0x25B2, [0x00, 0x00, 0x20, 0x20, 0x70, 0x70, 0xF8, 0xF8, 0x00, 0x00]),
+    (0x25B3, [0x00, 0x00, 0x20, 0x20, 0x50, 0x50, 0x88, 0xF8, 0x00, 0x00]),
+    (0x25BA, [0x00, 0x00, 0x00, 0xC0, 0xF0, 0xFC, 0xF0, 0xC0, 0x00, 0x00]),
+    (0x25BB, [0x00, 0x00, 0x00, 0xC0, 0xB0, 0x8C, 0xB0, 0xC0, 0x00, 0x00]),
+    (0x25BC, [0x00, 0x00, 0xF8, 0xF8, 0x70, 0x70, 0x20, 0x20, 0x00, 0x00]),
+    (0x25BD, [0x00, 0x00, 0xF8, 0x88, 0x50, 0x50, 0x20, 0x20, 0x00, 0x00]),
+    (0x25C4, [0x00, 0x00, 0x00, 0x0C, 0x3C, 0xFC, 0x3C, 0x0C, 0x00, 0x00]),
+    (0x25C5, [0x00, 0x00, 0x00, 0x0C, 0x34, 0xC4, 0x34, 0x0C, 0x00, 0x00]),
+    (0x25C6, [0x00, 0x00, 0x20, 0x70, 0xF8, 0x70, 0x20, 0x00, 0x00, 0x00]),
+    (0x25CA, [0x00, 0x20, 0x50, 0x50, 0x88, 0x50, 0x50, 0x20, 0x00, 0x00]),
+    (0x25CB, [0x00, 0x00, 0x00, 0x70, 0x88, 0x88, 0x88, 0x70, 0x00, 0x00]),
+    (0x25CC, [0x00, 0x00, 0x00, 0x70, 0xF8, 0xF8, 0xF8, 0x70, 0x00, 0x00]),
+    (0x25CF, [0x00, 0x00, 0x00, 0x70, 0xF8, 0xF8, 0xF8, 0x70, 0x00, 0x00]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyph_lookup() {
+        assert!(GLYPHS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(glyph(' '), Some(&[0; GLYPH_HEIGHT]));
+        assert_eq!(glyph('─').unwrap()[5], 0xFC);
+        assert!(glyph('A').is_some_and(|rows| rows.iter().any(|&row| row != 0)));
+        assert!(glyph('界').is_none());
+    }
+}
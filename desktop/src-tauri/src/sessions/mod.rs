@@ -1,226 +1,634 @@
 // Port of SessionMonitor.swift functionality
 
+pub mod buffer;
+pub mod emulator;
+pub mod events;
+mod font;
 pub mod monitor;
+pub mod playback;
+pub mod preview;
+pub mod profiles;
+pub mod reaper;
+pub mod recording;
+pub mod search;
+mod shell;
+pub mod stream;
+pub mod triggers;
 pub mod websocket;
+pub mod workspaces;
 
+pub use events::*;
 pub use monitor::*;
+pub use playback::*;
+pub use preview::*;
+pub use profiles::*;
+pub use reaper::*;
+pub use recording::*;
+pub use search::*;
+pub use stream::*;
+pub use triggers::*;
 pub use websocket::*;
+pub use workspaces::*;
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::State;
 use tokio::sync::broadcast;
+use log::debug;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::server::ServerManager;
+
+/// Lets the server skip authentication for requests from this machine (`ServerAuthMode::LocalBypass`)
+const LOCAL_BYPASS_HEADER: &str = "X-TunnelForge-Local";
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// A session as returned by the server's `/api/sessions` endpoints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Session {
     pub id: String,
+    #[serde(default)]
     pub title: String,
+    #[serde(rename = "createdAt")]
     pub created_at: String,
+    #[serde(rename = "updatedAt")]
     pub last_activity: String,
     pub status: String,
+    #[serde(default)]
     pub pid: Option<u32>,
+    #[serde(default)]
     pub command: Option<String>,
+    #[serde(default, rename = "cwd")]
     pub working_directory: Option<String>,
+    #[serde(default)]
+    pub cols: u16,
+    #[serde(default)]
+    pub rows: u16,
+    #[serde(default)]
+    pub active: bool,
+    #[serde(default)]
+    pub clients: u32,
+}
+
+/// Body of `POST /api/sessions`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CreateSessionRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Program and arguments; the server starts the user's shell when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cols: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows: Option<u16>,
+    /// Variables added to the server's environment for the session's process
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+/// A server-side session group; its tags apply to every session in it
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SessionGroup {
+    id: String,
+    name: String,
+    tags: Vec<String>,
+}
+
+/// Session fields tracked for change detection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionField {
+    Title,
+    Status,
+    Command,
+    WorkingDirectory,
+    Pid,
+    Cols,
+    Rows,
+    Active,
+    Clients,
+    LastActivity,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionFieldChange {
+    pub field: SessionField,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
+
+impl Session {
+    /// Fields whose values differ in `newer`, in declaration order
+    pub fn changes_to(&self, newer: &Session) -> Vec<SessionFieldChange> {
+        let mut changes = Vec::new();
+        let mut check = |field: SessionField, from: serde_json::Value, to: serde_json::Value| {
+            if from != to {
+                changes.push(SessionFieldChange { field, from, to });
+            }
+        };
+
+        check(SessionField::Title, self.title.clone().into(), newer.title.clone().into());
+        check(SessionField::Status, self.status.clone().into(), newer.status.clone().into());
+        check(SessionField::Command, self.command.clone().into(), newer.command.clone().into());
+        check(SessionField::WorkingDirectory, self.working_directory.clone().into(), newer.working_directory.clone().into());
+        check(SessionField::Pid, self.pid.into(), newer.pid.into());
+        check(SessionField::Cols, self.cols.into(), newer.cols.into());
+        check(SessionField::Rows, self.rows.into(), newer.rows.into());
+        check(SessionField::Active, self.active.into(), newer.active.into());
+        check(SessionField::Clients, self.clients.into(), newer.clients.into());
+        check(SessionField::LastActivity, self.last_activity.clone().into(), newer.last_activity.clone().into());
+        changes
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What happened to a session. Serialized as `{"event_type": "...", "data": {...}}`, where
+/// `created`, `updated` and `deleted` keep the shape the frontend already handles.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event_type", content = "data", rename_all = "snake_case")]
+pub enum SessionEventKind {
+    Created(Session),
+    /// Every change, with the new state and the fields that differ. The events below follow it
+    /// for the changes consumers usually care about.
+    Updated {
+        #[serde(flatten)]
+        session: Session,
+        changes: Vec<SessionFieldChange>,
+    },
+    StatusChanged { from: String, to: String },
+    Exited { exit_code: Option<i32> },
+    TitleChanged { from: String, to: String },
+    ActivityChanged { last_activity: String, active: bool },
+    Deleted,
+    Bell,
+    CommandFinished { command: Option<String>, exit_code: Option<i32>, duration_ms: Option<u64> },
+    CommandError { command: Option<String>, exit_code: Option<i32>, message: Option<String> },
+    ClaudeTurn,
+}
+
+impl SessionEventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SessionEventKind::Created(_) => "created",
+            SessionEventKind::Updated { .. } => "updated",
+            SessionEventKind::StatusChanged { .. } => "status_changed",
+            SessionEventKind::Exited { .. } => "exited",
+            SessionEventKind::TitleChanged { .. } => "title_changed",
+            SessionEventKind::ActivityChanged { .. } => "activity_changed",
+            SessionEventKind::Deleted => "deleted",
+            SessionEventKind::Bell => "bell",
+            SessionEventKind::CommandFinished { .. } => "command_finished",
+            SessionEventKind::CommandError { .. } => "command_error",
+            SessionEventKind::ClaudeTurn => "claude_turn",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SessionEvent {
     pub session_id: String,
-    pub event_type: String,
-    pub data: serde_json::Value,
+    #[serde(flatten)]
+    pub kind: SessionEventKind,
     pub timestamp: String,
 }
 
+impl SessionEvent {
+    pub fn new(session_id: &str, kind: SessionEventKind) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            kind,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
 pub type SessionEventSender = broadcast::Sender<SessionEvent>;
 pub type SessionEventReceiver = broadcast::Receiver<SessionEvent>;
 
+/// Client for the server's session API. A single instance is registered with `app.manage`,
+/// so the connection pool, the session cache and the event channel live as long as the app.
+#[derive(Clone)]
 pub struct SessionManager {
+    client: reqwest::Client,
+    server_manager: ServerManager,
     sessions: Arc<Mutex<HashMap<String, Session>>>,
     event_sender: SessionEventSender,
-    server_url: String,
+    // One lock per group name, so two sessions tagged at once don't both create the group
+    group_locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
 }
 
 impl SessionManager {
-    pub fn new(server_url: String) -> Self {
-        let (event_sender, _) = broadcast::channel(100");
+    pub fn new(server_manager: ServerManager) -> Self {
+        let (event_sender, _) = broadcast::channel(100);
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(LOCAL_BYPASS_HEADER, reqwest::header::HeaderValue::from_static("true"));
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
 
         Self {
+            client,
+            server_manager,
             sessions: Arc::new(Mutex::new(HashMap::new())),
             event_sender,
-            server_url,
+            group_locks: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Base URL of the server as currently run by the `ServerManager`, e.g. after a port change
+    pub fn server_url(&self) -> String {
+        format!("http://{}:{}", self.server_manager.get_host(), self.server_manager.get_port())
+    }
+
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Fetch all sessions, refresh the cache and emit events for whatever changed since the last fetch
     pub async fn fetch_sessions(&self) -> Result<Vec<Session>, String> {
-        let client = reqwest::Client::new(");
-        let url = format!("{}/api/sessions", self.server_url");
-
-        match client.get(&url).send().await {
-            Ok(response) => {
-                if response.status().is_success() {
-                    let sessions: Vec<Session> = response.json().await
-                        .map_err(|e| format!("Failed to parse sessions: {}", e))?;
-
-                    // Update local cache
-                    {
-                        let mut local_sessions = self.sessions.lock().unwrap(");
-                        local_sessions.clear(");
-                        for session in &sessions {
-                            local_sessions.insert(session.id.clone(), session.clone()");
-                        }
-                    }
-
-                    Ok(sessions)
-                } else {
-                    Err(format!("Failed to fetch sessions: HTTP {}", response.status()))
-                }
+        let url = format!("{}/api/sessions", self.server_url());
+
+        let response = self.client.get(&url).send().await
+            .map_err(|e| format!("Failed to request sessions: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch sessions: HTTP {}", response.status()));
+        }
+
+        let sessions: Vec<Session> = response.json().await
+            .map_err(|e| format!("Failed to parse sessions: {}", e))?;
+        self.replace_sessions(&sessions);
+        Ok(sessions)
+    }
+
+    /// Emit an event the server pushed. An exit is also written to the cache, so the next refresh
+    /// doesn't report it again without the exit code.
+    pub fn publish_server_event(&self, event: SessionEvent) {
+        if let SessionEventKind::Exited { .. } = event.kind {
+            if let Some(session) = self.sessions.lock().unwrap().get_mut(&event.session_id) {
+                session.status = "exited".to_string();
             }
-            Err(e) => Err(format!("Failed to request sessions: {}", e))
         }
+        let _ = self.event_sender.send(event);
     }
 
-    pub async fn create_session(&self, title: Option<String>, command: Option<String>) -> Result<Session, String> {
-        let client = reqwest::Client::new(");
-        let url = format!("{}/api/sessions", self.server_url");
+    /// Swap the cache for `sessions` and emit created, updated and deleted events for the difference
+    pub fn replace_sessions(&self, sessions: &[Session]) {
+        let events = {
+            let mut local_sessions = self.sessions.lock().unwrap();
+            let events = diff_sessions(&local_sessions, sessions);
+
+            local_sessions.clear();
+            for session in sessions {
+                local_sessions.insert(session.id.clone(), session.clone());
+            }
+            events
+        };
 
-        let mut body = serde_json::Map::new(");
-        if let Some(title) = title {
-            body.insert("title".to_string(), serde_json::Value::String(title)");
+        for event in events {
+            debug!("Session {} {}", event.session_id, event.kind.name());
+            let _ = self.event_sender.send(event);
         }
-        if let Some(command) = command {
-            body.insert("command".to_string(), serde_json::Value::String(command)");
+    }
+
+    /// Ids of the sessions in groups with the tag `tag`
+    pub async fn fetch_tagged_session_ids(&self, tag: &str) -> Result<Vec<String>, String> {
+        #[derive(Deserialize)]
+        struct TaggedSession {
+            id: String,
         }
 
-        match client.post(&url).json(&body).send().await {
-            Ok(response) => {
-                if response.status().is_success() {
-                    let session: Session = response.json().await
-                        .map_err(|e| format!("Failed to parse created session: {}", e))?;
+        let mut url = reqwest::Url::parse(&format!("{}/api/sessions/by-tag", self.server_url()))
+            .map_err(|e| format!("Invalid server URL: {}", e))?;
+        url.path_segments_mut().map_err(|_| "Invalid server URL")?.push(tag);
 
-                    // Update local cache
-                    {
-                        let mut local_sessions = self.sessions.lock().unwrap(");
-                        local_sessions.insert(session.id.clone(), session.clone()");
-                    }
+        let response = self.client.get(url).send().await
+            .map_err(|e| format!("Failed to request sessions tagged {}: {}", tag, e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch sessions tagged {}: HTTP {}", tag, response.status()));
+        }
+
+        let sessions: Vec<TaggedSession> = response.json().await
+            .map_err(|e| format!("Failed to parse sessions tagged {}: {}", tag, e))?;
+        Ok(sessions.into_iter().map(|session| session.id).collect())
+    }
 
-                    // Emit event
-                    let event = SessionEvent {
-                        session_id: session.id.clone(),
-                        event_type: "created".to_string(),
-                        data: serde_json::to_value(&session).unwrap(),
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    };
-                    let _ = self.event_sender.send(event");
+    /// Put a session in the group named `tag`, carrying that tag, creating the group on first use
+    pub async fn tag_session(&self, session_id: &str, tag: &str) -> Result<(), String> {
+        let url = format!("{}/api/sessions/groups", self.server_url());
+        let group_lock = self.group_locks.lock().unwrap().entry(tag.to_string()).or_default().clone();
+        let creating = group_lock.lock().await;
 
-                    Ok(session)
-                } else {
-                    Err(format!("Failed to create session: HTTP {}", response.status()))
+        let response = self.client.get(&url).send().await
+            .map_err(|e| format!("Failed to request session groups: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch session groups: HTTP {}", response.status()));
+        }
+        let groups: Vec<SessionGroup> = response.json().await
+            .map_err(|e| format!("Failed to parse session groups: {}", e))?;
+
+        let group_id = match groups.into_iter().find(|group| group.name == tag) {
+            Some(group) => group.id,
+            None => {
+                let response = self.client.post(&url)
+                    .json(&serde_json::json!({ "name": tag, "tags": [tag] }))
+                    .send().await
+                    .map_err(|e| format!("Failed to create session group {}: {}", tag, e))?;
+                if !response.status().is_success() {
+                    return Err(format!("Failed to create session group {}: HTTP {}", tag, response.status()));
                 }
+                let group: SessionGroup = response.json().await
+                    .map_err(|e| format!("Failed to parse session group {}: {}", tag, e))?;
+                group.id
             }
-            Err(e) => Err(format!("Failed to create session: {}", e))
+        };
+        drop(creating);
+
+        let response = self.client.post(format!("{}/{}/sessions", url, group_id))
+            .json(&serde_json::json!({ "sessionId": session_id }))
+            .send().await
+            .map_err(|e| format!("Failed to tag session {}: {}", session_id, e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to tag session {}: HTTP {}", session_id, response.status()));
         }
+        Ok(())
+    }
+
+    /// Tags of all groups the session is in
+    pub async fn fetch_session_tags(&self, session_id: &str) -> Result<Vec<String>, String> {
+        let url = format!("{}/api/sessions/{}/groups", self.server_url(), session_id);
+
+        let response = self.client.get(&url).send().await
+            .map_err(|e| format!("Failed to request groups of session {}: {}", session_id, e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch groups of session {}: HTTP {}", session_id, response.status()));
+        }
+
+        let groups: Vec<SessionGroup> = response.json().await
+            .map_err(|e| format!("Failed to parse groups of session {}: {}", session_id, e))?;
+        Ok(groups.into_iter().flat_map(|group| group.tags).collect())
+    }
+
+    pub async fn create_session(&self, request: &CreateSessionRequest) -> Result<Session, String> {
+        let url = format!("{}/api/sessions", self.server_url());
+
+        let response = self.client.post(&url).json(request).send().await
+            .map_err(|e| format!("Failed to create session: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to create session: HTTP {}", response.status()));
+        }
+
+        let session: Session = response.json().await
+            .map_err(|e| format!("Failed to parse created session: {}", e))?;
+
+        self.sessions.lock().unwrap().insert(session.id.clone(), session.clone());
+        let _ = self.event_sender.send(SessionEvent::new(&session.id, SessionEventKind::Created(session.clone())));
+
+        Ok(session)
     }
 
     pub async fn delete_session(&self, session_id: &str) -> Result<(), String> {
-        let client = reqwest::Client::new(");
-        let url = format!("{}/api/sessions/{}", self.server_url, session_id");
-
-        match client.delete(&url).send().await {
-            Ok(response) => {
-                if response.status().is_success() {
-                    // Remove from local cache
-                    {
-                        let mut local_sessions = self.sessions.lock().unwrap(");
-                        local_sessions.remove(session_id");
-                    }
-
-                    // Emit event
-                    let event = SessionEvent {
-                        session_id: session_id.to_string(),
-                        event_type: "deleted".to_string(),
-                        data: serde_json::Value::Null,
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    };
-                    let _ = self.event_sender.send(event");
-
-                    Ok(())
-                } else {
-                    Err(format!("Failed to delete session: HTTP {}", response.status()))
-                }
-            }
-            Err(e) => Err(format!("Failed to delete session: {}", e))
+        let url = format!("{}/api/sessions/{}", self.server_url(), session_id);
+
+        let response = self.client.delete(&url).send().await
+            .map_err(|e| format!("Failed to delete session: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to delete session: HTTP {}", response.status()));
         }
+
+        self.sessions.lock().unwrap().remove(session_id);
+        let _ = self.event_sender.send(SessionEvent::new(session_id, SessionEventKind::Deleted));
+
+        Ok(())
     }
 
     pub async fn get_session_details(&self, session_id: &str) -> Result<Session, String> {
         // First check local cache
-        {
-            let sessions = self.sessions.lock().unwrap(");
-            if let Some(session) = sessions.get(session_id) {
-                return Ok(session.clone()");
-            }
+        if let Some(session) = self.sessions.lock().unwrap().get(session_id) {
+            return Ok(session.clone());
         }
 
-        // If not in cache, fetch from server
-        let client = reqwest::Client::new(");
-        let url = format!("{}/api/sessions/{}", self.server_url, session_id");
+        let url = format!("{}/api/sessions/{}", self.server_url(), session_id);
 
-        match client.get(&url).send().await {
-            Ok(response) => {
-                if response.status().is_success() {
-                    let session: Session = response.json().await
-                        .map_err(|e| format!("Failed to parse session details: {}", e))?;
+        let response = self.client.get(&url).send().await
+            .map_err(|e| format!("Failed to request session details: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to get session details: HTTP {}", response.status()));
+        }
 
-                    // Update local cache
-                    {
-                        let mut local_sessions = self.sessions.lock().unwrap(");
-                        local_sessions.insert(session.id.clone(), session.clone()");
-                    }
+        let session: Session = response.json().await
+            .map_err(|e| format!("Failed to parse session details: {}", e))?;
+        self.sessions.lock().unwrap().insert(session.id.clone(), session.clone());
 
-                    Ok(session)
-                } else {
-                    Err(format!("Failed to get session details: HTTP {}", response.status()))
-                }
-            }
-            Err(e) => Err(format!("Failed to request session details: {}", e))
-        }
+        Ok(session)
     }
 
     pub fn get_local_sessions(&self) -> Vec<Session> {
-        let sessions = self.sessions.lock().unwrap(");
+        let sessions = self.sessions.lock().unwrap();
         sessions.values().cloned().collect()
     }
 
+    pub fn event_sender(&self) -> SessionEventSender {
+        self.event_sender.clone()
+    }
+
     pub fn subscribe_to_events(&self) -> SessionEventReceiver {
         self.event_sender.subscribe()
     }
 }
 
+/// Events turning `previous` into `current`
+fn diff_sessions(previous: &HashMap<String, Session>, current: &[Session]) -> Vec<SessionEvent> {
+    let mut events = Vec::new();
+
+    for session in current {
+        match previous.get(&session.id) {
+            None => events.push(SessionEvent::new(&session.id, SessionEventKind::Created(session.clone()))),
+            Some(existing) => events.extend(
+                update_events(existing, session).into_iter().map(|kind| SessionEvent::new(&session.id, kind)),
+            ),
+        }
+    }
+
+    let current_ids: HashSet<&String> = current.iter().map(|s| &s.id).collect();
+    for session_id in previous.keys().filter(|id| !current_ids.contains(id)) {
+        events.push(SessionEvent::new(session_id, SessionEventKind::Deleted));
+    }
+
+    events
+}
+
+/// `Updated` with the full diff, followed by one event per notable change
+fn update_events(existing: &Session, session: &Session) -> Vec<SessionEventKind> {
+    let changes = existing.changes_to(session);
+    if changes.is_empty() {
+        return Vec::new();
+    }
+
+    let changed = |field: SessionField| changes.iter().any(|change| change.field == field);
+    let mut kinds = Vec::new();
+
+    if changed(SessionField::Status) {
+        kinds.push(match session.status.as_str() {
+            "exited" => SessionEventKind::Exited { exit_code: None },
+            _ => SessionEventKind::StatusChanged { from: existing.status.clone(), to: session.status.clone() },
+        });
+    }
+    if changed(SessionField::Title) {
+        kinds.push(SessionEventKind::TitleChanged { from: existing.title.clone(), to: session.title.clone() });
+    }
+    if changed(SessionField::LastActivity) || changed(SessionField::Active) {
+        kinds.push(SessionEventKind::ActivityChanged {
+            last_activity: session.last_activity.clone(),
+            active: session.active,
+        });
+    }
+
+    kinds.insert(0, SessionEventKind::Updated { session: session.clone(), changes });
+    kinds
+}
+
 // Tauri commands for session management
 #[tauri::command]
-pub async fn get_sessions(server_url: String) -> Result<Vec<Session>, String> {
-    let session_manager = SessionManager::new(server_url");
+pub async fn get_sessions(session_manager: State<'_, SessionManager>) -> Result<Vec<Session>, String> {
     session_manager.fetch_sessions().await
 }
 
 #[tauri::command]
 pub async fn create_session(
-    server_url: String,
+    session_manager: State<'_, SessionManager>,
     title: Option<String>,
     command: Option<String>
 ) -> Result<Session, String> {
-    let session_manager = SessionManager::new(server_url");
-    session_manager.create_session(title, command).await
+    let words = command.as_deref().map(shell::split_words).transpose()?.unwrap_or_default();
+    let request = CreateSessionRequest {
+        title,
+        command: words.iter().map(|word| shell::quote(word)).collect(),
+        ..Default::default()
+    };
+    session_manager.create_session(&request).await
 }
 
 #[tauri::command]
-pub async fn delete_session(server_url: String, session_id: String) -> Result<(), String> {
-    let session_manager = SessionManager::new(server_url");
+pub async fn delete_session(session_manager: State<'_, SessionManager>, session_id: String) -> Result<(), String> {
     session_manager.delete_session(&session_id).await
 }
 
 #[tauri::command]
-pub async fn get_session_details(server_url: String, session_id: String) -> Result<Session, String> {
-    let session_manager = SessionManager::new(server_url");
+pub async fn get_session_details(session_manager: State<'_, SessionManager>, session_id: String) -> Result<Session, String> {
     session_manager.get_session_details(&session_id).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::test_support::test_session_manager;
+
+    const SESSIONS_JSON: &str = r#"[
+        {"id":"a1","title":"zsh","command":"zsh","cwd":"/home/dev","cols":120,"rows":40,
+         "createdAt":"2025-01-27T10:00:00Z","updatedAt":"2025-01-27T10:05:00Z","status":"running","active":true,"clients":1}
+    ]"#;
+
+    #[test]
+    fn test_parses_server_sessions() {
+        let sessions: Vec<Session> = serde_json::from_str(SESSIONS_JSON).unwrap();
+
+        assert_eq!(sessions[0].working_directory.as_deref(), Some("/home/dev"));
+        assert_eq!(sessions[0].last_activity, "2025-01-27T10:05:00Z");
+        assert_eq!((sessions[0].cols, sessions[0].rows), (120, 40));
+        assert!(sessions[0].active);
+    }
+
+    #[test]
+    fn test_diff_emits_typed_events() {
+        let mut session: Session = serde_json::from_str::<Vec<Session>>(SESSIONS_JSON).unwrap().remove(0);
+        let mut previous = HashMap::new();
+
+        let events = diff_sessions(&previous, std::slice::from_ref(&session));
+        assert_eq!(events[0].kind, SessionEventKind::Created(session.clone()));
+
+        previous.insert(session.id.clone(), session.clone());
+        assert!(diff_sessions(&previous, std::slice::from_ref(&session)).is_empty());
+
+        session.status = "exited".to_string();
+        session.active = false;
+        let kinds: Vec<_> = diff_sessions(&previous, std::slice::from_ref(&session)).into_iter().map(|e| e.kind).collect();
+        match &kinds[0] {
+            SessionEventKind::Updated { changes, .. } => {
+                let fields: Vec<_> = changes.iter().map(|c| c.field).collect();
+                assert_eq!(fields, vec![SessionField::Status, SessionField::Active]);
+                assert_eq!(changes[0].to, serde_json::json!("exited"));
+            }
+            other => panic!("expected an update, got {:?}", other),
+        }
+        assert_eq!(kinds[1], SessionEventKind::Exited { exit_code: None });
+        assert_eq!(kinds[2].name(), "activity_changed");
+
+        let events = diff_sessions(&previous, &[]);
+        assert_eq!((events[0].session_id.as_str(), &events[0].kind), ("a1", &SessionEventKind::Deleted));
+    }
+
+    #[test]
+    fn test_pushed_exit_is_not_reported_again_by_refresh() {
+        let mut session: Session = serde_json::from_str::<Vec<Session>>(SESSIONS_JSON).unwrap().remove(0);
+        let session_manager = test_session_manager();
+        session_manager.replace_sessions(std::slice::from_ref(&session));
+        let mut events = session_manager.subscribe_to_events();
+
+        session_manager.publish_server_event(SessionEvent::new("a1", SessionEventKind::Exited { exit_code: Some(3) }));
+        session.status = "exited".to_string();
+        session.active = false;
+        session_manager.replace_sessions(std::slice::from_ref(&session));
+
+        let mut exits = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let SessionEventKind::Exited { exit_code } = event.kind {
+                exits.push(exit_code);
+            }
+        }
+        assert_eq!(exits, vec![Some(3)]);
+    }
+
+    #[test]
+    fn test_event_json_keeps_frontend_shape() {
+        let session: Session = serde_json::from_str::<Vec<Session>>(SESSIONS_JSON).unwrap().remove(0);
+        let event = SessionEvent::new("a1", SessionEventKind::Updated {
+            session: session.clone(),
+            changes: vec![SessionFieldChange { field: SessionField::Title, from: "bash".into(), to: "zsh".into() }],
+        });
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event_type"], "updated");
+        assert_eq!(json["data"]["id"], "a1");
+        assert_eq!(json["data"]["changes"][0]["field"], "title");
+        assert_eq!(serde_json::from_value::<SessionEvent>(json).unwrap(), event);
+
+        let json = serde_json::to_value(SessionEvent::new("a1", SessionEventKind::StatusChanged {
+            from: "running".to_string(),
+            to: "stopped".to_string(),
+        })).unwrap();
+        assert_eq!((json["event_type"].as_str(), json["data"]["to"].as_str()), (Some("status_changed"), Some("stopped")));
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::SessionManager;
+    use std::sync::Arc;
+
+    /// Session manager for a server that is never started, shared by the session tests
+    pub(crate) fn test_session_manager() -> SessionManager {
+        let server_manager = crate::server::ServerManager::new(
+            4021,
+            "127.0.0.1".to_string(),
+            Arc::new(crate::metrics::StartupTimer::new()),
+        );
+        SessionManager::new(server_manager)
+    }
+}
@@ -0,0 +1,297 @@
+// Recording playback
+// Replays asciicast files at an adjustable speed into a headless screen or the session window
+
+use super::preview::SessionScreens;
+use super::recording::{parse_cast, Cast, SessionRecorder};
+use super::stream::{OutputKind, SessionOutput};
+use super::websocket::{TerminalEvent, TerminalState, WebSocketManager};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::watch;
+use tokio::time::sleep;
+use log::{error, info};
+
+pub const PLAYBACK_EVENT: &str = "recording-playback";
+/// Screens of playbacks live next to session screens under ids with this prefix
+pub const PLAYBACK_ID_PREFIX: &str = "playback-";
+const MIN_SPEED: f64 = 0.1;
+const MAX_SPEED: f64 = 32.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackTarget {
+    /// Replay into a headless screen, readable with the preview and thumbnail commands
+    Emulator,
+    /// Stream the output to the session window as terminal output under the playback id
+    Window,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlaybackEvent {
+    Started { playback_id: String, file_name: String, cols: u16, rows: u16, duration_secs: f64 },
+    Finished { playback_id: String, stopped: bool },
+}
+
+fn validate_speed(speed: f64) -> Result<f64, String> {
+    if (MIN_SPEED..=MAX_SPEED).contains(&speed) {
+        Ok(speed)
+    } else {
+        Err(format!("Playback speed must be between {} and {}", MIN_SPEED, MAX_SPEED))
+    }
+}
+
+fn validate_idle_limit(idle_limit_secs: Option<f64>) -> Result<Option<f64>, String> {
+    match idle_limit_secs {
+        Some(limit) if !(limit.is_finite() && limit > 0.0) => Err("Idle limit must be a positive number of seconds".to_string()),
+        limit => Ok(limit),
+    }
+}
+
+/// How long to wait between two events at `speed`, with pauses capped at `idle_limit_secs`
+fn event_delay(previous_secs: f64, time_secs: f64, speed: f64, idle_limit_secs: Option<f64>) -> Duration {
+    let mut gap = (time_secs - previous_secs).max(0.0);
+    if let Some(limit) = idle_limit_secs {
+        gap = gap.min(limit);
+    }
+    // Gaps too long for a `Duration`, from a broken or hostile recording, are skipped
+    Duration::try_from_secs_f64(gap / speed).unwrap_or(Duration::ZERO)
+}
+
+/// How one playback runs; the speed can change while it does
+struct PlaybackOptions {
+    speed: Arc<Mutex<f64>>,
+    idle_limit_secs: Option<f64>,
+    target: PlaybackTarget,
+}
+
+struct Playback {
+    speed: Arc<Mutex<f64>>,
+    // Dropping the sender stops the playback
+    _shutdown: watch::Sender<()>,
+}
+
+#[derive(Clone)]
+pub struct RecordingPlayer {
+    recorder: SessionRecorder,
+    screens: SessionScreens,
+    terminals: WebSocketManager,
+    playbacks: Arc<Mutex<HashMap<String, Playback>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl RecordingPlayer {
+    pub fn new(recorder: SessionRecorder, screens: SessionScreens, terminals: WebSocketManager) -> Self {
+        Self {
+            recorder,
+            screens,
+            terminals,
+            playbacks: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    /// Start replaying a recording from the recordings directory; returns the playback id
+    pub fn play(
+        &self,
+        app: AppHandle,
+        file_name: &str,
+        speed: f64,
+        idle_limit_secs: Option<f64>,
+        target: PlaybackTarget,
+    ) -> Result<String, String> {
+        let speed = validate_speed(speed)?;
+        let idle_limit_secs = validate_idle_limit(idle_limit_secs)?;
+        let path = self.recorder.recording_path(file_name)?;
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read recording: {}", e))?;
+        let cast = parse_cast(&text)?;
+
+        let playback_id = format!("{}{}", PLAYBACK_ID_PREFIX, self.next_id.fetch_add(1, Ordering::SeqCst));
+        let speed = Arc::new(Mutex::new(speed));
+        let (shutdown, shutdown_receiver) = watch::channel(());
+        self.playbacks.lock().unwrap().insert(playback_id.clone(), Playback { speed: Arc::clone(&speed), _shutdown: shutdown });
+
+        if target == PlaybackTarget::Window {
+            if let Some(window) = app.try_state::<crate::ui::SessionWindow>() {
+                window.select_session(Some(playback_id.clone()));
+                if let Err(e) = window.show() {
+                    error!("Failed to show the session window for playback: {}", e);
+                }
+            }
+        }
+
+        info!("Playing {} as {} at {}x", file_name, playback_id, speed.lock().unwrap());
+        emit(&app, PlaybackEvent::Started {
+            playback_id: playback_id.clone(),
+            file_name: file_name.to_string(),
+            cols: cast.header.width,
+            rows: cast.header.height,
+            duration_secs: cast.duration_secs(),
+        });
+
+        let player = self.clone();
+        let id = playback_id.clone();
+        let options = PlaybackOptions { speed, idle_limit_secs, target };
+        tauri::async_runtime::spawn(async move {
+            player.run(app, &id, cast, options, shutdown_receiver).await;
+        });
+        Ok(playback_id)
+    }
+
+    pub fn set_speed(&self, playback_id: &str, speed: f64) -> Result<(), String> {
+        let speed = validate_speed(speed)?;
+        let playbacks = self.playbacks.lock().unwrap();
+        let playback = playbacks.get(playback_id).ok_or_else(|| format!("Playback {} is not running", playback_id))?;
+        *playback.speed.lock().unwrap() = speed;
+        Ok(())
+    }
+
+    /// Stop a playback and drop its screen
+    pub fn stop(&self, playback_id: &str) -> bool {
+        let stopped = self.playbacks.lock().unwrap().remove(playback_id).is_some();
+        let removed = self.screens.remove(playback_id);
+        stopped || removed
+    }
+
+    async fn run(&self, app: AppHandle, playback_id: &str, cast: Cast, options: PlaybackOptions, mut shutdown: watch::Receiver<()>) {
+        let target = options.target;
+        self.deliver(playback_id, target, OutputKind::Start { cols: cast.header.width, rows: cast.header.height });
+
+        let mut previous = 0.0;
+        let mut stopped = false;
+        for (time, kind) in cast.events {
+            let delay = event_delay(previous, time, *options.speed.lock().unwrap(), options.idle_limit_secs);
+            previous = time;
+
+            tokio::select! {
+                _ = sleep(delay) => {}
+                _ = shutdown.changed() => {
+                    stopped = true;
+                    break;
+                }
+            }
+            self.deliver(playback_id, target, kind);
+        }
+
+        // Still registered unless stopped; an emulator screen stays readable until `stop`
+        if shutdown.has_changed().is_ok() {
+            self.playbacks.lock().unwrap().remove(playback_id);
+        }
+        if target == PlaybackTarget::Window {
+            let reason = if stopped { "Playback stopped" } else { "Playback finished" };
+            self.publish_state(playback_id, TerminalState::Closed { reason: reason.to_string() });
+        }
+        info!("Playback {} {}", playback_id, if stopped { "stopped" } else { "finished" });
+        emit(&app, PlaybackEvent::Finished { playback_id: playback_id.to_string(), stopped });
+    }
+
+    fn deliver(&self, playback_id: &str, target: PlaybackTarget, kind: OutputKind) {
+        match target {
+            PlaybackTarget::Emulator => self.screens.apply(&SessionOutput {
+                session_id: playback_id.to_string(),
+                kind,
+                received_at: Instant::now(),
+            }),
+            // The window's terminal sizes itself; the cast size is in the `Started` event
+            PlaybackTarget::Window => match kind {
+                OutputKind::Start { .. } => self.publish_state(playback_id, TerminalState::Connected),
+                OutputKind::Output(data) => self.terminals.publish(TerminalEvent::Output {
+                    session_id: playback_id.to_string(),
+                    data: data.into_bytes(),
+                }),
+                OutputKind::Resize { .. } | OutputKind::Exit { .. } => {}
+            },
+        }
+    }
+
+    fn publish_state(&self, playback_id: &str, state: TerminalState) {
+        self.terminals.publish(TerminalEvent::State { session_id: playback_id.to_string(), state });
+    }
+}
+
+fn emit(app: &AppHandle, event: PlaybackEvent) {
+    if let Err(e) = app.emit(PLAYBACK_EVENT, &event) {
+        error!("Failed to emit playback event: {}", e);
+    }
+}
+
+// Tauri commands for recording playback
+#[tauri::command]
+pub async fn play_recording(
+    app: AppHandle,
+    player: State<'_, RecordingPlayer>,
+    file_name: String,
+    speed: Option<f64>,
+    idle_limit_secs: Option<f64>,
+    target: Option<PlaybackTarget>
+) -> Result<String, String> {
+    player.play(app, &file_name, speed.unwrap_or(1.0), idle_limit_secs, target.unwrap_or(PlaybackTarget::Emulator))
+}
+
+#[tauri::command]
+pub async fn set_playback_speed(player: State<'_, RecordingPlayer>, playback_id: String, speed: f64) -> Result<(), String> {
+    player.set_speed(&playback_id, speed)
+}
+
+#[tauri::command]
+pub async fn stop_playback(player: State<'_, RecordingPlayer>, playback_id: String) -> Result<bool, String> {
+    Ok(player.stop(&playback_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sessions::test_support::test_session_manager;
+    use crate::sessions::SessionOutputHub;
+
+    #[test]
+    fn test_event_delay_scales_and_caps_pauses() {
+        assert_eq!(event_delay(1.0, 3.0, 1.0, None), Duration::from_secs(2));
+        assert_eq!(event_delay(1.0, 3.0, 4.0, None), Duration::from_millis(500));
+        assert_eq!(event_delay(0.0, 60.0, 2.0, Some(2.0)), Duration::from_secs(1));
+        assert_eq!(event_delay(3.0, 1.0, 1.0, None), Duration::ZERO);
+        assert_eq!(event_delay(0.0, 1e300, 1.0, None), Duration::ZERO);
+        assert_eq!(event_delay(0.0, f64::MAX, 0.1, None), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_idle_limit_must_be_positive() {
+        assert_eq!(validate_idle_limit(None), Ok(None));
+        assert_eq!(validate_idle_limit(Some(1.5)), Ok(Some(1.5)));
+        for limit in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(validate_idle_limit(Some(limit)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_speed_bounds_and_event_shape() {
+        assert!(validate_speed(0.0).is_err());
+        assert!(validate_speed(f64::NAN).is_err());
+        assert_eq!(validate_speed(2.5), Ok(2.5));
+
+        let json = serde_json::to_value(PlaybackEvent::Finished { playback_id: "playback-1".to_string(), stopped: false }).unwrap();
+        assert_eq!(json, serde_json::json!({ "kind": "finished", "playback_id": "playback-1", "stopped": false }));
+    }
+
+    #[test]
+    fn test_window_target_feeds_terminal_output() {
+        let session_manager = test_session_manager();
+        let recorder = SessionRecorder::new(session_manager.clone(), SessionOutputHub::new(session_manager.clone()));
+        let terminals = WebSocketManager::new(session_manager.clone());
+        let player = RecordingPlayer::new(recorder, SessionScreens::new(session_manager), terminals.clone());
+        let mut events = terminals.subscribe();
+
+        player.deliver("playback-1", PlaybackTarget::Window, OutputKind::Start { cols: 80, rows: 24 });
+        player.deliver("playback-1", PlaybackTarget::Window, OutputKind::Output("$ ls\r\n".to_string()));
+
+        assert!(matches!(events.try_recv(), Ok(TerminalEvent::State { state: TerminalState::Connected, .. })));
+        match events.try_recv() {
+            Ok(TerminalEvent::Output { session_id, data }) => assert_eq!((session_id.as_str(), data), ("playback-1", b"$ ls\r\n".to_vec())),
+            _ => panic!("expected terminal output"),
+        }
+    }
+}
@@ -0,0 +1,362 @@
+// Session screen previews
+// Keeps a headless terminal per followed session for text snapshots and PNG thumbnails
+
+use super::buffer::{fetch_buffer_snapshot, TerminalSnapshot};
+use super::emulator::{Color, Terminal};
+use super::font::{self, GLYPH_HEIGHT, GLYPH_WIDTH};
+use super::playback::PLAYBACK_ID_PREFIX;
+use super::stream::{OutputKind, SessionOutput};
+use super::SessionManager;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::State;
+use tokio::sync::broadcast;
+use log::warn;
+
+pub const DEFAULT_PREVIEW_LINES: usize = 20;
+pub const DEFAULT_THUMBNAIL_WIDTH: u32 = 320;
+/// Size assumed when output arrives before the stream header
+const FALLBACK_COLS: u16 = 80;
+const FALLBACK_ROWS: u16 = 24;
+/// How long a preview waits on the server's buffer socket for a session without a local screen
+const SNAPSHOT_FETCH_TIMEOUT: Duration = Duration::from_secs(2);
+/// Largest part of a screen drawn into a thumbnail; sizes come from the server and are not
+/// otherwise bounded, and a full-size render allocates 180 bytes per cell before downscaling
+const MAX_RENDER_COLS: u16 = 320;
+const MAX_RENDER_ROWS: u16 = 120;
+
+const DEFAULT_FG: [u8; 3] = [229, 229, 229];
+const DEFAULT_BG: [u8; 3] = [30, 30, 30];
+/// xterm's default ANSI colors
+const ANSI_COLORS: [[u8; 3]; 16] = [
+    [0, 0, 0], [205, 0, 0], [0, 205, 0], [205, 205, 0], [0, 0, 238], [205, 0, 205], [0, 205, 205], [229, 229, 229],
+    [127, 127, 127], [255, 0, 0], [0, 255, 0], [255, 255, 0], [92, 92, 255], [255, 0, 255], [0, 255, 255], [255, 255, 255],
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionPreview {
+    pub session_id: String,
+    /// Title the program set via OSC, if any
+    pub title: String,
+    pub cols: u16,
+    pub rows: u16,
+    pub alternate_screen: bool,
+    pub lines: Vec<String>,
+}
+
+impl SessionPreview {
+    /// Preview of a snapshot from the server's buffer socket, which has no title or screen mode
+    pub fn from_snapshot(snapshot: &TerminalSnapshot, lines: usize) -> Self {
+        let text = snapshot.text();
+        let all: Vec<&str> = if text.is_empty() { Vec::new() } else { text.lines().collect() };
+
+        Self {
+            session_id: snapshot.session_id.clone(),
+            title: String::new(),
+            cols: snapshot.cols,
+            rows: snapshot.rows,
+            alternate_screen: false,
+            lines: all[all.len().saturating_sub(lines)..].iter().map(|line| line.to_string()).collect(),
+        }
+    }
+}
+
+fn indexed_color(index: u8) -> [u8; 3] {
+    match index {
+        0..=15 => ANSI_COLORS[index as usize],
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let n = index - 16;
+            [level(n / 36), level(n / 6 % 6), level(n % 6)]
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            [gray, gray, gray]
+        }
+    }
+}
+
+fn resolve_color(color: Color, default: [u8; 3], bold: bool) -> [u8; 3] {
+    match color {
+        Color::Default => default,
+        // Bold doubles as "bright" for the basic colors
+        Color::Indexed(index @ 0..=7) if bold => indexed_color(index + 8),
+        Color::Indexed(index) => indexed_color(index),
+        Color::Rgb(r, g, b) => [r, g, b],
+    }
+}
+
+/// Pixels of the screen, one glyph cell per terminal cell, as RGB rows
+fn render_screen(terminal: &Terminal) -> (usize, usize, Vec<u8>) {
+    let (cols, rows) = terminal.size();
+    // Oversized screens are cropped to their top-left corner
+    let (cols, rows) = (cols.min(MAX_RENDER_COLS), rows.min(MAX_RENDER_ROWS));
+    let (width, height) = (cols as usize * GLYPH_WIDTH, rows as usize * GLYPH_HEIGHT);
+    let mut pixels = vec![0u8; width * height * 3];
+    let cursor = terminal.cursor_visible().then(|| terminal.cursor());
+
+    for y in 0..rows {
+        for x in 0..cols {
+            let Some(cell) = terminal.cell(x, y) else { continue };
+            let attributes = cell.style.attributes;
+
+            let mut fg = resolve_color(cell.style.fg, DEFAULT_FG, attributes.bold);
+            let mut bg = resolve_color(cell.style.bg, DEFAULT_BG, false);
+            if attributes.inverse != (cursor == Some((x, y))) {
+                std::mem::swap(&mut fg, &mut bg);
+            }
+            if attributes.dim {
+                fg = [0, 1, 2].map(|c| ((fg[c] as u16 + bg[c] as u16) / 2) as u8);
+            }
+
+            let glyph = match cell.ch {
+                // Second half of a wide character, drawn by the first half
+                _ if cell.width == 0 => &[0; GLYPH_HEIGHT],
+                ch => font::glyph(ch).unwrap_or_else(font::fallback_glyph),
+            };
+
+            for (row, &bits) in glyph.iter().enumerate() {
+                let underline = attributes.underline && row == GLYPH_HEIGHT - 2;
+                let strikeout = attributes.strikeout && row == GLYPH_HEIGHT / 2 - 1;
+                for column in 0..GLYPH_WIDTH {
+                    let lit = underline || strikeout || bits & (0x80 >> column) != 0;
+                    let px = x as usize * GLYPH_WIDTH + column;
+                    let py = y as usize * GLYPH_HEIGHT + row;
+                    let offset = (py * width + px) * 3;
+                    pixels[offset..offset + 3].copy_from_slice(if lit { &fg } else { &bg });
+                }
+            }
+        }
+    }
+
+    (width, height, pixels)
+}
+
+/// Average `factor` x `factor` blocks of RGB pixels
+fn downscale(width: usize, height: usize, pixels: &[u8], factor: usize) -> (usize, usize, Vec<u8>) {
+    let (scaled_width, scaled_height) = ((width / factor).max(1), (height / factor).max(1));
+    let mut scaled = Vec::with_capacity(scaled_width * scaled_height * 3);
+
+    for y in 0..scaled_height {
+        for x in 0..scaled_width {
+            let mut sum = [0usize; 3];
+            let mut count = 0;
+            for py in y * factor..((y + 1) * factor).min(height) {
+                for px in x * factor..((x + 1) * factor).min(width) {
+                    let offset = (py * width + px) * 3;
+                    (0..3).for_each(|c| sum[c] += pixels[offset + c] as usize);
+                    count += 1;
+                }
+            }
+            scaled.extend(sum.map(|total| (total / count.max(1)) as u8));
+        }
+    }
+
+    (scaled_width, scaled_height, scaled)
+}
+
+/// PNG of the screen, scaled down by whole factors until it is at most `max_width` pixels wide
+pub fn render_png(terminal: &Terminal, max_width: u32) -> Result<Vec<u8>, String> {
+    let (mut width, mut height, mut pixels) = render_screen(terminal);
+
+    let factor = width.div_ceil(max_width.max(1) as usize);
+    if factor > 1 {
+        (width, height, pixels) = downscale(width, height, &pixels, factor);
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+    Ok(png)
+}
+
+/// Screens of all followed sessions, fed by the `SessionOutputHub`
+#[derive(Clone)]
+pub struct SessionScreens {
+    session_manager: SessionManager,
+    screens: Arc<Mutex<HashMap<String, Terminal>>>,
+}
+
+impl SessionScreens {
+    pub fn new(session_manager: SessionManager) -> Self {
+        Self {
+            session_manager,
+            screens: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Apply session output until the hub's channel closes
+    pub fn start(&self, mut receiver: broadcast::Receiver<SessionOutput>) {
+        let screens = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(output) => screens.apply(&output),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Dropped {} output events; session previews may be garbled until redrawn", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    pub fn apply(&self, output: &SessionOutput) {
+        let mut screens = self.screens.lock().unwrap();
+
+        match &output.kind {
+            OutputKind::Start { cols, rows } | OutputKind::Resize { cols, rows } => {
+                screens.entry(output.session_id.clone())
+                    .or_insert_with(|| Terminal::new(*cols, *rows))
+                    .resize(*cols, *rows);
+            }
+            OutputKind::Output(data) => {
+                screens.entry(output.session_id.clone())
+                    .or_insert_with(|| Terminal::new(FALLBACK_COLS, FALLBACK_ROWS))
+                    .process(data.as_bytes());
+            }
+            // The last screen of an exited session stays around until the session is removed
+            OutputKind::Exit { .. } => {
+                let live: HashSet<String> = self.session_manager.get_local_sessions().into_iter().map(|s| s.id).collect();
+                screens.retain(|id, _| live.contains(id) || id.starts_with(PLAYBACK_ID_PREFIX));
+            }
+        }
+    }
+
+    pub fn remove(&self, session_id: &str) -> bool {
+        self.screens.lock().unwrap().remove(session_id).is_some()
+    }
+
+    pub fn preview(&self, session_id: &str, lines: usize) -> Option<SessionPreview> {
+        let screens = self.screens.lock().unwrap();
+        let terminal = screens.get(session_id)?;
+        let (cols, rows) = terminal.size();
+
+        Some(SessionPreview {
+            session_id: session_id.to_string(),
+            title: terminal.title().to_string(),
+            cols,
+            rows,
+            alternate_screen: terminal.is_alternate_screen(),
+            lines: terminal.last_lines(lines),
+        })
+    }
+
+    /// Last non-empty lines joined for a notification body; `None` before any output
+    pub fn summary(&self, session_id: &str, lines: usize) -> Option<String> {
+        let preview = self.preview(session_id, lines)?;
+        let text = preview.lines.iter().map(|line| line.trim()).filter(|line| !line.is_empty()).collect::<Vec<_>>().join("\n");
+        (!text.is_empty()).then_some(text)
+    }
+
+    pub fn thumbnail(&self, session_id: &str, max_width: u32) -> Result<Vec<u8>, String> {
+        let screens = self.screens.lock().unwrap();
+        let terminal = screens.get(session_id)
+            .ok_or_else(|| format!("No screen for session {}", session_id))?;
+        render_png(terminal, max_width)
+    }
+}
+
+// Tauri commands for session previews
+#[tauri::command]
+pub async fn get_session_preview(
+    screens: State<'_, SessionScreens>,
+    session_manager: State<'_, SessionManager>,
+    session_id: String,
+    lines: Option<usize>
+) -> Result<SessionPreview, String> {
+    let lines = lines.unwrap_or(DEFAULT_PREVIEW_LINES);
+    if let Some(preview) = screens.preview(&session_id, lines) {
+        return Ok(preview);
+    }
+
+    // Sessions this app is not following have no local screen; ask the server for one
+    let snapshot = fetch_buffer_snapshot(&session_manager.server_url(), &session_id, SNAPSHOT_FETCH_TIMEOUT).await
+        .map_err(|e| format!("No screen for session {}: {}", session_id, e))?;
+    Ok(SessionPreview::from_snapshot(&snapshot, lines))
+}
+
+/// PNG thumbnail of the session's screen as a `data:` URL
+#[tauri::command]
+pub async fn get_session_thumbnail(
+    screens: State<'_, SessionScreens>,
+    session_id: String,
+    max_width: Option<u32>
+) -> Result<String, String> {
+    let png = screens.thumbnail(&session_id, max_width.unwrap_or(DEFAULT_THUMBNAIL_WIDTH))?;
+    Ok(format!("data:image/png;base64,{}", data_encoding::BASE64.encode(&png)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(png: &[u8]) -> (png::OutputInfo, Vec<u8>) {
+        let mut reader = png::Decoder::new(png).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        pixels.truncate(info.buffer_size());
+        (info, pixels)
+    }
+
+    #[test]
+    fn test_palette() {
+        assert_eq!(indexed_color(1), [205, 0, 0]);
+        assert_eq!(indexed_color(16), [0, 0, 0]);
+        assert_eq!(indexed_color(196), [255, 0, 0]);
+        assert_eq!(indexed_color(232), [8, 8, 8]);
+        assert_eq!(resolve_color(Color::Indexed(1), DEFAULT_FG, true), [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_renders_cells_as_pixels() {
+        let mut terminal = Terminal::new(2, 1);
+        terminal.process(b"\x1b[?25l\x1b[41m \x1b[0m\x1b[7m ");
+
+        let (info, pixels) = decode(&render_png(&terminal, 1000).unwrap());
+        assert_eq!((info.width, info.height), (12, 10));
+        assert_eq!(pixels[..3], [205, 0, 0]);
+        assert_eq!(pixels[(6 * 3)..(6 * 3 + 3)], DEFAULT_FG);
+    }
+
+    #[test]
+    fn test_thumbnail_fits_max_width() {
+        let mut terminal = Terminal::new(80, 24);
+        terminal.process(b"$ cargo build\r\n   Compiling tunnelforge");
+
+        let (info, _) = decode(&render_png(&terminal, 200).unwrap());
+        assert_eq!((info.width, info.height), (160, 80));
+    }
+
+    #[test]
+    fn test_oversized_screens_are_cropped() {
+        let mut terminal = Terminal::new(1000, 400);
+        terminal.process(b"\x1b[41m ");
+
+        let (info, pixels) = decode(&render_png(&terminal, u32::MAX).unwrap());
+        assert_eq!((info.width, info.height), (MAX_RENDER_COLS as u32 * 6, MAX_RENDER_ROWS as u32 * 10));
+        assert_eq!(pixels[..3], [205, 0, 0]);
+    }
+
+    #[test]
+    fn test_screens_follow_output() {
+        let screens = SessionScreens::new(crate::sessions::test_support::test_session_manager());
+        let output = |kind| SessionOutput { session_id: "a1".to_string(), kind, received_at: std::time::Instant::now() };
+
+        screens.apply(&output(OutputKind::Start { cols: 20, rows: 5 }));
+        screens.apply(&output(OutputKind::Output("\x1b]2;vim\x07$ make\r\nok\r\n".to_string())));
+
+        let preview = screens.preview("a1", 1).unwrap();
+        assert_eq!((preview.title.as_str(), preview.cols, preview.lines), ("vim", 20, vec!["ok".to_string()]));
+        assert_eq!(screens.summary("a1", 5).as_deref(), Some("$ make\nok"));
+
+        screens.apply(&output(OutputKind::Exit { exit_code: Some(0) }));
+        assert!(screens.preview("a1", 1).is_none());
+    }
+}
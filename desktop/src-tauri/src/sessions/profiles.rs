@@ -0,0 +1,489 @@
+// Session launch profiles
+// Named presets for command, working directory, environment and title, launched by command, tray or shortcut
+
+use super::recording::SessionRecorder;
+use super::shell;
+use super::{CreateSessionRequest, Session, SessionEventKind, SessionManager};
+use crate::config::update_and_apply;
+use crate::AppState;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use tokio::sync::{broadcast, watch};
+use log::{error, info, warn};
+
+/// Tray menu items that launch a profile have ids with this prefix
+pub const PROFILE_MENU_PREFIX: &str = "launch_profile:";
+/// Sessions are tagged on the server with this prefix and the profile id, so the link survives app restarts
+pub const PROFILE_TAG_PREFIX: &str = "profile:";
+
+pub fn profile_tag(profile_id: &str) -> String {
+    format!("{}{}", PROFILE_TAG_PREFIX, profile_id)
+}
+
+/// Profile id from the tags of a session's groups
+fn tagged_profile(tags: &[String]) -> Option<String> {
+    tags.iter().find_map(|tag| tag.strip_prefix(PROFILE_TAG_PREFIX)).map(str::to_string)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchProfile {
+    /// Derived from the name when a new profile is saved without one
+    pub id: String,
+    pub name: String,
+    /// Program to run; the user's shell when unset
+    pub command: Option<String>,
+    /// Passed to the program as separate arguments, even when they contain spaces
+    pub args: Vec<String>,
+    /// `~` is the home directory; its last component is `${project}`
+    pub working_directory: Option<String>,
+    pub env: BTreeMap<String, String>,
+    /// The server picks a title when unset
+    pub title_template: Option<String>,
+    /// Start recording as soon as the session is created
+    pub auto_record: bool,
+    /// Show a notification when the session exits
+    pub notify: bool,
+    /// Global shortcut such as `CmdOrCtrl+Shift+1`
+    pub shortcut: Option<String>,
+}
+
+/// Replace every `${name}` in `template` with `lookup(name)`
+fn expand(template: &str, lookup: &dyn Fn(&str) -> Result<String, String>) -> Result<String, String> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let end = rest[start..].find('}').map(|end| start + end)
+            .ok_or_else(|| format!("Unclosed variable in \"{}\"", template))?;
+        expanded.push_str(&lookup(&rest[start + 2..end])?);
+        rest = &rest[end + 1..];
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+pub(super) fn expand_home(path: &str) -> String {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => match dirs::home_dir() {
+            Some(home) => format!("{}{}", home.display(), rest),
+            None => path.to_string(),
+        },
+        _ => path.to_string(),
+    }
+}
+
+impl LaunchProfile {
+    /// The create request with variables expanded for a launch at `now`
+    pub fn to_request(&self, now: DateTime<Local>) -> Result<CreateSessionRequest, String> {
+        let date = now.format("%Y-%m-%d").to_string();
+        let variable = |name: &str, project: Option<&str>| match name {
+            "date" => Ok(date.clone()),
+            "profile" => Ok(self.name.clone()),
+            "project" => project.map(str::to_string)
+                .ok_or_else(|| "${project} needs a working directory and can't be used in it".to_string()),
+            _ => Err(format!("Unknown variable ${{{}}}", name)),
+        };
+
+        let working_directory = match self.working_directory.as_deref().filter(|dir| !dir.is_empty()) {
+            Some(dir) => Some(expand_home(&expand(dir, &|name| variable(name, None))?)),
+            None => None,
+        };
+        let project = working_directory.as_deref()
+            .and_then(|dir| Path::new(dir).file_name())
+            .map(|name| name.to_string_lossy().into_owned());
+        let lookup = |name: &str| variable(name, project.as_deref());
+
+        let command = self.command.iter().filter(|command| !command.is_empty()).chain(&self.args)
+            .map(|word| expand(word, &lookup).map(|word| shell::quote(&word)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let env = self.env.iter()
+            .map(|(key, value)| Ok((key.clone(), expand(value, &lookup)?)))
+            .collect::<Result<BTreeMap<_, _>, String>>()?;
+        let title = self.title_template.as_deref()
+            .filter(|title| !title.is_empty())
+            .map(|title| expand(title, &lookup))
+            .transpose()?;
+
+        Ok(CreateSessionRequest { title, command, cwd: working_directory, env, ..Default::default() })
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Launch profile needs a name".to_string());
+        }
+        if !self.args.is_empty() && self.command.as_deref().is_none_or(str::is_empty) {
+            return Err(format!("Launch profile {} has arguments but no command", self.name));
+        }
+        if let Some(key) = self.env.keys().find(|key| key.is_empty() || key.contains(['=', '\0'])) {
+            return Err(format!("Invalid environment variable name {:?} in launch profile {}", key, self.name));
+        }
+        self.to_request(Local::now())
+            .map(|_| ())
+            .map_err(|e| format!("Launch profile {}: {}", self.name, e))
+    }
+}
+
+pub fn validate_launch_profiles(profiles: &[LaunchProfile]) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    let mut shortcuts = HashSet::new();
+
+    for profile in profiles {
+        profile.validate()?;
+        if profile.id.is_empty() || !ids.insert(profile.id.as_str()) {
+            return Err(format!("Launch profile {} needs a unique id", profile.name));
+        }
+        if let Some(shortcut) = profile.shortcut.as_deref().filter(|shortcut| !shortcut.is_empty()) {
+            if !shortcuts.insert(shortcut.to_lowercase()) {
+                return Err(format!("Shortcut {} is used by more than one launch profile", shortcut));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lowercase name with dashes, numbered when another profile already has it
+fn profile_id(name: &str, profiles: &[LaunchProfile]) -> String {
+    let slug = name.to_lowercase()
+        .split(|ch: char| !ch.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let base = if slug.is_empty() { "profile".to_string() } else { slug };
+
+    let taken = |id: &str| profiles.iter().any(|profile| profile.id == id);
+    let mut id = base.clone();
+    let mut n = 2;
+    while taken(&id) {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+/// Add `profile`, or replace the one with its id; returns it as stored
+fn upsert_profile(profiles: &mut Vec<LaunchProfile>, mut profile: LaunchProfile) -> LaunchProfile {
+    if profile.id.is_empty() {
+        profile.id = profile_id(&profile.name, profiles);
+    }
+
+    match profiles.iter_mut().find(|existing| existing.id == profile.id) {
+        Some(existing) => *existing = profile.clone(),
+        None => profiles.push(profile.clone()),
+    }
+    profile
+}
+
+/// Creates sessions from profiles and owns what outlives the launch: exit notifications and shortcuts
+#[derive(Clone)]
+pub struct ProfileLauncher {
+    session_manager: SessionManager,
+    recorder: SessionRecorder,
+    // Sessions launched with `notify`, with the profile name
+    notify: Arc<Mutex<HashMap<String, String>>>,
+    // Sessions launched from a profile, with the profile id; restored from the server's tags for
+    // sessions that predate this app run
+    launched: Arc<Mutex<HashMap<String, String>>>,
+    // Launches whose session exists but is not in `launched` yet; restoring waits for them, so the
+    // `Created` event of a session being launched does not send it to the server for its tags
+    pending_launches: Arc<watch::Sender<usize>>,
+    shortcuts: Arc<Mutex<Vec<String>>>,
+}
+
+impl ProfileLauncher {
+    pub fn new(session_manager: SessionManager, recorder: SessionRecorder) -> Self {
+        Self {
+            session_manager,
+            recorder,
+            notify: Arc::new(Mutex::new(HashMap::new())),
+            launched: Arc::new(Mutex::new(HashMap::new())),
+            pending_launches: Arc::new(watch::channel(0).0),
+            shortcuts: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub async fn launch(&self, profile: &LaunchProfile) -> Result<Session, String> {
+        let request = profile.to_request(Local::now())?;
+        let session = {
+            let _pending = PendingLaunch::new(&self.pending_launches);
+            let session = self.session_manager.create_session(&request).await?;
+            self.launched.lock().unwrap().insert(session.id.clone(), profile.id.clone());
+            session
+        };
+        info!("Launched profile {} as session {}", profile.name, session.id);
+
+        if profile.auto_record {
+            if let Err(e) = self.recorder.start_recording(&session.id) {
+                warn!("Failed to record session {} of profile {}: {}", session.id, profile.name, e);
+            }
+        }
+        if profile.notify {
+            self.notify.lock().unwrap().insert(session.id.clone(), profile.name.clone());
+        }
+        if let Err(e) = self.session_manager.tag_session(&session.id, &profile_tag(&profile.id)).await {
+            warn!("Failed to tag session {} with profile {}: {}", session.id, profile.name, e);
+        }
+        Ok(session)
+    }
+
+    /// Id of the profile the session was launched from
+    pub fn launched_profile(&self, session_id: &str) -> Option<String> {
+        self.launched.lock().unwrap().get(session_id).cloned()
+    }
+
+    /// Look up the profile of a session this app run did not launch in the session's server tags
+    async fn restore_launched(&self, session_id: &str) {
+        // A launch in flight may be about to claim this session
+        let _ = self.pending_launches.subscribe().wait_for(|pending| *pending == 0).await;
+        if self.launched_profile(session_id).is_some() {
+            return;
+        }
+
+        match self.session_manager.fetch_session_tags(session_id).await {
+            Ok(tags) => {
+                if let Some(profile_id) = tagged_profile(&tags) {
+                    self.launched.lock().unwrap().insert(session_id.to_string(), profile_id);
+                }
+            }
+            Err(e) => warn!("Failed to fetch the profile of session {}: {}", session_id, e),
+        }
+    }
+
+    /// Notify when sessions launched with `notify` end, restore the profiles of sessions found
+    /// on the server and forget deleted sessions, until the event channel closes
+    pub fn start(&self, app: AppHandle) {
+        let launcher = self.clone();
+        let mut events = self.session_manager.subscribe_to_events();
+
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                let exit_code = match event.kind {
+                    SessionEventKind::Created(_) => {
+                        if launcher.launched_profile(&event.session_id).is_none() {
+                            let launcher = launcher.clone();
+                            tauri::async_runtime::spawn(async move { launcher.restore_launched(&event.session_id).await });
+                        }
+                        continue;
+                    }
+                    SessionEventKind::Exited { exit_code } => exit_code,
+                    SessionEventKind::Deleted => {
+                        launcher.launched.lock().unwrap().remove(&event.session_id);
+                        None
+                    }
+                    _ => continue,
+                };
+                let profile = launcher.notify.lock().unwrap().remove(&event.session_id);
+                if let (Some(profile), Some(service)) = (profile, app.try_state::<crate::notifications::NotificationService>()) {
+                    let body = match exit_code {
+                        Some(0) => "Finished successfully".to_string(),
+                        Some(code) => format!("Exited with code {}", code),
+                        None => "Session ended".to_string(),
+                    };
+                    service.show_notification(&profile, &body, "Launched from a profile", None).await;
+                }
+            }
+        });
+    }
+
+    /// Replace the registered profile shortcuts with the ones in `profiles`
+    pub fn register_shortcuts(&self, app: &AppHandle, profiles: &[LaunchProfile]) {
+        let global_shortcut = app.global_shortcut();
+        let mut registered = self.shortcuts.lock().unwrap();
+
+        for shortcut in registered.drain(..) {
+            if let Err(e) = global_shortcut.unregister(shortcut.as_str()) {
+                warn!("Failed to unregister shortcut {}: {}", shortcut, e);
+            }
+        }
+
+        for profile in profiles {
+            let Some(shortcut) = profile.shortcut.clone().filter(|shortcut| !shortcut.is_empty()) else { continue };
+            let profile_id = profile.id.clone();
+            let result = global_shortcut.on_shortcut(shortcut.as_str(), move |app, _, event| {
+                if event.state() == ShortcutState::Pressed {
+                    spawn_profile_launch(app, &profile_id);
+                }
+            });
+
+            match result {
+                Ok(()) => registered.push(shortcut),
+                Err(e) => warn!("Failed to register shortcut {} for profile {}: {}", shortcut, profile.name, e),
+            }
+        }
+    }
+}
+
+/// Counts a launch as pending until dropped, including when the launch fails
+struct PendingLaunch<'a>(&'a watch::Sender<usize>);
+
+impl<'a> PendingLaunch<'a> {
+    fn new(pending: &'a watch::Sender<usize>) -> Self {
+        pending.send_modify(|count| *count += 1);
+        Self(pending)
+    }
+}
+
+impl Drop for PendingLaunch<'_> {
+    fn drop(&mut self) {
+        self.0.send_modify(|count| *count -= 1);
+    }
+}
+
+/// Launch a configured profile in the background, for the tray and shortcuts
+pub fn spawn_profile_launch(app: &AppHandle, profile_id: &str) {
+    let app = app.clone();
+    let profile_id = profile_id.to_string();
+
+    tauri::async_runtime::spawn(async move {
+        let profile = app.state::<AppState>().config.lock().unwrap()
+            .launch_profiles.iter().find(|profile| profile.id == profile_id).cloned();
+        let Some(profile) = profile else {
+            warn!("Launch profile {} no longer exists", profile_id);
+            return;
+        };
+
+        if let Err(e) = app.state::<ProfileLauncher>().launch(&profile).await {
+            error!("Failed to launch profile {}: {}", profile.name, e);
+        }
+    });
+}
+
+// Tauri commands for launch profiles
+#[tauri::command]
+pub async fn list_launch_profiles(app: AppHandle) -> Result<Vec<LaunchProfile>, String> {
+    Ok(app.state::<AppState>().config.lock().unwrap().launch_profiles.clone())
+}
+
+/// Create a profile, or replace the one with the same id; returns it with its id
+#[tauri::command]
+pub async fn save_launch_profile(app: AppHandle, profile: LaunchProfile) -> Result<LaunchProfile, String> {
+    // Applying the config re-registers shortcuts and rebuilds the tray menu
+    update_and_apply(&app, |config| Ok(upsert_profile(&mut config.launch_profiles, profile)))
+}
+
+#[tauri::command]
+pub async fn delete_launch_profile(app: AppHandle, profile_id: String) -> Result<(), String> {
+    update_and_apply(&app, |config| {
+        let count = config.launch_profiles.len();
+        config.launch_profiles.retain(|profile| profile.id != profile_id);
+        if config.launch_profiles.len() == count {
+            return Err(format!("Launch profile {} not found", profile_id));
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn launch_profile(
+    app: AppHandle,
+    launcher: State<'_, ProfileLauncher>,
+    profile_id: String
+) -> Result<Session, String> {
+    let profile = app.state::<AppState>().config.lock().unwrap()
+        .launch_profiles.iter().find(|profile| profile.id == profile_id).cloned()
+        .ok_or_else(|| format!("Launch profile {} not found", profile_id))?;
+    launcher.launch(&profile).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sessions::SessionOutputHub;
+    use chrono::TimeZone;
+
+    fn launch_time() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 3, 7, 9, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_expands_variables_into_request() {
+        let profile = LaunchProfile {
+            name: "API".to_string(),
+            command: Some("npm".to_string()),
+            args: vec!["run".to_string(), "dev server".to_string(), "--log=${project}-${date}.log".to_string()],
+            working_directory: Some("/work/${date}/tunnelforge".to_string()),
+            env: BTreeMap::from([("SESSION_NAME".to_string(), "${profile}".to_string())]),
+            title_template: Some("${project} (${date})".to_string()),
+            ..Default::default()
+        };
+
+        let request = profile.to_request(launch_time()).unwrap();
+        assert_eq!(request.command, vec!["npm", "run", "'dev server'", "--log=tunnelforge-2025-03-07.log"]);
+        assert_eq!(request.cwd.as_deref(), Some("/work/2025-03-07/tunnelforge"));
+        assert_eq!(request.title.as_deref(), Some("tunnelforge (2025-03-07)"));
+        assert_eq!(request.env.get("SESSION_NAME").map(String::as_str), Some("API"));
+        assert_eq!(shell::quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_profile_tags_round_trip() {
+        let tags = vec!["build".to_string(), profile_tag("api-server")];
+        assert_eq!(tagged_profile(&tags).as_deref(), Some("api-server"));
+        assert_eq!(tagged_profile(&["build".to_string()]), None);
+    }
+
+    #[test]
+    fn test_rejects_bad_templates() {
+        let profile = |title: &str| LaunchProfile {
+            name: "Shell".to_string(),
+            title_template: Some(title.to_string()),
+            ..Default::default()
+        };
+
+        assert!(profile("${project}").validate().is_err());
+        assert!(profile("${user}").validate().is_err());
+        assert!(profile("${date").validate().is_err());
+        assert!(profile("$HOME on ${date}").validate().is_ok());
+
+        let mut profiles = vec![LaunchProfile { id: "shell".to_string(), ..profile("a") }];
+        profiles.push(LaunchProfile { id: "shell".to_string(), ..profile("b") });
+        assert!(validate_launch_profiles(&profiles).is_err());
+    }
+
+    #[test]
+    fn test_upsert_assigns_ids_and_replaces() {
+        let mut profiles = Vec::new();
+        let first = upsert_profile(&mut profiles, LaunchProfile { name: "Dev Server".to_string(), ..Default::default() });
+        let second = upsert_profile(&mut profiles, LaunchProfile { name: "dev server!".to_string(), ..Default::default() });
+        assert_eq!((first.id.as_str(), second.id.as_str()), ("dev-server", "dev-server-2"));
+
+        upsert_profile(&mut profiles, LaunchProfile { notify: true, ..first });
+        assert_eq!(profiles.len(), 2);
+        assert!(profiles[0].notify);
+        assert!(validate_launch_profiles(&profiles).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_restore_waits_for_pending_launch() {
+        let session_manager = crate::sessions::test_support::test_session_manager();
+        let recorder = SessionRecorder::new(session_manager.clone(), SessionOutputHub::new(session_manager.clone()));
+        let launcher = ProfileLauncher::new(session_manager, recorder);
+
+        // The launch's own `Created` event reaches the restore before the launch records its session
+        let pending = PendingLaunch::new(&launcher.pending_launches);
+        let restoring = {
+            let launcher = launcher.clone();
+            tokio::spawn(async move { launcher.restore_launched("a").await })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!restoring.is_finished());
+
+        launcher.launched.lock().unwrap().insert("a".to_string(), "api".to_string());
+        drop(pending);
+        restoring.await.unwrap();
+        assert_eq!(launcher.launched_profile("a").as_deref(), Some("api"));
+    }
+}
@@ -0,0 +1,381 @@
+// Session reaper
+// Deletes exited sessions, warns about and kills idle ones and caps the session count, per configured policy
+
+use super::profiles::{profile_tag, ProfileLauncher};
+use super::{Session, SessionManager, SessionMonitor};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::time::{interval, Duration};
+use log::{error, info, warn};
+
+pub const REAPER_EVENT: &str = "session-reaper";
+const REAP_INTERVAL_SECS: u64 = 60;
+
+/// Every rule is off while unset
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReaperPolicy {
+    /// Delete exited sessions this long after they ended
+    pub delete_exited_after_mins: Option<u64>,
+    /// Warn once about running sessions without input or output for this long
+    pub idle_warning_mins: Option<u64>,
+    /// Kill running sessions without input or output for this long
+    pub idle_kill_mins: Option<u64>,
+    /// Beyond this many sessions, exited ones and then the longest idle are removed
+    pub max_sessions: Option<usize>,
+    /// Sessions in a session group with one of these tags are left alone
+    pub exempt_tags: Vec<String>,
+    /// Sessions launched from these profiles are left alone
+    pub exempt_profiles: Vec<String>,
+}
+
+impl ReaperPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        let minutes = [self.delete_exited_after_mins, self.idle_warning_mins, self.idle_kill_mins];
+        if minutes.contains(&Some(0)) || self.max_sessions == Some(0) {
+            return Err("Reaper thresholds must be at least 1".to_string());
+        }
+        if let (Some(warning), Some(kill)) = (self.idle_warning_mins, self.idle_kill_mins) {
+            if warning >= kill {
+                return Err("Idle sessions must be warned about before they are killed".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReapAction {
+    DeleteExited,
+    WarnIdle,
+    KillIdle,
+    /// Removed to get back under `max_sessions`
+    EnforceLimit,
+}
+
+impl ReapAction {
+    pub fn removes(self) -> bool {
+        self != ReapAction::WarnIdle
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReapCandidate {
+    pub session_id: String,
+    pub title: String,
+    pub action: ReapAction,
+    /// Minutes since the last input or output, or since the session exited
+    pub idle_mins: u64,
+    #[serde(skip)]
+    last_activity: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReapReport {
+    /// Nothing was deleted, killed or warned about
+    pub dry_run: bool,
+    pub candidates: Vec<ReapCandidate>,
+    /// Sessions that could not be removed, with the reason
+    pub failed: Vec<String>,
+}
+
+fn idle_minutes(session: &Session, now: DateTime<Utc>) -> Option<u64> {
+    let last_activity = DateTime::parse_from_rfc3339(&session.last_activity).ok()?;
+    Some((now - last_activity.with_timezone(&Utc)).num_minutes().max(0) as u64)
+}
+
+/// What `policy` does to `sessions` at `now`. Sessions without a readable activity time are never touched.
+pub fn plan_reaping(
+    policy: &ReaperPolicy,
+    sessions: &[Session],
+    now: DateTime<Utc>,
+    is_exempt: &dyn Fn(&Session) -> bool
+) -> Vec<ReapCandidate> {
+    let reached = |threshold: Option<u64>, idle: u64| threshold.is_some_and(|threshold| idle >= threshold);
+    let mut candidates = Vec::new();
+    let mut kept = Vec::new();
+
+    for session in sessions.iter().filter(|session| !is_exempt(session)) {
+        let Some(idle) = idle_minutes(session, now) else { continue };
+
+        let action = if session.status == "exited" {
+            reached(policy.delete_exited_after_mins, idle).then_some(ReapAction::DeleteExited)
+        } else if reached(policy.idle_kill_mins, idle) {
+            Some(ReapAction::KillIdle)
+        } else {
+            reached(policy.idle_warning_mins, idle).then_some(ReapAction::WarnIdle)
+        };
+
+        if !action.is_some_and(ReapAction::removes) {
+            kept.push((session, idle));
+        }
+        if let Some(action) = action {
+            candidates.push(ReapCandidate {
+                session_id: session.id.clone(),
+                title: session.title.clone(),
+                action,
+                idle_mins: idle,
+                last_activity: session.last_activity.clone(),
+            });
+        }
+    }
+
+    if let Some(max_sessions) = policy.max_sessions {
+        let removed = candidates.iter().filter(|candidate| candidate.action.removes()).count();
+        let surplus = sessions.len().saturating_sub(removed).saturating_sub(max_sessions);
+
+        kept.sort_by_key(|(session, idle)| (session.status != "exited", Reverse(*idle)));
+        for (session, idle) in kept.into_iter().take(surplus) {
+            candidates.retain(|candidate| candidate.session_id != session.id);
+            candidates.push(ReapCandidate {
+                session_id: session.id.clone(),
+                title: session.title.clone(),
+                action: ReapAction::EnforceLimit,
+                idle_mins: idle,
+                last_activity: session.last_activity.clone(),
+            });
+        }
+    }
+    candidates
+}
+
+/// Applies the reaper policy to the sessions `SessionMonitor` keeps, once a minute
+#[derive(Clone)]
+pub struct SessionReaper {
+    session_manager: SessionManager,
+    monitor: SessionMonitor,
+    launcher: ProfileLauncher,
+    policy: Arc<Mutex<ReaperPolicy>>,
+    // Idle sessions already warned about, with their activity time then
+    warned: Arc<Mutex<HashMap<String, String>>>,
+    running: Arc<AtomicBool>,
+}
+
+impl SessionReaper {
+    pub fn new(session_manager: SessionManager, monitor: SessionMonitor, launcher: ProfileLauncher) -> Self {
+        Self {
+            session_manager,
+            monitor,
+            launcher,
+            policy: Arc::new(Mutex::new(ReaperPolicy::default())),
+            warned: Arc::new(Mutex::new(HashMap::new())),
+            running: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn set_policy(&self, policy: ReaperPolicy) {
+        *self.policy.lock().unwrap() = policy;
+    }
+
+    pub fn policy(&self) -> ReaperPolicy {
+        self.policy.lock().unwrap().clone()
+    }
+
+    pub fn start(&self, app: AppHandle) {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let reaper = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut interval = interval(Duration::from_secs(REAP_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                let policy = reaper.policy();
+                let tagged = match reaper.tagged_sessions(&policy).await {
+                    Ok(tagged) => tagged,
+                    Err(e) => {
+                        warn!("Not reaping sessions while their tags are unknown: {}", e);
+                        continue;
+                    }
+                };
+                let sessions = reaper.monitor.get_cached_sessions();
+                let candidates = plan_reaping(&policy, &sessions, Utc::now(), &|session| reaper.is_exempt(&policy, &tagged, session));
+                reaper.forget_warnings(&sessions);
+
+                let report = reaper.apply(&app, candidates, false).await;
+                if report.candidates.is_empty() && report.failed.is_empty() {
+                    continue;
+                }
+                if let Err(e) = app.emit(REAPER_EVENT, &report) {
+                    error!("Failed to emit reaper event: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Sessions with one of the exempt tags or launched from an exempt profile, as the server reports them
+    async fn tagged_sessions(&self, policy: &ReaperPolicy) -> Result<HashSet<String>, String> {
+        let profile_tags = policy.exempt_profiles.iter().map(|profile_id| profile_tag(profile_id));
+        let mut tagged = HashSet::new();
+        for tag in policy.exempt_tags.iter().cloned().chain(profile_tags) {
+            tagged.extend(self.session_manager.fetch_tagged_session_ids(&tag).await?);
+        }
+        Ok(tagged)
+    }
+
+    // Launches whose tagging failed are still known to this app run
+    fn is_exempt(&self, policy: &ReaperPolicy, tagged: &HashSet<String>, session: &Session) -> bool {
+        tagged.contains(&session.id)
+            || self.launcher.launched_profile(&session.id).is_some_and(|profile_id| policy.exempt_profiles.contains(&profile_id))
+    }
+
+    // Warn again once a session has been active since
+    fn forget_warnings(&self, sessions: &[Session]) {
+        self.warned.lock().unwrap().retain(|session_id, last_activity| {
+            sessions.iter().any(|session| session.id == *session_id && session.last_activity == *last_activity)
+        });
+    }
+
+    async fn apply(&self, app: &AppHandle, candidates: Vec<ReapCandidate>, dry_run: bool) -> ReapReport {
+        let mut report = ReapReport { dry_run, ..Default::default() };
+
+        for candidate in candidates {
+            if dry_run {
+                report.candidates.push(candidate);
+                continue;
+            }
+
+            if candidate.action == ReapAction::WarnIdle {
+                let warned = self.warned.lock().unwrap()
+                    .insert(candidate.session_id.clone(), candidate.last_activity.clone());
+                if warned.as_ref() == Some(&candidate.last_activity) {
+                    continue;
+                }
+                if let Some(service) = app.try_state::<crate::notifications::NotificationService>() {
+                    let body = format!("{} has been idle for {} minutes", candidate.title, candidate.idle_mins);
+                    service.show_notification("Idle session", &body, "Session reaper", None).await;
+                }
+            } else if let Err(e) = self.session_manager.delete_session(&candidate.session_id).await {
+                warn!("Failed to reap session {}: {}", candidate.session_id, e);
+                report.failed.push(format!("{}: {}", candidate.title, e));
+                continue;
+            } else {
+                info!("Reaped session {} ({:?}, idle {} min)", candidate.session_id, candidate.action, candidate.idle_mins);
+            }
+            report.candidates.push(candidate);
+        }
+        report
+    }
+
+    /// Run `policy`, keeping the configured exemptions, on a fresh session list
+    async fn run(&self, app: &AppHandle, policy: ReaperPolicy, dry_run: bool) -> Result<ReapReport, String> {
+        let sessions = self.session_manager.fetch_sessions().await?;
+        let exemptions = self.policy();
+        let tagged = self.tagged_sessions(&exemptions).await?;
+        let candidates = plan_reaping(&policy, &sessions, Utc::now(), &|session| self.is_exempt(&exemptions, &tagged, session));
+        Ok(self.apply(app, candidates, dry_run).await)
+    }
+}
+
+// Tauri commands for the session reaper
+/// Apply the configured policy now, or with `dry_run` only report what it would do
+#[tauri::command]
+pub async fn run_session_reaper(app: AppHandle, reaper: State<'_, SessionReaper>, dry_run: bool) -> Result<ReapReport, String> {
+    reaper.run(&app, reaper.policy(), dry_run).await
+}
+
+/// Kill every running session idle for `idle_mins`, the configured kill or warning threshold by default
+#[tauri::command]
+pub async fn kill_idle_sessions(
+    app: AppHandle,
+    reaper: State<'_, SessionReaper>,
+    idle_mins: Option<u64>,
+    dry_run: bool
+) -> Result<ReapReport, String> {
+    let configured = reaper.policy();
+    let idle_mins = idle_mins.or(configured.idle_kill_mins).or(configured.idle_warning_mins)
+        .ok_or("No idle threshold given or configured")?;
+    let policy = ReaperPolicy { idle_kill_mins: Some(idle_mins), ..Default::default() };
+    reaper.run(&app, policy, dry_run).await
+}
+
+#[tauri::command]
+pub async fn delete_exited_sessions(app: AppHandle, reaper: State<'_, SessionReaper>, dry_run: bool) -> Result<ReapReport, String> {
+    let policy = ReaperPolicy { delete_exited_after_mins: Some(0), ..Default::default() };
+    reaper.run(&app, policy, dry_run).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, status: &str, idle_mins: i64) -> Session {
+        let last_activity = (Utc::now() - chrono::Duration::minutes(idle_mins)).to_rfc3339();
+        Session {
+            id: id.to_string(),
+            title: id.to_string(),
+            created_at: last_activity.clone(),
+            last_activity,
+            status: status.to_string(),
+            pid: None,
+            command: None,
+            working_directory: None,
+            cols: 80,
+            rows: 24,
+            active: status != "exited",
+            clients: 0,
+        }
+    }
+
+    fn actions(candidates: &[ReapCandidate]) -> Vec<(&str, ReapAction)> {
+        candidates.iter().map(|candidate| (candidate.session_id.as_str(), candidate.action)).collect()
+    }
+
+    #[test]
+    fn test_plans_exited_and_idle_sessions() {
+        let policy = ReaperPolicy {
+            delete_exited_after_mins: Some(30),
+            idle_warning_mins: Some(60),
+            idle_kill_mins: Some(240),
+            ..Default::default()
+        };
+        let sessions = vec![
+            session("old-exit", "exited", 45),
+            session("new-exit", "exited", 5),
+            session("quiet", "running", 90),
+            session("abandoned", "running", 300),
+            session("busy", "running", 1),
+            session("pinned", "running", 300),
+        ];
+
+        let candidates = plan_reaping(&policy, &sessions, Utc::now(), &|session| session.id == "pinned");
+        assert_eq!(actions(&candidates), vec![
+            ("old-exit", ReapAction::DeleteExited),
+            ("quiet", ReapAction::WarnIdle),
+            ("abandoned", ReapAction::KillIdle),
+        ]);
+    }
+
+    #[test]
+    fn test_limit_removes_exited_then_longest_idle() {
+        let policy = ReaperPolicy { max_sessions: Some(2), idle_warning_mins: Some(60), ..Default::default() };
+        let sessions = vec![
+            session("a", "running", 10),
+            session("b", "exited", 1),
+            session("c", "running", 120),
+            session("d", "running", 2),
+            session("e", "running", 500),
+        ];
+
+        let candidates = plan_reaping(&policy, &sessions, Utc::now(), &|session| session.id == "e");
+        assert_eq!(actions(&candidates), vec![
+            ("b", ReapAction::EnforceLimit),
+            ("c", ReapAction::EnforceLimit),
+            ("a", ReapAction::EnforceLimit),
+        ]);
+    }
+
+    #[test]
+    fn test_validates_thresholds() {
+        assert!(ReaperPolicy::default().validate().is_ok());
+        assert!(ReaperPolicy { idle_warning_mins: Some(60), idle_kill_mins: Some(30), ..Default::default() }.validate().is_err());
+        assert!(ReaperPolicy { max_sessions: Some(0), ..Default::default() }.validate().is_err());
+    }
+}
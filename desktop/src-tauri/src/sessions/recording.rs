@@ -0,0 +1,566 @@
+// Session recording
+// Writes session output to asciicast v2 files, rotating large files and capping the total size
+
+use super::stream::{cast_event_kind, OutputKind, SessionOutput, SessionOutputHub};
+use super::SessionManager;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::State;
+use tokio::sync::broadcast;
+use log::{error, info, warn};
+
+/// Folder in the app data dir used when no directory is configured
+pub const RECORDINGS_DIR: &str = "recordings";
+const CAST_EXTENSION: &str = "cast";
+const DEFAULT_COLS: u16 = 80;
+const DEFAULT_ROWS: u16 = 24;
+const MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingOptions {
+    /// Record every session whose output the app follows
+    pub always_record: bool,
+    /// A recording continues in a new part file once its current file reaches this size
+    pub max_file_mb: u64,
+    /// The oldest recordings are deleted once all of them together exceed this
+    pub max_total_mb: u64,
+    /// Where recordings are written; `recordings` in the app data dir when unset
+    pub directory: Option<PathBuf>,
+}
+
+impl Default for RecordingOptions {
+    fn default() -> Self {
+        Self {
+            always_record: false,
+            max_file_mb: 50,
+            max_total_mb: 1024,
+            directory: None,
+        }
+    }
+}
+
+/// First line of a `.cast` file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CastHeader {
+    pub version: u32,
+    pub width: u16,
+    pub height: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// A parsed recording: output and resize events with their time in seconds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cast {
+    pub header: CastHeader,
+    pub events: Vec<(f64, OutputKind)>,
+}
+
+impl Cast {
+    pub fn duration_secs(&self) -> f64 {
+        self.events.last().map_or(0.0, |(time, _)| *time)
+    }
+}
+
+/// Parse asciicast v2; input, marker and unknown events are skipped
+pub fn parse_cast(text: &str) -> Result<Cast, String> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let header: CastHeader = serde_json::from_str(lines.next().ok_or("Recording is empty")?)
+        .map_err(|e| format!("Invalid recording header: {}", e))?;
+    if header.version != 2 {
+        return Err(format!("Unsupported asciicast version {}", header.version));
+    }
+
+    let mut events = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let value: serde_json::Value = serde_json::from_str(line)
+            .map_err(|e| format!("Invalid recording event on line {}: {}", index + 2, e))?;
+        let Some(time) = value.get(0).and_then(|time| time.as_f64()) else { continue };
+        if let Some(kind @ (OutputKind::Output(_) | OutputKind::Resize { .. })) = cast_event_kind(&value) {
+            events.push((time, kind));
+        }
+    }
+
+    Ok(Cast { header, events })
+}
+
+/// One event line, or `None` for what asciicast v2 cannot express
+fn cast_event_line(time_secs: f64, kind: &OutputKind) -> Option<String> {
+    // Microseconds are as precise as asciinema itself writes
+    let time = (time_secs * 1_000_000.0).round() / 1_000_000.0;
+    let line = match kind {
+        OutputKind::Output(data) => serde_json::json!([time, "o", data]),
+        OutputKind::Resize { cols, rows } => serde_json::json!([time, "r", format!("{}x{}", cols, rows)]),
+        OutputKind::Start { .. } | OutputKind::Exit { .. } => return None,
+    };
+    Some(format!("{}\n", line))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecordingInfo {
+    pub file_name: String,
+    pub session_id: Option<String>,
+    pub size_bytes: u64,
+    /// RFC 3339
+    pub modified: Option<String>,
+    /// Still being written
+    pub active: bool,
+}
+
+struct ActiveRecording {
+    file: File,
+    path: PathBuf,
+    /// File name without the part suffix and extension
+    base_name: String,
+    part: u32,
+    /// Event times count from here; every part starts at zero so it plays on its own
+    started_at: Instant,
+    bytes: u64,
+    cols: u16,
+    rows: u16,
+    title: Option<String>,
+}
+
+impl ActiveRecording {
+    fn create(dir: &Path, base_name: String, part: u32, cols: u16, rows: u16, title: Option<String>) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+
+        let file_name = match part {
+            1 => format!("{}.{}", base_name, CAST_EXTENSION),
+            part => format!("{}_part{}.{}", base_name, part, CAST_EXTENSION),
+        };
+        let path = dir.join(file_name);
+        let mut file = OpenOptions::new().write(true).create_new(true).open(&path)
+            .map_err(|e| format!("Failed to create recording {}: {}", path.display(), e))?;
+
+        let header = CastHeader {
+            version: 2,
+            width: cols,
+            height: rows,
+            timestamp: Some(chrono::Utc::now().timestamp()),
+            title: title.clone(),
+        };
+        let header = format!("{}\n", serde_json::to_string(&header).map_err(|e| e.to_string())?);
+        file.write_all(header.as_bytes()).map_err(|e| format!("Failed to write recording header: {}", e))?;
+
+        Ok(Self {
+            file,
+            path,
+            base_name,
+            part,
+            started_at: Instant::now(),
+            bytes: header.len() as u64,
+            cols,
+            rows,
+            title,
+        })
+    }
+
+    fn write(&mut self, line: &str) -> Result<(), String> {
+        self.file.write_all(line.as_bytes()).map_err(|e| format!("Failed to write recording: {}", e))?;
+        self.bytes += line.len() as u64;
+        Ok(())
+    }
+}
+
+fn cast_files(dir: &Path) -> Vec<(PathBuf, fs::Metadata)> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    entries.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == CAST_EXTENSION))
+        .filter_map(|path| Some((path.clone(), fs::metadata(&path).ok()?)))
+        .filter(|(_, metadata)| metadata.is_file())
+        .collect()
+}
+
+/// Delete the oldest finished recordings until the rest fit in `max_total_bytes`
+fn prune_recordings(dir: &Path, max_total_bytes: u64, active: &[PathBuf]) {
+    let mut files = cast_files(dir);
+    let mut total: u64 = files.iter().map(|(_, metadata)| metadata.len()).sum();
+    files.sort_by_key(|(_, metadata)| metadata.modified().ok());
+
+    for (path, metadata) in files {
+        if total <= max_total_bytes {
+            break;
+        }
+        if active.contains(&path) {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                info!("Deleted old recording {}", path.display());
+                total -= metadata.len();
+            }
+            Err(e) => warn!("Failed to delete old recording {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Records session output from the `SessionOutputHub`, on request or for every session
+#[derive(Clone)]
+pub struct SessionRecorder {
+    session_manager: SessionManager,
+    hub: SessionOutputHub,
+    options: Arc<Mutex<RecordingOptions>>,
+    default_directory: Arc<Mutex<Option<PathBuf>>>,
+    /// Each recording has its own lock, so writing one file never holds up the others
+    active: Arc<Mutex<HashMap<String, Arc<Mutex<ActiveRecording>>>>>,
+    /// Sessions whose recording the user stopped; `always_record` leaves them alone until they exit
+    stopped: Arc<Mutex<HashSet<String>>>,
+}
+
+impl SessionRecorder {
+    pub fn new(session_manager: SessionManager, hub: SessionOutputHub) -> Self {
+        Self {
+            session_manager,
+            hub,
+            options: Arc::new(Mutex::new(RecordingOptions::default())),
+            default_directory: Arc::new(Mutex::new(None)),
+            active: Arc::new(Mutex::new(HashMap::new())),
+            stopped: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    pub fn set_options(&self, options: RecordingOptions) {
+        *self.options.lock().unwrap() = options;
+    }
+
+    pub fn set_default_directory(&self, directory: Option<PathBuf>) {
+        *self.default_directory.lock().unwrap() = directory;
+    }
+
+    pub fn directory(&self) -> Result<PathBuf, String> {
+        self.options.lock().unwrap().directory.clone()
+            .or_else(|| self.default_directory.lock().unwrap().clone())
+            .ok_or_else(|| "No recordings directory configured".to_string())
+    }
+
+    /// Write output events until the hub's channel closes
+    pub fn start(&self, mut receiver: broadcast::Receiver<SessionOutput>) {
+        let recorder = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    // File writes, rotation and pruning stay off the async workers
+                    Ok(output) => {
+                        let recorder = recorder.clone();
+                        let _ = tauri::async_runtime::spawn_blocking(move || recorder.apply(&output)).await;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Dropped {} output events; recordings have gaps", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    pub fn start_recording(&self, session_id: &str) -> Result<RecordingInfo, String> {
+        let session = self.session_manager.get_local_sessions().into_iter().find(|session| session.id == session_id);
+        let (cols, rows) = match &session {
+            Some(session) if session.cols > 0 && session.rows > 0 => (session.cols, session.rows),
+            _ => (DEFAULT_COLS, DEFAULT_ROWS),
+        };
+        let title = session.map(|session| session.title).filter(|title| !title.is_empty());
+
+        let info = self.begin(session_id, cols, rows, title)?;
+        self.stopped.lock().unwrap().remove(session_id);
+        // Output only arrives for followed sessions; always-on tracking already covers running ones
+        self.hub.follow(session_id);
+        Ok(info)
+    }
+
+    pub fn stop_recording(&self, session_id: &str) -> Result<RecordingInfo, String> {
+        let recording = self.active.lock().unwrap().remove(session_id)
+            .ok_or_else(|| format!("Session {} is not being recorded", session_id))?;
+        self.stopped.lock().unwrap().insert(session_id.to_string());
+
+        let path = recording.lock().unwrap().path.clone();
+        info!("Stopped recording session {} ({})", session_id, path.display());
+        Ok(recording_info(&path, false))
+    }
+
+    pub fn is_recording(&self, session_id: &str) -> bool {
+        self.active.lock().unwrap().contains_key(session_id)
+    }
+
+    /// All recordings in the directory, newest first
+    pub fn list_recordings(&self) -> Result<Vec<RecordingInfo>, String> {
+        let active = self.active_paths();
+        let mut files = cast_files(&self.directory()?);
+        files.sort_by_key(|(_, metadata)| std::cmp::Reverse(metadata.modified().ok()));
+
+        Ok(files.iter().map(|(path, _)| recording_info(path, active.contains(path))).collect())
+    }
+
+    /// Path of a recording by file name; names that could leave the directory are refused
+    pub fn recording_path(&self, file_name: &str) -> Result<PathBuf, String> {
+        let valid = Path::new(file_name).file_name().is_some_and(|name| name.to_str() == Some(file_name))
+            && Path::new(file_name).extension().is_some_and(|ext| ext == CAST_EXTENSION);
+        if !valid {
+            return Err(format!("Invalid recording name: {}", file_name));
+        }
+
+        let path = self.directory()?.join(file_name);
+        if !path.is_file() {
+            return Err(format!("Recording {} not found", file_name));
+        }
+        Ok(path)
+    }
+
+    pub fn apply(&self, output: &SessionOutput) {
+        let session_id = &output.session_id;
+
+        if let OutputKind::Exit { .. } = output.kind {
+            self.stopped.lock().unwrap().remove(session_id);
+        }
+
+        if let OutputKind::Start { cols, rows } = output.kind {
+            // Reconnects repeat `Start`, which must not undo a stop
+            let stopped = self.stopped.lock().unwrap().contains(session_id);
+            if !self.is_recording(session_id) && !stopped && self.options.lock().unwrap().always_record {
+                if let Err(e) = self.begin(session_id, cols, rows, None) {
+                    error!("Failed to start recording session {}: {}", session_id, e);
+                }
+                return;
+            }
+        }
+
+        // Only the map lookup holds the shared lock; the file work below holds this recording's
+        let Some(recording) = self.active.lock().unwrap().get(session_id).cloned() else { return };
+        let mut recording = recording.lock().unwrap();
+
+        let kind = match &output.kind {
+            OutputKind::Exit { .. } => {
+                info!("Session {} ended, recording saved to {}", session_id, recording.path.display());
+                drop(recording);
+                self.active.lock().unwrap().remove(session_id);
+                return;
+            }
+            // A reconnected stream repeats the size; only a real change is worth an event
+            OutputKind::Start { cols, rows } if (*cols, *rows) == (recording.cols, recording.rows) => return,
+            OutputKind::Start { cols, rows } => OutputKind::Resize { cols: *cols, rows: *rows },
+            kind => kind.clone(),
+        };
+
+        let max_file_bytes = self.options.lock().unwrap().max_file_mb.max(1) * MB;
+        let written = self.write_event(&mut recording, output.received_at, &kind, max_file_bytes);
+        // Released before pruning, which reads the path of every recording including this one
+        drop(recording);
+        match written {
+            Ok(false) => {}
+            Ok(true) => self.prune(&self.active_paths()),
+            Err(e) => {
+                error!("Recording of session {} stopped: {}", session_id, e);
+                self.active.lock().unwrap().remove(session_id);
+            }
+        }
+    }
+
+    /// Append one event, first moving on to a new part when it would not fit; returns whether it did
+    fn write_event(&self, recording: &mut ActiveRecording, at: Instant, kind: &OutputKind, max_file_bytes: u64) -> Result<bool, String> {
+        let time = at.saturating_duration_since(recording.started_at).as_secs_f64();
+        let Some(line) = cast_event_line(time, kind) else { return Ok(false) };
+
+        if recording.bytes + line.len() as u64 > max_file_bytes {
+            let next = ActiveRecording::create(
+                &self.directory()?,
+                recording.base_name.clone(),
+                recording.part + 1,
+                recording.cols,
+                recording.rows,
+                recording.title.clone(),
+            )?;
+            info!("Recording continues in {}", next.path.display());
+            *recording = next;
+            self.write_event(recording, at.max(recording.started_at), kind, u64::MAX)?;
+            return Ok(true);
+        }
+
+        recording.write(&line)?;
+        if let OutputKind::Resize { cols, rows } = *kind {
+            (recording.cols, recording.rows) = (cols, rows);
+        }
+        Ok(false)
+    }
+
+    fn begin(&self, session_id: &str, cols: u16, rows: u16, title: Option<String>) -> Result<RecordingInfo, String> {
+        let dir = self.directory()?;
+        let already_recording = || format!("Session {} is already being recorded", session_id);
+        if self.is_recording(session_id) {
+            return Err(already_recording());
+        }
+
+        // The file is created without the shared lock; a recording started meanwhile wins
+        let base_name = format!("{}_{}", session_id, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+        let recording = ActiveRecording::create(&dir, base_name, 1, cols, rows, title)?;
+        let path = recording.path.clone();
+        match self.active.lock().unwrap().entry(session_id.to_string()) {
+            Entry::Occupied(_) => {
+                let _ = fs::remove_file(&path);
+                return Err(already_recording());
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(Arc::new(Mutex::new(recording)));
+            }
+        }
+
+        info!("Recording session {} to {}", session_id, path.display());
+        self.prune(&self.active_paths());
+        Ok(recording_info(&path, true))
+    }
+
+    fn active_paths(&self) -> Vec<PathBuf> {
+        let recordings: Vec<_> = self.active.lock().unwrap().values().cloned().collect();
+        recordings.iter().map(|recording| recording.lock().unwrap().path.clone()).collect()
+    }
+
+    fn prune(&self, active: &[PathBuf]) {
+        let max_total_bytes = self.options.lock().unwrap().max_total_mb.max(1) * MB;
+        if let Ok(dir) = self.directory() {
+            prune_recordings(&dir, max_total_bytes, active);
+        }
+    }
+}
+
+fn recording_info(path: &Path, active: bool) -> RecordingInfo {
+    let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let metadata = fs::metadata(path).ok();
+
+    RecordingInfo {
+        session_id: file_name.split_once('_').map(|(session_id, _)| session_id.to_string()),
+        file_name,
+        size_bytes: metadata.as_ref().map_or(0, |metadata| metadata.len()),
+        modified: metadata
+            .and_then(|metadata| metadata.modified().ok())
+            .map(|modified| chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339()),
+        active,
+    }
+}
+
+// Tauri commands for session recording
+#[tauri::command]
+pub async fn start_session_recording(recorder: State<'_, SessionRecorder>, session_id: String) -> Result<RecordingInfo, String> {
+    recorder.start_recording(&session_id)
+}
+
+#[tauri::command]
+pub async fn stop_session_recording(recorder: State<'_, SessionRecorder>, session_id: String) -> Result<RecordingInfo, String> {
+    recorder.stop_recording(&session_id)
+}
+
+#[tauri::command]
+pub async fn is_session_recording(recorder: State<'_, SessionRecorder>, session_id: String) -> Result<bool, String> {
+    Ok(recorder.is_recording(&session_id))
+}
+
+#[tauri::command]
+pub async fn list_session_recordings(recorder: State<'_, SessionRecorder>) -> Result<Vec<RecordingInfo>, String> {
+    recorder.list_recordings()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn recorder(dir: &Path, options: RecordingOptions) -> SessionRecorder {
+        let session_manager = crate::sessions::test_support::test_session_manager();
+        let recorder = SessionRecorder::new(session_manager.clone(), SessionOutputHub::new(session_manager));
+        recorder.set_default_directory(Some(dir.to_path_buf()));
+        recorder.set_options(options);
+        recorder
+    }
+
+    fn output(kind: OutputKind, at: Instant) -> SessionOutput {
+        SessionOutput { session_id: "a1".to_string(), kind, received_at: at }
+    }
+
+    #[test]
+    fn test_always_record_writes_playable_cast() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = recorder(dir.path(), RecordingOptions { always_record: true, ..Default::default() });
+        let start = Instant::now();
+
+        recorder.apply(&output(OutputKind::Start { cols: 100, rows: 30 }, start));
+        recorder.apply(&output(OutputKind::Start { cols: 100, rows: 30 }, start));
+        recorder.apply(&output(OutputKind::Output("$ ls\r\n".to_string()), start + Duration::from_millis(500)));
+        recorder.apply(&output(OutputKind::Resize { cols: 80, rows: 24 }, start + Duration::from_secs(1)));
+        recorder.apply(&output(OutputKind::Exit { exit_code: Some(0) }, start + Duration::from_secs(2)));
+        assert!(!recorder.is_recording("a1"));
+
+        let recordings = recorder.list_recordings().unwrap();
+        assert_eq!(recordings.len(), 1);
+        assert_eq!(recordings[0].session_id.as_deref(), Some("a1"));
+
+        let path = recorder.recording_path(&recordings[0].file_name).unwrap();
+        let cast = parse_cast(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!((cast.header.width, cast.header.height), (100, 30));
+        assert_eq!(cast.events.len(), 2);
+        assert_eq!(cast.events[0].1, OutputKind::Output("$ ls\r\n".to_string()));
+        assert_eq!(cast.events[1].1, OutputKind::Resize { cols: 80, rows: 24 });
+        assert!(cast.events[0].0 > 0.4 && cast.events[1].0 > cast.events[0].0);
+    }
+
+    #[test]
+    fn test_always_record_respects_explicit_stop() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = recorder(dir.path(), RecordingOptions { always_record: true, ..Default::default() });
+        let start = Instant::now();
+
+        recorder.apply(&output(OutputKind::Start { cols: 80, rows: 24 }, start));
+        recorder.stop_recording("a1").unwrap();
+        // The output stream reconnects
+        recorder.apply(&output(OutputKind::Start { cols: 80, rows: 24 }, start));
+        assert!(!recorder.is_recording("a1"));
+
+        // The stop only lasts for the session's lifetime
+        recorder.apply(&output(OutputKind::Exit { exit_code: Some(0) }, start));
+        assert!(recorder.stopped.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rotates_files_and_prunes_oldest() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("old_20240101-000000.cast");
+        fs::write(&old, vec![b'x'; (MB + MB / 2) as usize]).unwrap();
+        File::options().write(true).open(&old).unwrap()
+            .set_modified(std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(86_400)).unwrap();
+
+        let recorder = recorder(dir.path(), RecordingOptions { max_file_mb: 1, max_total_mb: 2, ..Default::default() });
+        // `begin` rather than `start_recording`, which would also follow the session on the server
+        recorder.begin("a1", 80, 24, None).unwrap();
+        let chunk = "y".repeat(256 * 1024);
+        for _ in 0..6 {
+            recorder.apply(&output(OutputKind::Output(chunk.clone()), Instant::now()));
+        }
+
+        let names: Vec<String> = recorder.list_recordings().unwrap().into_iter().map(|info| info.file_name).collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.iter().any(|name| name.ends_with("_part2.cast")));
+        assert!(!old.exists());
+        assert!(cast_files(dir.path()).iter().all(|(_, metadata)| metadata.len() <= MB));
+    }
+
+    #[test]
+    fn test_recording_paths_stay_in_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = recorder(dir.path(), RecordingOptions::default());
+        fs::write(dir.path().join("a1_x.cast"), "{\"version\":2,\"width\":80,\"height\":24}\n").unwrap();
+
+        assert!(recorder.recording_path("a1_x.cast").is_ok());
+        assert!(recorder.recording_path("../config.json").is_err());
+        assert!(recorder.recording_path("/etc/passwd.cast").is_err());
+        assert!(recorder.recording_path("missing.cast").is_err());
+        assert!(parse_cast("{\"version\":1,\"width\":80,\"height\":24}").is_err());
+    }
+}
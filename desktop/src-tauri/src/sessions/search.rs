@@ -0,0 +1,630 @@
+// Session output search
+// Indexes the text lines each session prints for substring and regex search
+
+use super::stream::{OutputKind, SessionOutput};
+use super::{SessionEvent, SessionEventKind, SessionManager};
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+use tokio::sync::broadcast;
+use vte::{Params, Parser, Perform};
+use log::{debug, warn};
+
+pub const DEFAULT_CONTEXT_LINES: usize = 2;
+const MAX_CONTEXT_LINES: usize = 20;
+pub const DEFAULT_MAX_RESULTS: usize = 200;
+const MAX_RESULTS: usize = 5000;
+/// Rough per-line cost of the index beyond the text itself
+const LINE_OVERHEAD_BYTES: usize = 64;
+/// Lines wrap here like at a terminal's last column, so output without newlines can't grow one line forever
+const MAX_LINE_CHARS: usize = 1024;
+const MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchIndexOptions {
+    /// Older lines of a session are dropped beyond this
+    pub max_lines_per_session: usize,
+    /// Memory cap for the whole index; exited sessions go first, then the oldest lines of the largest session
+    pub max_total_mb: u64,
+    /// How long an exited session stays searchable
+    pub exited_retention_mins: u64,
+    pub max_exited_sessions: usize,
+}
+
+impl Default for SearchIndexOptions {
+    fn default() -> Self {
+        Self {
+            max_lines_per_session: 10_000,
+            max_total_mb: 64,
+            exited_retention_mins: 30,
+            max_exited_sessions: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SearchQuery {
+    pub pattern: String,
+    /// Treat `pattern` as a regular expression instead of plain text
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// Search only these sessions; all indexed sessions when unset
+    pub session_ids: Option<Vec<String>>,
+    pub context_lines: Option<usize>,
+    pub max_results: Option<usize>,
+}
+
+impl SearchQuery {
+    fn compile(&self) -> Result<Regex, String> {
+        if self.pattern.is_empty() {
+            return Err("Search pattern is empty".to_string());
+        }
+
+        let pattern = if self.regex { self.pattern.clone() } else { regex::escape(&self.pattern) };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .map_err(|e| format!("Invalid search pattern: {}", e))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchMatch {
+    pub session_id: String,
+    pub session_title: String,
+    pub session_exited: bool,
+    /// Counted from the first line the session printed, so it stays stable as old lines are dropped
+    pub line_number: u64,
+    pub line: String,
+    /// Character offsets of the first match in `line`
+    pub match_start: usize,
+    pub match_end: usize,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+    /// When the line was printed, RFC 3339
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchResults {
+    /// Newest first
+    pub matches: Vec<SearchMatch>,
+    /// More lines matched than `max_results`
+    pub truncated: bool,
+    pub sessions_searched: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexedSession {
+    pub session_id: String,
+    pub title: String,
+    pub lines: usize,
+    pub bytes: usize,
+    /// RFC 3339
+    pub exited_at: Option<String>,
+}
+
+/// Turns terminal output into text lines: overwrites from `\r` and backspace are applied,
+/// escape sequences dropped, and full-screen (alternate screen) output skipped
+#[derive(Default)]
+pub(super) struct LineCollector {
+    line: Vec<char>,
+    column: usize,
+    alternate_screen: bool,
+    completed: Vec<String>,
+}
+
+impl LineCollector {
+    fn put(&mut self, ch: char) {
+        if self.column >= MAX_LINE_CHARS {
+            self.finish_line();
+        }
+        if self.column < self.line.len() {
+            self.line[self.column] = ch;
+        } else {
+            self.line.resize(self.column, ' ');
+            self.line.push(ch);
+        }
+        self.column += 1;
+    }
+
+    fn finish_line(&mut self) {
+        let text: String = self.line.drain(..).collect();
+        self.completed.push(text.trim_end().to_string());
+        self.column = 0;
+    }
+
+    /// Cursor addressing to another row ends the line without an empty one for every move
+    fn break_line(&mut self) {
+        if self.line.is_empty() {
+            self.column = 0;
+        } else {
+            self.finish_line();
+        }
+    }
+
+    /// The unfinished line
+    pub(super) fn text(&self) -> String {
+        self.line.iter().collect::<String>().trim_end().to_string()
+    }
+
+    /// Lines finished since the last call
+    pub(super) fn take_completed(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.completed)
+    }
+}
+
+impl Perform for LineCollector {
+    fn print(&mut self, ch: char) {
+        if !self.alternate_screen {
+            self.put(ch);
+        }
+    }
+
+    fn execute(&mut self, byte: u8) {
+        if self.alternate_screen {
+            return;
+        }
+        match byte {
+            0x08 => self.column = self.column.saturating_sub(1),
+            0x09 => {
+                let stop = ((self.column / 8 + 1) * 8).min(MAX_LINE_CHARS);
+                while self.column < stop {
+                    self.put(' ');
+                }
+            }
+            0x0A..=0x0C => self.finish_line(),
+            0x0D => self.column = 0,
+            _ => {}
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        if ignore {
+            return;
+        }
+
+        let values: Vec<u16> = params.iter().map(|param| param[0]).collect();
+        let first = values.first().copied().unwrap_or(0) as usize;
+
+        match (intermediates, action) {
+            ([b'?'], 'h' | 'l') if values.iter().any(|mode| matches!(mode, 47 | 1047 | 1049)) => {
+                if action == 'h' {
+                    self.break_line();
+                }
+                self.alternate_screen = action == 'h';
+            }
+            _ if self.alternate_screen => {}
+            ([], 'C') => self.column = (self.column + first.max(1)).min(MAX_LINE_CHARS),
+            ([], 'D') => self.column = self.column.saturating_sub(first.max(1)),
+            ([], 'G') => self.column = first.saturating_sub(1).min(MAX_LINE_CHARS),
+            ([], 'K') => match first {
+                0 => self.line.truncate(self.column),
+                2 => self.line.clear(),
+                _ => self.line.iter_mut().take(self.column + 1).for_each(|ch| *ch = ' '),
+            },
+            ([], 'A' | 'B' | 'E' | 'F' | 'H' | 'd' | 'f') => self.break_line(),
+            _ => {}
+        }
+    }
+}
+
+struct IndexedLine {
+    number: u64,
+    text: String,
+    timestamp: DateTime<Utc>,
+}
+
+struct SessionIndex {
+    title: String,
+    lines: VecDeque<IndexedLine>,
+    next_number: u64,
+    parser: Parser,
+    collector: LineCollector,
+    /// When the unfinished line got its first output
+    line_started: Option<DateTime<Utc>>,
+    /// Completed lines plus the unfinished one
+    bytes: usize,
+    partial_bytes: usize,
+    exited_at: Option<DateTime<Utc>>,
+}
+
+impl SessionIndex {
+    fn new(title: String) -> Self {
+        Self {
+            title,
+            lines: VecDeque::new(),
+            next_number: 1,
+            parser: Parser::new(),
+            collector: LineCollector::default(),
+            line_started: None,
+            bytes: 0,
+            partial_bytes: 0,
+            exited_at: None,
+        }
+    }
+
+    fn process(&mut self, data: &[u8], now: DateTime<Utc>, max_lines: usize) {
+        for &byte in data {
+            self.parser.advance(&mut self.collector, byte);
+        }
+
+        for text in self.collector.take_completed() {
+            let timestamp = self.line_started.take().unwrap_or(now);
+            self.bytes += text.len() + LINE_OVERHEAD_BYTES;
+            self.lines.push_back(IndexedLine { number: self.next_number, text, timestamp });
+            self.next_number += 1;
+        }
+        if !self.collector.line.is_empty() && self.line_started.is_none() {
+            self.line_started = Some(now);
+        }
+        let partial_bytes = self.collector.line.len() * std::mem::size_of::<char>();
+        self.bytes = self.bytes - self.partial_bytes + partial_bytes;
+        self.partial_bytes = partial_bytes;
+
+        while self.lines.len() > max_lines.max(1) {
+            self.drop_oldest_line();
+        }
+    }
+
+    fn finish(&mut self, now: DateTime<Utc>) {
+        if !self.collector.line.is_empty() {
+            self.collector.finish_line();
+            self.process(&[], now, usize::MAX);
+        }
+        self.exited_at.get_or_insert(now);
+    }
+
+    fn drop_oldest_line(&mut self) -> bool {
+        match self.lines.pop_front() {
+            Some(line) => {
+                self.bytes -= line.text.len() + LINE_OVERHEAD_BYTES;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Completed lines followed by the unfinished one, as `(number, text, timestamp)`
+    fn snapshot(&self) -> Vec<(u64, String, DateTime<Utc>)> {
+        let mut lines: Vec<_> = self.lines.iter().map(|line| (line.number, line.text.clone(), line.timestamp)).collect();
+        let partial = self.collector.text();
+        if !partial.is_empty() {
+            lines.push((self.next_number, partial, self.line_started.unwrap_or_else(Utc::now)));
+        }
+        lines
+    }
+}
+
+/// Drop what the options no longer allow: expired and surplus exited sessions, then lines until
+/// the index fits its memory cap
+fn evict(index: &mut HashMap<String, SessionIndex>, options: &SearchIndexOptions, now: DateTime<Utc>) {
+    let retention = chrono::Duration::minutes(options.exited_retention_mins as i64);
+    index.retain(|_, session| session.exited_at.is_none_or(|exited_at| now - exited_at < retention));
+
+    let mut exited: Vec<(DateTime<Utc>, String)> = index.iter()
+        .filter_map(|(id, session)| session.exited_at.map(|exited_at| (exited_at, id.clone())))
+        .collect();
+    exited.sort();
+
+    let max_bytes = (options.max_total_mb.max(1) * MB) as usize;
+    let mut total: usize = index.values().map(|session| session.bytes).sum();
+    let surplus = exited.len().saturating_sub(options.max_exited_sessions);
+    for (position, (_, id)) in exited.iter().enumerate() {
+        if position >= surplus && total <= max_bytes {
+            break;
+        }
+        if let Some(session) = index.remove(id) {
+            debug!("Dropped search index of exited session {}", id);
+            total -= session.bytes;
+        }
+    }
+
+    while total > max_bytes {
+        let Some(largest) = index.values_mut().max_by_key(|session| session.bytes) else { break };
+        let before = largest.bytes;
+        if !largest.drop_oldest_line() {
+            break;
+        }
+        total -= before - largest.bytes;
+    }
+}
+
+/// Text of every followed session, fed by the `SessionOutputHub` and pruned on session lifecycle events
+#[derive(Clone)]
+pub struct SessionSearch {
+    session_manager: SessionManager,
+    options: Arc<Mutex<SearchIndexOptions>>,
+    index: Arc<Mutex<HashMap<String, SessionIndex>>>,
+}
+
+impl SessionSearch {
+    pub fn new(session_manager: SessionManager) -> Self {
+        Self {
+            session_manager,
+            options: Arc::new(Mutex::new(SearchIndexOptions::default())),
+            index: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn set_options(&self, options: SearchIndexOptions) {
+        let mut index = self.index.lock().unwrap();
+        for session in index.values_mut() {
+            while session.lines.len() > options.max_lines_per_session.max(1) {
+                session.drop_oldest_line();
+            }
+        }
+        evict(&mut index, &options, Utc::now());
+        *self.options.lock().unwrap() = options;
+    }
+
+    /// Index session output and follow the sessions' lifecycle until the channels close
+    pub fn start(&self, mut outputs: broadcast::Receiver<SessionOutput>) {
+        let search = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                match outputs.recv().await {
+                    Ok(output) => search.apply(&output),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Dropped {} output events; search results may miss lines", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        let search = self.clone();
+        let mut events = self.session_manager.subscribe_to_events();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => search.handle_event(&event),
+                    Err(broadcast::error::RecvError::Lagged(_)) => search.reconcile(),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    pub fn apply(&self, output: &SessionOutput) {
+        let options = self.options.lock().unwrap().clone();
+        let now = Utc::now();
+        let mut index = self.index.lock().unwrap();
+
+        match &output.kind {
+            OutputKind::Output(data) => {
+                index.entry(output.session_id.clone())
+                    .or_insert_with(|| SessionIndex::new(self.session_title(&output.session_id)))
+                    .process(data.as_bytes(), now, options.max_lines_per_session);
+            }
+            OutputKind::Exit { .. } => {
+                if let Some(session) = index.get_mut(&output.session_id) {
+                    session.finish(now);
+                }
+            }
+            OutputKind::Start { .. } | OutputKind::Resize { .. } => return,
+        }
+
+        evict(&mut index, &options, now);
+    }
+
+    /// Keep titles current, and start the retention clock of sessions that exit or disappear
+    pub fn handle_event(&self, event: &SessionEvent) {
+        let options = self.options.lock().unwrap().clone();
+        let now = Utc::now();
+        let mut index = self.index.lock().unwrap();
+
+        match &event.kind {
+            SessionEventKind::Created(session) | SessionEventKind::Updated { session, .. } => {
+                if let Some(indexed) = index.get_mut(&session.id) {
+                    indexed.title = session.title.clone();
+                }
+            }
+            SessionEventKind::Exited { .. } | SessionEventKind::Deleted => {
+                if let Some(session) = index.get_mut(&event.session_id) {
+                    session.finish(now);
+                }
+            }
+            _ => {}
+        }
+
+        evict(&mut index, &options, now);
+    }
+
+    /// After missed events, treat sessions the server no longer lists as exited
+    fn reconcile(&self) {
+        let live: HashSet<String> = self.session_manager.get_local_sessions().into_iter()
+            .filter(|session| session.status != "exited")
+            .map(|session| session.id)
+            .collect();
+        let now = Utc::now();
+        let mut index = self.index.lock().unwrap();
+        for (id, session) in index.iter_mut() {
+            if !live.contains(id) {
+                session.finish(now);
+            }
+        }
+        evict(&mut index, &self.options.lock().unwrap(), now);
+    }
+
+    fn session_title(&self, session_id: &str) -> String {
+        self.session_manager.get_local_sessions().into_iter()
+            .find(|session| session.id == session_id)
+            .map(|session| session.title)
+            .unwrap_or_default()
+    }
+
+    pub fn search(&self, query: &SearchQuery) -> Result<SearchResults, String> {
+        let regex = query.compile()?;
+        let context = query.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES).min(MAX_CONTEXT_LINES);
+        let limit = query.max_results.unwrap_or(DEFAULT_MAX_RESULTS).clamp(1, MAX_RESULTS);
+
+        let index = self.index.lock().unwrap();
+        let mut found = Vec::new();
+        let mut truncated = false;
+        let mut sessions_searched = 0;
+
+        for (session_id, session) in index.iter() {
+            if query.session_ids.as_ref().is_some_and(|ids| !ids.contains(session_id)) {
+                continue;
+            }
+            sessions_searched += 1;
+
+            let lines = session.snapshot();
+            let mut session_matches = 0;
+            // Newest first, so the limit keeps the most recent matches
+            for position in (0..lines.len()).rev() {
+                let (number, text, timestamp) = &lines[position];
+                let Some(m) = regex.find(text) else { continue };
+                if session_matches == limit {
+                    truncated = true;
+                    break;
+                }
+                session_matches += 1;
+
+                let context_text = |range: std::ops::Range<usize>| lines[range].iter().map(|(_, text, _)| text.clone()).collect();
+                found.push((*timestamp, *number, SearchMatch {
+                    session_id: session_id.clone(),
+                    session_title: session.title.clone(),
+                    session_exited: session.exited_at.is_some(),
+                    line_number: *number,
+                    line: text.clone(),
+                    match_start: text[..m.start()].chars().count(),
+                    match_end: text[..m.end()].chars().count(),
+                    before: context_text(position.saturating_sub(context)..position),
+                    after: context_text(position + 1..(position + 1 + context).min(lines.len())),
+                    timestamp: timestamp.to_rfc3339(),
+                }));
+            }
+        }
+
+        found.sort_by_key(|(timestamp, number, _)| std::cmp::Reverse((*timestamp, *number)));
+        if found.len() > limit {
+            truncated = true;
+            found.truncate(limit);
+        }
+
+        Ok(SearchResults {
+            matches: found.into_iter().map(|(_, _, m)| m).collect(),
+            truncated,
+            sessions_searched,
+        })
+    }
+
+    pub fn indexed_sessions(&self) -> Vec<IndexedSession> {
+        let index = self.index.lock().unwrap();
+        let mut sessions: Vec<IndexedSession> = index.iter()
+            .map(|(id, session)| IndexedSession {
+                session_id: id.clone(),
+                title: session.title.clone(),
+                lines: session.lines.len(),
+                bytes: session.bytes,
+                exited_at: session.exited_at.map(|exited_at| exited_at.to_rfc3339()),
+            })
+            .collect();
+        sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        sessions
+    }
+}
+
+// Tauri commands for session search
+#[tauri::command]
+pub async fn search_sessions(search: State<'_, SessionSearch>, query: SearchQuery) -> Result<SearchResults, String> {
+    search.search(&query)
+}
+
+#[tauri::command]
+pub async fn get_search_index(search: State<'_, SessionSearch>) -> Result<Vec<IndexedSession>, String> {
+    Ok(search.indexed_sessions())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search() -> SessionSearch {
+        SessionSearch::new(crate::sessions::test_support::test_session_manager())
+    }
+
+    fn output(session_id: &str, kind: OutputKind) -> SessionOutput {
+        SessionOutput { session_id: session_id.to_string(), kind, received_at: std::time::Instant::now() }
+    }
+
+    fn lines(data: &str) -> Vec<String> {
+        let mut session = SessionIndex::new(String::new());
+        session.process(data.as_bytes(), Utc::now(), usize::MAX);
+        session.snapshot().into_iter().map(|(_, text, _)| text).collect()
+    }
+
+    #[test]
+    fn test_collects_printed_lines() {
+        assert_eq!(lines("\x1b[1;31merror\x1b[0m: boom\r\n\r\nnext"), vec!["error: boom", "", "next"]);
+        assert_eq!(lines(" 10%\r 50%\r100%\r\n"), vec!["100%"]);
+        assert_eq!(lines("abcdef\r\x1b[Kxy\n"), vec!["xy"]);
+        assert_eq!(lines("a\tb\x08c\n"), vec!["a       c"]);
+        assert_eq!(lines("$ vim\r\n\x1b[?1049h\x1b[2J\x1b[1;1H~ file\x1b[?1049l$ \n"), vec!["$ vim", "$"]);
+    }
+
+    #[test]
+    fn test_unfinished_lines_wrap_and_count_toward_memory() {
+        let wrapped = lines(&"x".repeat(MAX_LINE_CHARS * 2 + 10));
+        assert_eq!(wrapped.iter().map(|line| line.len()).collect::<Vec<_>>(), vec![MAX_LINE_CHARS, MAX_LINE_CHARS, 10]);
+        assert_eq!(lines("a\x1b[60000Cb\x1b[60000G\tc"), vec!["a", "b", "c"]);
+
+        let mut session = SessionIndex::new(String::new());
+        session.process(b"progress 10%", Utc::now(), usize::MAX);
+        assert_eq!(session.bytes, "progress 10%".len() * 4);
+        session.process(b"\r\n", Utc::now(), usize::MAX);
+        assert_eq!(session.bytes, "progress 10%".len() + LINE_OVERHEAD_BYTES);
+    }
+
+    #[test]
+    fn test_finds_substring_and_regex_matches_with_context() {
+        let search = search();
+        search.apply(&output("a1", OutputKind::Output("cargo test\r\nrunning 3 tests\r\nthread 'main' panicked at src/lib.rs:4\r\nfailed\r\n".to_string())));
+        search.apply(&output("b2", OutputKind::Output("npm start\r\nlistening on :3000\r\n$ ".to_string())));
+
+        let results = search.search(&SearchQuery { pattern: "PANICKED".to_string(), context_lines: Some(1), ..Default::default() }).unwrap();
+        assert_eq!((results.sessions_searched, results.matches.len()), (2, 1));
+        let found = &results.matches[0];
+        assert_eq!((found.session_id.as_str(), found.line_number), ("a1", 3));
+        assert_eq!((found.match_start, found.match_end), (14, 22));
+        assert_eq!((found.before.clone(), found.after.clone()), (vec!["running 3 tests".to_string()], vec!["failed".to_string()]));
+
+        let query = SearchQuery { pattern: r":\d+$".to_string(), regex: true, case_sensitive: true, ..Default::default() };
+        let found: Vec<_> = search.search(&query).unwrap().matches.into_iter().map(|m| (m.session_id, m.line_number)).collect();
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(&("a1".to_string(), 3)) && found.contains(&("b2".to_string(), 2)));
+
+        let partial = search.search(&SearchQuery { pattern: "$".to_string(), session_ids: Some(vec!["b2".to_string()]), ..Default::default() }).unwrap();
+        assert_eq!(partial.matches[0].line_number, 3);
+        assert!(search.search(&SearchQuery { pattern: "(".to_string(), regex: true, ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn test_caps_and_evicts_on_lifecycle_events() {
+        let search = search();
+        search.set_options(SearchIndexOptions { max_lines_per_session: 3, max_exited_sessions: 1, ..Default::default() });
+
+        search.apply(&output("a1", OutputKind::Output("1\n2\n3\n4\n5\n".to_string())));
+        let session = &search.indexed_sessions()[0];
+        assert_eq!(session.lines, 3);
+        let first = search.search(&SearchQuery { pattern: "3".to_string(), ..Default::default() }).unwrap();
+        assert_eq!(first.matches[0].line_number, 3);
+        assert!(search.search(&SearchQuery { pattern: "1".to_string(), ..Default::default() }).unwrap().matches.is_empty());
+
+        search.apply(&output("b2", OutputKind::Output("still running".to_string())));
+        search.handle_event(&SessionEvent::new("a1", SessionEventKind::Exited { exit_code: Some(0) }));
+        assert!(search.indexed_sessions()[0].exited_at.is_some());
+
+        search.apply(&output("b2", OutputKind::Exit { exit_code: None }));
+        let remaining = search.indexed_sessions();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!((remaining[0].session_id.as_str(), remaining[0].lines), ("b2", 1));
+    }
+}
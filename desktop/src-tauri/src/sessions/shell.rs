@@ -0,0 +1,108 @@
+// Shell word handling for session commands
+// The server joins the command words with spaces and runs them through the shell
+
+/// Split a command line into words the way a POSIX shell would, without expanding anything.
+/// Unterminated quotes and a trailing backslash are errors rather than guesses.
+pub fn split_words(command: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    // Set once a quote opens, so `''` still produces an empty word
+    let mut in_word = false;
+    let mut chars = command.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            ch if ch.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => word.push(ch),
+                        None => return Err(format!("Unterminated single quote in command: {}", command)),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        // Inside double quotes a backslash only escapes characters the shell treats specially there
+                        Some('\\') => match chars.next() {
+                            Some(next @ ('"' | '\\' | '$' | '`')) => word.push(next),
+                            Some('\n') => {}
+                            Some(next) => {
+                                word.push('\\');
+                                word.push(next);
+                            }
+                            None => return Err(format!("Unterminated double quote in command: {}", command)),
+                        },
+                        Some(ch) => word.push(ch),
+                        None => return Err(format!("Unterminated double quote in command: {}", command)),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some(next) => {
+                    in_word = true;
+                    word.push(next);
+                }
+                None => return Err(format!("Trailing backslash in command: {}", command)),
+            },
+            ch => {
+                in_word = true;
+                word.push(ch);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Quote `word` so the shell the server runs the joined command through sees it as one argument
+pub fn quote(word: &str) -> String {
+    let safe = !word.is_empty()
+        && word.chars().all(|ch| ch.is_ascii_alphanumeric() || "_-./=:,@%+".contains(ch));
+    if safe {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_splits_quoted_words() {
+        assert_eq!(split_words(r#"bash -c "make test""#).unwrap(), vec!["bash", "-c", "make test"]);
+        assert_eq!(split_words("ls  '/Volumes/My Disk' ''").unwrap(), vec!["ls", "/Volumes/My Disk", ""]);
+        assert_eq!(split_words(r#"echo My\ File "a \"b\" \n""#).unwrap(), vec!["echo", "My File", r#"a "b" \n"#]);
+        assert!(split_words("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rejects_unbalanced_quotes() {
+        assert!(split_words(r#"bash -c "make test"#).unwrap_err().contains("double quote"));
+        assert!(split_words("echo 'oops").unwrap_err().contains("single quote"));
+        assert!(split_words(r"echo \").unwrap_err().contains("backslash"));
+    }
+
+    #[test]
+    fn test_quoted_words_split_back_unchanged() {
+        let words = vec!["bash", "-c", "make test", "it's", "", "plain"];
+        let joined = words.iter().map(|word| quote(word)).collect::<Vec<_>>().join(" ");
+        assert_eq!(split_words(&joined).unwrap(), words);
+    }
+}
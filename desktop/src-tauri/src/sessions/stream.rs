@@ -0,0 +1,266 @@
+// Session output streams
+// Follows /api/sessions/{id}/stream for each live session and broadcasts its asciicast events
+
+use super::events::{streaming_client, Backoff, SseParser};
+use super::{SessionEventKind, SessionField, SessionManager};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{broadcast, watch};
+use tokio::time::sleep;
+use log::{debug, info, warn};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputKind {
+    /// First event of every (re)connection; output from before it is not replayed
+    Start { cols: u16, rows: u16 },
+    Output(String),
+    Resize { cols: u16, rows: u16 },
+    Exit { exit_code: Option<i32> },
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionOutput {
+    pub session_id: String,
+    pub kind: OutputKind,
+    pub received_at: Instant,
+}
+
+/// One SSE `data:` payload: the asciicast v2 header, `[time, "o" | "r", data]` or `["exit", code, id]`
+pub fn parse_cast_event(data: &str) -> Option<OutputKind> {
+    cast_event_kind(&serde_json::from_str(data).ok()?)
+}
+
+pub(super) fn cast_event_kind(value: &serde_json::Value) -> Option<OutputKind> {
+    if let Some(header) = value.as_object() {
+        let size = |key: &str| header.get(key)?.as_u64().map(|n| n.min(u16::MAX as u64) as u16);
+        return Some(OutputKind::Start { cols: size("width")?, rows: size("height")? });
+    }
+
+    match value.as_array()?.as_slice() {
+        [serde_json::Value::String(tag), code, ..] if tag == "exit" => {
+            Some(OutputKind::Exit { exit_code: code.as_i64().map(|code| code as i32) })
+        }
+        [_, serde_json::Value::String(kind), serde_json::Value::String(data)] => match kind.as_str() {
+            "o" => Some(OutputKind::Output(data.clone())),
+            "r" => {
+                let (cols, rows) = data.split_once('x')?;
+                Some(OutputKind::Resize { cols: cols.parse().ok()?, rows: rows.parse().ok()? })
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Output of every followed session on one channel, for previews, recording and search
+#[derive(Clone)]
+pub struct SessionOutputHub {
+    session_manager: SessionManager,
+    client: reqwest::Client,
+    // Dropping a sender ends that session's stream
+    streams: Arc<Mutex<HashMap<String, watch::Sender<()>>>>,
+    tracking: Arc<Mutex<Option<watch::Sender<()>>>>,
+    sender: broadcast::Sender<SessionOutput>,
+}
+
+impl SessionOutputHub {
+    pub fn new(session_manager: SessionManager) -> Self {
+        let (sender, _) = broadcast::channel(1024);
+
+        Self {
+            session_manager,
+            client: streaming_client(),
+            streams: Arc::new(Mutex::new(HashMap::new())),
+            tracking: Arc::new(Mutex::new(None)),
+            sender,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionOutput> {
+        self.sender.subscribe()
+    }
+
+    /// Follow every session that is running now or starts later, until `stop`
+    pub fn start(&self) -> Result<(), String> {
+        let shutdown = {
+            let mut tracking = self.tracking.lock().unwrap();
+            if tracking.is_some() {
+                return Err("Session output tracking is already running".to_string());
+            }
+            let (sender, receiver) = watch::channel(());
+            *tracking = Some(sender);
+            receiver
+        };
+
+        info!("Following session output");
+        // Subscribe before following the cached sessions so no creation falls in between
+        let events = self.session_manager.subscribe_to_events();
+        for session in self.session_manager.get_local_sessions() {
+            if session.status != "exited" {
+                self.follow(&session.id);
+            }
+        }
+
+        let hub = self.clone();
+        tauri::async_runtime::spawn(async move { hub.track_sessions(events, shutdown).await });
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if self.tracking.lock().unwrap().take().is_some() {
+            info!("Stopped following session output");
+        }
+        self.streams.lock().unwrap().clear();
+    }
+
+    pub fn follow(&self, session_id: &str) {
+        let mut streams = self.streams.lock().unwrap();
+        if streams.contains_key(session_id) {
+            return;
+        }
+
+        let (shutdown, receiver) = watch::channel(());
+        streams.insert(session_id.to_string(), shutdown);
+
+        let hub = self.clone();
+        let session_id = session_id.to_string();
+        tauri::async_runtime::spawn(async move { hub.run(&session_id, receiver).await });
+    }
+
+    pub fn unfollow(&self, session_id: &str) -> bool {
+        self.streams.lock().unwrap().remove(session_id).is_some()
+    }
+
+    pub fn is_following(&self, session_id: &str) -> bool {
+        self.streams.lock().unwrap().contains_key(session_id)
+    }
+
+    fn emit(&self, session_id: &str, kind: OutputKind) {
+        let _ = self.sender.send(SessionOutput { session_id: session_id.to_string(), kind, received_at: Instant::now() });
+    }
+
+    async fn track_sessions(&self, mut events: super::SessionEventReceiver, mut shutdown: watch::Receiver<()>) {
+        loop {
+            let event = tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Missed {} session events; following the cached sessions again", skipped);
+                        for session in self.session_manager.get_local_sessions() {
+                            if session.status != "exited" {
+                                self.follow(&session.id);
+                            }
+                        }
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = shutdown.changed() => break,
+            };
+
+            match event.kind {
+                SessionEventKind::Created(session) if session.status != "exited" => self.follow(&session.id),
+                SessionEventKind::Updated { session, changes } => {
+                    // The stream itself only reports the size it started with
+                    let resized = changes.iter().any(|change| matches!(change.field, SessionField::Cols | SessionField::Rows));
+                    if resized && self.is_following(&session.id) {
+                        self.emit(&session.id, OutputKind::Resize { cols: session.cols, rows: session.rows });
+                    }
+                }
+                SessionEventKind::Exited { exit_code } if self.unfollow(&event.session_id) => {
+                    self.emit(&event.session_id, OutputKind::Exit { exit_code });
+                }
+                SessionEventKind::Deleted if self.unfollow(&event.session_id) => {
+                    self.emit(&event.session_id, OutputKind::Exit { exit_code: None });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    async fn run(&self, session_id: &str, mut shutdown: watch::Receiver<()>) {
+        let mut backoff = Backoff::new();
+
+        let reason = loop {
+            let error = tokio::select! {
+                result = self.attach(session_id, &mut backoff) => match result {
+                    Ok(reason) => break reason,
+                    Err(error) => error,
+                },
+                _ = shutdown.changed() => break "Stopped".to_string(),
+            };
+
+            let delay = backoff.next_delay();
+            debug!("Output stream of session {} lost ({}), retrying in {:?}", session_id, error, delay);
+            tokio::select! {
+                _ = sleep(delay) => {}
+                _ = shutdown.changed() => break "Stopped".to_string(),
+            }
+        };
+
+        debug!("Output stream of session {} ended: {}", session_id, reason);
+        // Still registered only if nobody unfollowed, i.e. our sender is alive
+        if shutdown.has_changed().is_ok() {
+            self.streams.lock().unwrap().remove(session_id);
+        }
+    }
+
+    /// One connection; `Ok` means the session is gone for good
+    async fn attach(&self, session_id: &str, backoff: &mut Backoff) -> Result<String, String> {
+        let url = format!("{}/api/sessions/{}/stream", self.session_manager.server_url(), session_id);
+        let mut response = match self.client.get(&url).header(reqwest::header::ACCEPT, "text/event-stream").send().await {
+            Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => {
+                return Ok("Session not found".to_string());
+            }
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => return Err(format!("HTTP {}", response.status())),
+            Err(e) => return Err(format!("Failed to connect: {}", e)),
+        };
+
+        backoff.reset(None);
+        let mut parser = SseParser::default();
+
+        loop {
+            let chunk = match response.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => return Err("Server closed the output stream".to_string()),
+                Err(e) => return Err(format!("Output stream failed: {}", e)),
+            };
+
+            for message in parser.feed(&chunk) {
+                match parse_cast_event(&message.data) {
+                    Some(kind) => self.emit(session_id, kind),
+                    None => debug!("Ignoring unexpected output event of session {}", session_id),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_cast_events() {
+        assert_eq!(
+            parse_cast_event(r#"{"version":2,"width":120,"height":40,"timestamp":1738000000}"#),
+            Some(OutputKind::Start { cols: 120, rows: 40 }),
+        );
+        assert_eq!(
+            parse_cast_event(r#"[0.25,"o","$ ls\r\n\u001b[1mbin\u001b[0m"]"#),
+            Some(OutputKind::Output("$ ls\r\n\x1b[1mbin\x1b[0m".to_string())),
+        );
+        assert_eq!(parse_cast_event(r#"[1.5,"r","100x30"]"#), Some(OutputKind::Resize { cols: 100, rows: 30 }));
+        assert_eq!(parse_cast_event(r#"["exit",2,"a1"]"#), Some(OutputKind::Exit { exit_code: Some(2) }));
+    }
+
+    #[test]
+    fn test_ignores_unknown_events() {
+        assert_eq!(parse_cast_event("raw pty bytes"), None);
+        assert_eq!(parse_cast_event(r#"[0.1,"i","input"]"#), None);
+        assert_eq!(parse_cast_event(r#"[0.1,"r","wide"]"#), None);
+        assert_eq!(parse_cast_event(r#"{"version":2}"#), None);
+    }
+}
@@ -0,0 +1,508 @@
+// Output triggers
+// Regex rules over session output that notify, badge the tray, call webhooks, type into or stop sessions
+
+use super::profiles::ProfileLauncher;
+use super::search::LineCollector;
+use super::stream::{OutputKind, SessionOutput};
+use super::websocket::WebSocketManager;
+use super::SessionManager;
+use crate::config::update_and_apply;
+use crate::AppState;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::broadcast;
+use vte::Parser;
+use log::{error, info, warn};
+
+pub const TRIGGER_EVENT: &str = "session-trigger";
+const DEFAULT_COOLDOWN_SECS: u64 = 60;
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TriggerAction {
+    Notify,
+    /// Count the firing on the tray icon until `clear_trigger_badge`
+    TrayBadge,
+    /// POST the firing as JSON
+    Webhook { url: String },
+    SendInput {
+        /// The matching session when unset
+        #[serde(default)]
+        session_id: Option<String>,
+        data: String,
+    },
+    StopSession,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TriggerFilter {
+    /// Any session when empty
+    pub session_ids: Vec<String>,
+    /// Regex the session title has to match
+    pub title_pattern: Option<String>,
+    /// Regex the session command has to match
+    pub command_pattern: Option<String>,
+    /// Sessions launched from one of these profiles
+    pub profiles: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TriggerRule {
+    pub name: String,
+    /// Matched against each line of output, with colors and other escape sequences removed
+    pub pattern: String,
+    pub case_sensitive: bool,
+    pub filter: TriggerFilter,
+    /// Least time between two firings of the rule for the same session
+    pub cooldown_secs: u64,
+    pub action: TriggerAction,
+    pub enabled: bool,
+}
+
+impl Default for TriggerRule {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            pattern: String::new(),
+            case_sensitive: true,
+            filter: TriggerFilter::default(),
+            cooldown_secs: DEFAULT_COOLDOWN_SECS,
+            action: TriggerAction::Notify,
+            enabled: true,
+        }
+    }
+}
+
+/// What rule filters are checked against
+#[derive(Debug, Clone, Default)]
+pub struct TriggerTarget {
+    pub title: String,
+    pub command: Option<String>,
+    pub profile_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TriggerFiring {
+    pub rule: String,
+    pub session_id: String,
+    pub session_title: String,
+    pub line: String,
+    pub action: TriggerAction,
+    /// RFC 3339
+    pub timestamp: String,
+}
+
+fn compile(pattern: &str, case_sensitive: bool) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid pattern {}: {}", pattern, e))
+}
+
+struct CompiledRule {
+    rule: TriggerRule,
+    pattern: Regex,
+    title: Option<Regex>,
+    command: Option<Regex>,
+}
+
+impl CompiledRule {
+    fn new(rule: &TriggerRule) -> Result<Self, String> {
+        if rule.pattern.is_empty() {
+            return Err(format!("Trigger {} needs a pattern", rule.name));
+        }
+        let filter = |pattern: &Option<String>| pattern.as_deref().map(|pattern| compile(pattern, true)).transpose();
+
+        Ok(Self {
+            rule: rule.clone(),
+            pattern: compile(&rule.pattern, rule.case_sensitive)?,
+            title: filter(&rule.filter.title_pattern)?,
+            command: filter(&rule.filter.command_pattern)?,
+        })
+    }
+
+    fn applies_to(&self, session_id: &str, target: &TriggerTarget) -> bool {
+        let filter = &self.rule.filter;
+        (filter.session_ids.is_empty() || filter.session_ids.iter().any(|id| id == session_id))
+            && self.title.as_ref().is_none_or(|title| title.is_match(&target.title))
+            && self.command.as_ref().is_none_or(|command| target.command.as_deref().is_some_and(|text| command.is_match(text)))
+            && (filter.profiles.is_empty() || target.profile_id.as_ref().is_some_and(|id| filter.profiles.contains(id)))
+    }
+}
+
+pub fn validate_trigger_rules(rules: &[TriggerRule]) -> Result<(), String> {
+    let mut names = HashSet::new();
+
+    for rule in rules {
+        if rule.name.trim().is_empty() {
+            return Err("Trigger needs a name".to_string());
+        }
+        if !names.insert(rule.name.as_str()) {
+            return Err(format!("There is more than one trigger named {}", rule.name));
+        }
+        CompiledRule::new(rule)?;
+
+        match &rule.action {
+            TriggerAction::Webhook { url } if !url.starts_with("http://") && !url.starts_with("https://") => {
+                return Err(format!("Trigger {} needs an http or https webhook URL", rule.name));
+            }
+            TriggerAction::SendInput { data, .. } if data.is_empty() => {
+                return Err(format!("Trigger {} has no input to send", rule.name));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+struct SessionLines {
+    parser: Parser,
+    collector: LineCollector,
+    /// Rules that already fired on the unfinished line
+    fired_on_partial: HashSet<String>,
+}
+
+/// Matches rules line by line, keeping each session's unfinished line between chunks
+#[derive(Default)]
+pub struct TriggerEngine {
+    rules: Vec<CompiledRule>,
+    sessions: HashMap<String, SessionLines>,
+    last_fired: HashMap<(String, String), Instant>,
+}
+
+impl TriggerEngine {
+    /// Use the enabled rules of `rules`; invalid ones are skipped
+    pub fn set_rules(&mut self, rules: &[TriggerRule]) {
+        self.rules = rules.iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| CompiledRule::new(rule).map_err(|e| warn!("Skipping trigger {}: {}", rule.name, e)).ok())
+            .collect();
+
+        let names: HashSet<&str> = self.rules.iter().map(|compiled| compiled.rule.name.as_str()).collect();
+        self.last_fired.retain(|(rule, _), _| names.contains(rule.as_str()));
+        if self.rules.is_empty() {
+            self.sessions.clear();
+        }
+    }
+
+    /// Feed a chunk of output. A match on the unfinished line fires right away, so prompts are
+    /// caught without a newline, and not again when the line is finished.
+    pub fn process(
+        &mut self,
+        session_id: &str,
+        data: &str,
+        now: Instant,
+        target: &dyn Fn() -> TriggerTarget
+    ) -> Vec<TriggerFiring> {
+        if self.rules.is_empty() {
+            return Vec::new();
+        }
+
+        let lines = self.sessions.entry(session_id.to_string()).or_insert_with(|| SessionLines {
+            parser: Parser::new(),
+            collector: LineCollector::default(),
+            fired_on_partial: HashSet::new(),
+        });
+        for byte in data.bytes() {
+            lines.parser.advance(&mut lines.collector, byte);
+        }
+
+        let mut matcher = Matcher { rules: &self.rules, last_fired: &mut self.last_fired, session_id, now, target, cached: None };
+        let mut firings = Vec::new();
+        for line in lines.collector.take_completed() {
+            let fired = std::mem::take(&mut lines.fired_on_partial);
+            firings.extend(matcher.evaluate(&line, &fired));
+        }
+
+        let partial = lines.collector.text();
+        if !partial.is_empty() {
+            let fired = matcher.evaluate(&partial, &lines.fired_on_partial);
+            lines.fired_on_partial.extend(fired.iter().map(|firing| firing.rule.clone()));
+            firings.extend(fired);
+        }
+        firings
+    }
+
+    /// Forget a session that ended
+    pub fn finish(&mut self, session_id: &str) {
+        self.sessions.remove(session_id);
+        self.last_fired.retain(|(_, session), _| session != session_id);
+    }
+}
+
+struct Matcher<'a> {
+    rules: &'a [CompiledRule],
+    last_fired: &'a mut HashMap<(String, String), Instant>,
+    session_id: &'a str,
+    now: Instant,
+    target: &'a dyn Fn() -> TriggerTarget,
+    // Looked up on the first match only
+    cached: Option<TriggerTarget>,
+}
+
+impl Matcher<'_> {
+    fn evaluate(&mut self, line: &str, skip: &HashSet<String>) -> Vec<TriggerFiring> {
+        let mut firings = Vec::new();
+
+        for compiled in self.rules {
+            let rule = &compiled.rule;
+            if skip.contains(&rule.name) || !compiled.pattern.is_match(line) {
+                continue;
+            }
+            let target = self.cached.get_or_insert_with(self.target);
+            if !compiled.applies_to(self.session_id, target) {
+                continue;
+            }
+
+            let key = (rule.name.clone(), self.session_id.to_string());
+            let cooling = self.last_fired.get(&key)
+                .is_some_and(|fired| self.now.duration_since(*fired) < Duration::from_secs(rule.cooldown_secs));
+            if cooling {
+                continue;
+            }
+            self.last_fired.insert(key, self.now);
+
+            firings.push(TriggerFiring {
+                rule: rule.name.clone(),
+                session_id: self.session_id.to_string(),
+                session_title: target.title.clone(),
+                line: line.to_string(),
+                action: rule.action.clone(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            });
+        }
+        firings
+    }
+}
+
+/// Runs the configured trigger rules over every session's output
+#[derive(Clone)]
+pub struct SessionTriggers {
+    session_manager: SessionManager,
+    terminals: WebSocketManager,
+    launcher: ProfileLauncher,
+    engine: Arc<Mutex<TriggerEngine>>,
+    badge: Arc<AtomicUsize>,
+    // Kept apart from the session manager's client, which carries the local server's headers
+    webhooks: reqwest::Client,
+}
+
+impl SessionTriggers {
+    pub fn new(session_manager: SessionManager, terminals: WebSocketManager, launcher: ProfileLauncher) -> Self {
+        Self {
+            session_manager,
+            terminals,
+            launcher,
+            engine: Arc::new(Mutex::new(TriggerEngine::default())),
+            badge: Arc::new(AtomicUsize::new(0)),
+            webhooks: reqwest::Client::builder()
+                .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn set_rules(&self, rules: &[TriggerRule]) {
+        self.engine.lock().unwrap().set_rules(rules);
+    }
+
+    pub fn start(&self, app: AppHandle, mut outputs: broadcast::Receiver<SessionOutput>) {
+        let triggers = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let output = match outputs.recv().await {
+                    Ok(output) => output,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Dropped {} output events; triggers may miss matches", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                let firings = match &output.kind {
+                    OutputKind::Output(data) => {
+                        let target = || triggers.target(&output.session_id);
+                        triggers.engine.lock().unwrap().process(&output.session_id, data, Instant::now(), &target)
+                    }
+                    OutputKind::Exit { .. } => {
+                        triggers.engine.lock().unwrap().finish(&output.session_id);
+                        continue;
+                    }
+                    OutputKind::Start { .. } | OutputKind::Resize { .. } => continue,
+                };
+
+                for firing in firings {
+                    let (triggers, app) = (triggers.clone(), app.clone());
+                    tauri::async_runtime::spawn(async move { triggers.fire(&app, firing).await });
+                }
+            }
+        });
+    }
+
+    fn target(&self, session_id: &str) -> TriggerTarget {
+        let session = self.session_manager.get_local_sessions().into_iter().find(|session| session.id == session_id);
+        TriggerTarget {
+            title: session.as_ref().map(|session| session.title.clone()).unwrap_or_default(),
+            command: session.and_then(|session| session.command),
+            profile_id: self.launcher.launched_profile(session_id),
+        }
+    }
+
+    async fn fire(&self, app: &AppHandle, firing: TriggerFiring) {
+        info!("Trigger {} fired in session {}", firing.rule, firing.session_id);
+        if let Err(e) = self.run_action(app, &firing).await {
+            error!("Action of trigger {} failed: {}", firing.rule, e);
+        }
+        if let Err(e) = app.emit(TRIGGER_EVENT, &firing) {
+            error!("Failed to emit trigger event: {}", e);
+        }
+    }
+
+    async fn run_action(&self, app: &AppHandle, firing: &TriggerFiring) -> Result<(), String> {
+        match &firing.action {
+            TriggerAction::Notify => {
+                if let Some(service) = app.try_state::<crate::notifications::NotificationService>() {
+                    service.show_notification(&firing.rule, &firing.line, &firing.session_title, None).await;
+                }
+                Ok(())
+            }
+            TriggerAction::TrayBadge => self.set_badge(app, self.badge.fetch_add(1, Ordering::SeqCst) + 1),
+            TriggerAction::Webhook { url } => {
+                let response = self.webhooks.post(url).json(firing).send().await
+                    .map_err(|e| format!("Failed to call webhook {}: {}", url, e))?;
+                if !response.status().is_success() {
+                    return Err(format!("Webhook {} returned HTTP {}", url, response.status()));
+                }
+                Ok(())
+            }
+            TriggerAction::SendInput { session_id, data } => {
+                let session_id = session_id.as_deref().unwrap_or(&firing.session_id);
+                self.terminals.send_input(session_id, data.clone()).await
+            }
+            TriggerAction::StopSession => self.session_manager.delete_session(&firing.session_id).await,
+        }
+    }
+
+    fn set_badge(&self, app: &AppHandle, count: usize) -> Result<(), String> {
+        match app.try_state::<crate::ui::TrayManager>() {
+            Some(tray_manager) => tray_manager.set_badge(count),
+            None => Err("No tray icon to show the trigger badge on".to_string()),
+        }
+    }
+}
+
+// Tauri commands for output triggers
+#[tauri::command]
+pub async fn list_trigger_rules(app: AppHandle) -> Result<Vec<TriggerRule>, String> {
+    Ok(app.state::<AppState>().config.lock().unwrap().trigger_rules.clone())
+}
+
+/// Create a rule, or replace the one with the same name
+#[tauri::command]
+pub async fn save_trigger_rule(app: AppHandle, rule: TriggerRule) -> Result<TriggerRule, String> {
+    update_and_apply(&app, |config| {
+        match config.trigger_rules.iter_mut().find(|existing| existing.name == rule.name) {
+            Some(existing) => *existing = rule.clone(),
+            None => config.trigger_rules.push(rule.clone()),
+        }
+        Ok(rule)
+    })
+}
+
+#[tauri::command]
+pub async fn delete_trigger_rule(app: AppHandle, name: String) -> Result<(), String> {
+    update_and_apply(&app, |config| {
+        let count = config.trigger_rules.len();
+        config.trigger_rules.retain(|rule| rule.name != name);
+        if config.trigger_rules.len() == count {
+            return Err(format!("Trigger {} not found", name));
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn clear_trigger_badge(app: AppHandle, triggers: State<'_, SessionTriggers>) -> Result<(), String> {
+    triggers.badge.store(0, Ordering::SeqCst);
+    triggers.set_badge(&app, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(rules: Vec<TriggerRule>) -> TriggerEngine {
+        let mut engine = TriggerEngine::default();
+        engine.set_rules(&rules);
+        engine
+    }
+
+    fn target() -> TriggerTarget {
+        TriggerTarget { title: "tests".to_string(), command: Some("cargo test".to_string()), profile_id: None }
+    }
+
+    fn fired(firings: &[TriggerFiring]) -> Vec<(&str, &str)> {
+        firings.iter().map(|firing| (firing.rule.as_str(), firing.line.as_str())).collect()
+    }
+
+    #[test]
+    fn test_matches_across_chunks_without_colors() {
+        let mut engine = engine(vec![TriggerRule {
+            name: "failed".to_string(),
+            pattern: r"test result: FAILED".to_string(),
+            ..Default::default()
+        }]);
+        let now = Instant::now();
+
+        assert!(engine.process("a", "running 3 tests\r\ntest result: \x1b[3", now, &target).is_empty());
+        let firings = engine.process("a", "1mFAIL\x1b[0mED. 1 passed; 2 failed\r\n", now, &target);
+        assert_eq!(fired(&firings), vec![("failed", "test result: FAILED. 1 passed; 2 failed")]);
+        assert_eq!(firings[0].session_title, "tests");
+    }
+
+    #[test]
+    fn test_partial_lines_and_cooldown() {
+        let mut engine = engine(vec![
+            TriggerRule { name: "password".to_string(), pattern: "(?i)password:".to_string(), cooldown_secs: 0, ..Default::default() },
+            TriggerRule { name: "error".to_string(), pattern: "ERROR".to_string(), cooldown_secs: 30, ..Default::default() },
+        ]);
+        let now = Instant::now();
+
+        assert_eq!(fired(&engine.process("a", "Password: ", now, &target)), vec![("password", "Password:")]);
+        assert!(engine.process("a", "\r\n", now, &target).is_empty());
+
+        assert_eq!(engine.process("a", "ERROR one\nERROR two\n", now, &target).len(), 1);
+        assert_eq!(engine.process("b", "ERROR elsewhere\n", now, &target).len(), 1);
+        assert_eq!(engine.process("a", "ERROR three\n", now + Duration::from_secs(31), &target).len(), 1);
+    }
+
+    #[test]
+    fn test_filters_and_validation() {
+        let rule = TriggerRule {
+            name: "deploy".to_string(),
+            pattern: "ERROR".to_string(),
+            filter: TriggerFilter { command_pattern: Some("^deploy".to_string()), ..Default::default() },
+            action: TriggerAction::Webhook { url: "https://hooks.example.com/deploy".to_string() },
+            ..Default::default()
+        };
+        let mut engine = engine(vec![rule.clone()]);
+        let deploy = || TriggerTarget { command: Some("deploy --prod".to_string()), ..target() };
+
+        assert!(engine.process("a", "ERROR\n", Instant::now(), &target).is_empty());
+        assert_eq!(engine.process("b", "ERROR\n", Instant::now(), &deploy).len(), 1);
+
+        assert!(validate_trigger_rules(std::slice::from_ref(&rule)).is_ok());
+        let ftp = TriggerRule { action: TriggerAction::Webhook { url: "ftp://example.com".to_string() }, ..rule.clone() };
+        assert!(validate_trigger_rules(&[ftp]).is_err());
+        assert!(validate_trigger_rules(&[TriggerRule { pattern: "(".to_string(), ..rule.clone() }]).is_err());
+        assert!(validate_trigger_rules(&[rule.clone(), rule]).is_err());
+    }
+}
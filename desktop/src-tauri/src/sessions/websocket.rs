@@ -1,182 +1,402 @@
-//! WebSocket functionality for real-time session updates with security controls
+//! Terminal I/O over the session WebSocket: keystrokes and paste data go in, PTY output comes back
 
+use super::events::Backoff;
+use super::{SessionManager, LOCAL_BYPASS_HEADER};
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::time::sleep;
 use tokio_tungstenite::connect_async;
-use tokio_tungstenite::tungstenite::Message;
-use futures_util::StreamExt;
-use tokio::sync::{broadcast, Mutex};
-use std::sync::Arc;
-use log::{info, error, warn};
-use url::Url;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderValue, StatusCode};
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use log::{debug, error, info, warn};
 
-// use crate::add_log_entry; // Will be implemented later
-// use crate::security::{InputValidator, MessageSanitizer, SecurityError}; // Will be implemented later
+pub const TERMINAL_OUTPUT_EVENT: &str = "terminal-output";
+pub const TERMINAL_STATE_EVENT: &str = "terminal-state";
 
-pub struct WebSocketManager {
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum TerminalState {
+    Connecting,
+    Connected,
+    Reconnecting { error: String, delay_ms: u64 },
+    /// Final: the session is gone or the app disconnected
+    Closed { reason: String },
+}
+
+#[derive(Debug, Clone)]
+pub enum TerminalEvent {
+    Output { session_id: String, data: Vec<u8> },
+    State { session_id: String, state: TerminalState },
+}
+
+enum TerminalCommand {
+    Input(String),
+}
+
+struct TerminalConnection {
+    commands: mpsc::UnboundedSender<TerminalCommand>,
     connected: Arc<Mutex<bool>>,
-    event_sender: broadcast::Sender<String>,
-    validator: InputValidator,
+    // Dropping the sender closes the socket and ends the reconnect loop
+    _shutdown: watch::Sender<()>,
+}
+
+/// Messages understood by the server's `/ws` handler (`types.InputMessage`)
+fn input_message(data: &str) -> String {
+    serde_json::json!({ "type": "input", "data": data }).to_string()
+}
+
+/// `ws://` (or `wss://`) URL of a session's terminal socket
+pub fn websocket_url(server_url: &str, session_id: &str) -> Result<String, String> {
+    let mut url = reqwest::Url::parse(server_url).map_err(|e| format!("Invalid server URL: {}", e))?;
+    let scheme = match url.scheme() {
+        "http" => "ws",
+        "https" => "wss",
+        other => return Err(format!("Unsupported server URL scheme: {}", other)),
+    };
+    url.set_scheme(scheme).map_err(|_| "Invalid WebSocket URL".to_string())?;
+    url.set_path("/ws");
+    url.query_pairs_mut().clear().append_pair("sessionId", session_id);
+    Ok(url.to_string())
+}
+
+/// One reconnecting terminal connection per session, kept in app state
+#[derive(Clone)]
+pub struct WebSocketManager {
+    session_manager: SessionManager,
+    connections: Arc<Mutex<HashMap<String, TerminalConnection>>>,
+    event_sender: broadcast::Sender<TerminalEvent>,
 }
 
 impl WebSocketManager {
-    pub fn new() -> Self {
-        let (event_sender, _) = broadcast::channel(100");
-        
+    pub fn new(session_manager: SessionManager) -> Self {
+        let (event_sender, _) = broadcast::channel(1024);
+
         Self {
-            connected: Arc::new(Mutex::new(false)),
+            session_manager,
+            connections: Arc::new(Mutex::new(HashMap::new())),
             event_sender,
-            validator: InputValidator::new(),
         }
     }
 
-    pub async fn connect(&self, server_url: String) -> Result<(), String> {
-        // Validate server URL
-        self.validator.validate_url(&server_url)
-            .map_err(|e| format!("Invalid server URL: {}", e))?;
+    pub fn subscribe(&self) -> broadcast::Receiver<TerminalEvent> {
+        self.event_sender.subscribe()
+    }
 
-        // Enforce WSS for non-localhost connections
-        let ws_url = if server_url.contains("localhost") || server_url.contains("127.0.0.1") {
-            format!("ws://{}/ws", server_url.trim_end_matches('/'))
-        } else {
-            format!("wss://{}/ws", server_url.trim_end_matches('/'))
-        };
+    /// Send output that does not come from a server socket, such as a recording playback, to the terminal views
+    pub fn publish(&self, event: TerminalEvent) {
+        let _ = self.event_sender.send(event);
+    }
 
-        // Validate final WebSocket URL
-        let ws_url = Url::parse(&ws_url)
-            .map_err(|e| format!("Invalid WebSocket URL: {}", e))?;
+    /// Attach to a session's terminal; does nothing when already attached
+    pub fn connect(&self, session_id: &str) -> Result<(), String> {
+        websocket_url(&self.session_manager.server_url(), session_id)?;
 
-        if !ws_url.scheme().starts_with("ws") {
-            return Err("Invalid WebSocket protocol".to_string()");
+        let mut connections = self.connections.lock().unwrap();
+        if connections.contains_key(session_id) {
+            return Ok(());
         }
 
-        log::info!("&format!("Connecting to WebSocket: {}", ws_url)");
-        info!("Connecting to WebSocket: {}", ws_url");
+        let (commands, command_receiver) = mpsc::unbounded_channel();
+        let (shutdown, shutdown_receiver) = watch::channel(());
+        let connected = Arc::new(Mutex::new(false));
+        connections.insert(session_id.to_string(), TerminalConnection {
+            commands,
+            connected: Arc::clone(&connected),
+            _shutdown: shutdown,
+        });
 
-        match connect_async(ws_url).await {
-            Ok((ws_stream, response)) => {
-                // Verify the server response
-                if !response.status().is_success() {
-                    return Err(format!("WebSocket connection failed: {}", response.status())");
-                }
+        let manager = self.clone();
+        let session_id = session_id.to_string();
+        tauri::async_runtime::spawn(async move {
+            manager.run(&session_id, connected, command_receiver, shutdown_receiver).await;
+        });
+        Ok(())
+    }
 
-                let (_write, mut read) = ws_stream.split(");
+    pub fn disconnect(&self, session_id: &str) -> bool {
+        self.connections.lock().unwrap().remove(session_id).is_some()
+    }
 
-                {
-                    let mut connected = self.connected.lock().await;
-                    *connected = true;
-                }
+    pub fn disconnect_all(&self) {
+        self.connections.lock().unwrap().clear();
+    }
 
-                log::info!(""WebSocket connected successfully");
-                info!("WebSocket connected successfully");
-
-                // Listen for messages with sanitization
-                while let Some(message) = read.next().await {
-                    match message {
-                        Ok(msg) => {
-                            if let Message::Text(text) = msg {
-                                // Sanitize incoming messages
-                                match self.sanitize_message(&text) {
-                                    Ok(sanitized) => {
-                                        log::debug!(""&format!("Received WebSocket message: {}", sanitized)");
-                                        // Forward sanitized message to subscribers
-                                        let _ = self.event_sender.send(sanitized");
-                                    }
-                                    Err(e) => {
-                                        warn!("Message sanitization failed: {}", e");
-                                        add_log_entry("warn", &format!("Message sanitization failed: {}", e)");
-                                    }
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            log::error!("&format!("WebSocket error: {}", e)");
-                            error!("WebSocket error: {}", e");
-                            break;
-                        }
+    pub fn is_connected(&self, session_id: &str) -> bool {
+        self.connections.lock().unwrap()
+            .get(session_id)
+            .is_some_and(|connection| *connection.connected.lock().unwrap())
+    }
+
+    /// Send keystrokes or pasted text; uses the HTTP input endpoint while the socket is down
+    pub async fn send_input(&self, session_id: &str, data: String) -> Result<(), String> {
+        let data = {
+            let connections = self.connections.lock().unwrap();
+            match connections.get(session_id) {
+                Some(connection) if *connection.connected.lock().unwrap() => {
+                    match connection.commands.send(TerminalCommand::Input(data)) {
+                        Ok(()) => return Ok(()),
+                        Err(mpsc::error::SendError(TerminalCommand::Input(data))) => data,
                     }
                 }
+                _ => data,
+            }
+        };
 
-                {
-                    let mut connected = self.connected.lock().await;
-                    *connected = false;
-                }
+        let url = format!("{}/api/sessions/{}/input", self.session_manager.server_url(), session_id);
+        let response = self.session_manager.client()
+            .post(&url)
+            .json(&serde_json::json!({ "type": "input", "data": data }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send input: {}", e))?;
 
-                log::info!(""WebSocket disconnected");
-                info!("WebSocket disconnected");
-            }
-            Err(e) => {
-                log::error!("&format!("Failed to connect to WebSocket: {}", e)");
-                return Err(format!("Failed to connect to WebSocket: {}", e)");
-            }
+        if !response.status().is_success() {
+            return Err(format!("Failed to send input: HTTP {}", response.status()));
+        }
+        Ok(())
+    }
+
+    pub async fn resize(&self, session_id: &str, cols: u16, rows: u16) -> Result<(), String> {
+        if cols == 0 || rows == 0 {
+            return Err(format!("Invalid terminal size {}x{}", cols, rows));
         }
 
+        let url = format!("{}/api/sessions/{}/resize", self.session_manager.server_url(), session_id);
+        let response = self.session_manager.client()
+            .post(&url)
+            .json(&serde_json::json!({ "cols": cols, "rows": rows }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to resize terminal: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Failed to resize terminal: HTTP {}", response.status()));
+        }
         Ok(())
     }
 
-    fn sanitize_message(&self, message: &str) -> Result<String, SecurityError> {
-        // First sanitize as HTML to prevent XSS
-        let html_safe = MessageSanitizer::sanitize_html(message");
-        
-        // Then sanitize any potential commands
-        MessageSanitizer::sanitize_command(&html_safe)
+    fn session_exited(&self, session_id: &str) -> bool {
+        self.session_manager.get_local_sessions()
+            .iter()
+            .any(|session| session.id == session_id && session.status == "exited")
     }
 
-    pub async fn is_connected(&self) -> bool {
-        let connected = self.connected.lock().await;
-        *connected
+    fn emit_state(&self, session_id: &str, state: TerminalState) {
+        let _ = self.event_sender.send(TerminalEvent::State { session_id: session_id.to_string(), state });
     }
 
-    pub fn subscribe(&self) -> broadcast::Receiver<String> {
-        self.event_sender.subscribe()
+    async fn run(
+        &self,
+        session_id: &str,
+        connected: Arc<Mutex<bool>>,
+        mut commands: mpsc::UnboundedReceiver<TerminalCommand>,
+        mut shutdown: watch::Receiver<()>,
+    ) {
+        let mut backoff = Backoff::new();
+
+        let reason = loop {
+            self.emit_state(session_id, TerminalState::Connecting);
+
+            let result = tokio::select! {
+                result = self.attach(session_id, &connected, &mut commands, &mut backoff) => result,
+                _ = shutdown.changed() => break "Disconnected".to_string(),
+            };
+            *connected.lock().unwrap() = false;
+
+            let error = match result {
+                Ok(reason) | Err(AttachError::Fatal(reason)) => break reason,
+                Err(AttachError::Retry(error)) => error,
+            };
+            if self.session_exited(session_id) {
+                break "Session exited".to_string();
+            }
+
+            let delay = backoff.next_delay();
+            warn!("Terminal connection to session {} lost ({}), retrying in {:?}", session_id, error, delay);
+            self.emit_state(session_id, TerminalState::Reconnecting { error, delay_ms: delay.as_millis() as u64 });
+
+            tokio::select! {
+                _ = sleep(delay) => {}
+                _ = shutdown.changed() => break "Disconnected".to_string(),
+            }
+        };
+
+        info!("Terminal connection to session {} closed: {}", session_id, reason);
+        {
+            let mut connections = self.connections.lock().unwrap();
+            // A later `connect` may already have replaced this connection
+            if connections.get(session_id).is_some_and(|c| Arc::ptr_eq(&c.connected, &connected)) {
+                connections.remove(session_id);
+            }
+        }
+        self.emit_state(session_id, TerminalState::Closed { reason });
+    }
+
+    /// One connection attempt; `Ok` means the session ended for good
+    async fn attach(
+        &self,
+        session_id: &str,
+        connected: &Mutex<bool>,
+        commands: &mut mpsc::UnboundedReceiver<TerminalCommand>,
+        backoff: &mut Backoff,
+    ) -> Result<String, AttachError> {
+        // Rebuilt on every attempt so reconnects follow the server to a new port
+        let url = websocket_url(&self.session_manager.server_url(), session_id).map_err(AttachError::Fatal)?;
+        let mut request = url.into_client_request()
+            .map_err(|e| AttachError::Fatal(format!("Invalid WebSocket URL: {}", e)))?;
+        request.headers_mut().insert(LOCAL_BYPASS_HEADER, HeaderValue::from_static("true"));
+
+        let (stream, _) = match connect_async(request).await {
+            Ok(connection) => connection,
+            Err(WsError::Http(response)) if response.status() == StatusCode::NOT_FOUND => {
+                return Ok("Session not found".to_string());
+            }
+            Err(e) => return Err(AttachError::Retry(format!("Failed to connect: {}", e))),
+        };
+
+        debug!("Terminal connected to session {}", session_id);
+        *connected.lock().unwrap() = true;
+        backoff.reset(None);
+        self.emit_state(session_id, TerminalState::Connected);
+
+        let (mut write, mut read) = stream.split();
+        loop {
+            tokio::select! {
+                command = commands.recv() => match command {
+                    Some(TerminalCommand::Input(data)) => {
+                        write.send(Message::Text(input_message(&data))).await
+                            .map_err(|e| AttachError::Retry(format!("Failed to send input: {}", e)))?;
+                    }
+                    None => return Ok("Disconnected".to_string()),
+                },
+                message = read.next() => {
+                    let data = match message {
+                        Some(Ok(Message::Text(text))) => text.into_bytes(),
+                        Some(Ok(Message::Binary(data))) => data,
+                        Some(Ok(Message::Close(frame))) => return Err(AttachError::Retry(match frame {
+                            Some(frame) if !frame.reason.is_empty() => format!("Server closed the connection: {}", frame.reason),
+                            _ => "Server closed the connection".to_string(),
+                        })),
+                        // Pongs are answered by tungstenite during reads
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => return Err(AttachError::Retry(format!("Connection failed: {}", e))),
+                        None => return Err(AttachError::Retry("Connection closed".to_string())),
+                    };
+                    let _ = self.event_sender.send(TerminalEvent::Output { session_id: session_id.to_string(), data });
+                }
+            }
+        }
     }
 }
 
-// Tauri commands for WebSocket management
+enum AttachError {
+    /// Worth reconnecting, e.g. a dropped connection or a restarting server
+    Retry(String),
+    Fatal(String),
+}
+
+#[derive(Clone, Serialize)]
+struct TerminalOutputPayload<'a> {
+    session_id: &'a str,
+    /// Base64, since PTY output is not guaranteed to be valid UTF-8
+    data: String,
+}
+
+#[derive(Clone, Serialize)]
+struct TerminalStatePayload<'a> {
+    session_id: &'a str,
+    #[serde(flatten)]
+    state: &'a TerminalState,
+}
+
+/// Stream terminal output and connection state to the frontend
+pub fn forward_terminal_events(app: AppHandle, mut receiver: broadcast::Receiver<TerminalEvent>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Dropped {} terminal events", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            let result = match &event {
+                TerminalEvent::Output { session_id, data } => app.emit(TERMINAL_OUTPUT_EVENT, TerminalOutputPayload {
+                    session_id,
+                    data: data_encoding::BASE64.encode(data),
+                }),
+                TerminalEvent::State { session_id, state } => {
+                    app.emit(TERMINAL_STATE_EVENT, TerminalStatePayload { session_id, state })
+                }
+            };
+            if let Err(e) = result {
+                error!("Failed to emit terminal event: {}", e);
+            }
+        }
+    });
+}
+
+// Tauri commands for terminal connections
 #[tauri::command]
-pub async fn connect_websocket(server_url: String) -> Result<(), String> {
-    let ws_manager = WebSocketManager::new(");
-    ws_manager.connect(server_url).await
+pub async fn connect_websocket(ws_manager: State<'_, WebSocketManager>, session_id: String) -> Result<(), String> {
+    ws_manager.connect(&session_id)
 }
 
+/// Disconnect one session's terminal, or all of them without a session id
 #[tauri::command]
-pub async fn disconnect_websocket() -> Result<(), String> {
-    // In a real implementation, this would disconnect the WebSocket
-    log::info!(""WebSocket disconnect requested");
+pub async fn disconnect_websocket(ws_manager: State<'_, WebSocketManager>, session_id: Option<String>) -> Result<(), String> {
+    match session_id {
+        Some(session_id) => {
+            ws_manager.disconnect(&session_id);
+        }
+        None => ws_manager.disconnect_all(),
+    }
     Ok(())
 }
 
+#[tauri::command]
+pub async fn send_terminal_input(ws_manager: State<'_, WebSocketManager>, session_id: String, data: String) -> Result<(), String> {
+    ws_manager.send_input(&session_id, data).await
+}
+
+#[tauri::command]
+pub async fn resize_terminal(ws_manager: State<'_, WebSocketManager>, session_id: String, cols: u16, rows: u16) -> Result<(), String> {
+    ws_manager.resize(&session_id, cols, rows).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[tokio::test]
-    async fn test_websocket_url_validation() {
-        let manager = WebSocketManager::new(");
+    #[test]
+    fn test_websocket_url() {
+        assert_eq!(websocket_url("http://127.0.0.1:4021", "a1").unwrap(), "ws://127.0.0.1:4021/ws?sessionId=a1");
+        assert_eq!(websocket_url("https://example.com/", "a b").unwrap(), "wss://example.com/ws?sessionId=a+b");
 
-        // Test localhost URL (ws:// allowed)
-        assert!(manager.connect("http://localhost:4020".to_string()).await.is_ok()");
-        assert!(manager.connect("http://127.0.0.1:4020".to_string()).await.is_ok()");
+        assert!(websocket_url("not-a-url", "a1").is_err());
+        assert!(websocket_url("javascript:alert(1)", "a1").is_err());
+    }
 
-        // Test external URLs (only wss:// allowed)
-        assert!(manager.connect("http://example.com".to_string()).await.is_err()");
-        assert!(manager.connect("ws://example.com".to_string()).await.is_err()");
-        assert!(manager.connect("https://example.com".to_string()).await.is_ok()");
+    #[test]
+    fn test_input_message_escapes_control_characters() {
+        let message: serde_json::Value = serde_json::from_str(&input_message("ls -la\r\u{3}\"")).unwrap();
 
-        // Test invalid URLs
-        assert!(manager.connect("not-a-url".to_string()).await.is_err()");
-        assert!(manager.connect("javascript:alert(1)".to_string()).await.is_err()");
+        assert_eq!(message["type"], "input");
+        assert_eq!(message["data"], "ls -la\r\u{3}\"");
     }
 
     #[test]
-    fn test_message_sanitization() {
-        let manager = WebSocketManager::new(");
-
-        // Test HTML sanitization
-        assert!(manager.sanitize_message("<script>alert(1)</script>").is_ok()");
-        
-        // Test command injection
-        assert!(manager.sanitize_message("; rm -rf /").is_err()");
-        
-        // Test valid messages
-        assert!(manager.sanitize_message("Hello, world!").is_ok()");
+    fn test_state_payload_shape() {
+        let state = TerminalState::Reconnecting { error: "refused".to_string(), delay_ms: 2000 };
+        let json = serde_json::to_value(TerminalStatePayload { session_id: "a1", state: &state }).unwrap();
+
+        assert_eq!(json, serde_json::json!({ "session_id": "a1", "state": "reconnecting", "error": "refused", "delay_ms": 2000 }));
     }
 }
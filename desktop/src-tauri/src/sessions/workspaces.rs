@@ -0,0 +1,406 @@
+// Session workspaces
+// Named sets of sessions plus the session window layout, saved from the current state and restored in one go
+
+use super::profiles::expand_home;
+use super::{CreateSessionRequest, Session, SessionManager};
+use crate::config::update_and_apply;
+use crate::ui::{SessionWindow, SessionWindowState, WindowGeometry};
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tauri::{AppHandle, Manager, State};
+use log::{info, warn};
+
+/// Version of exported workspace files
+const WORKSPACE_FILE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceSession {
+    pub title: String,
+    /// As the server reports it; the user's shell when unset
+    pub command: Option<String>,
+    pub working_directory: Option<String>,
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|value| !value.is_empty())
+}
+
+impl WorkspaceSession {
+    fn from_session(session: &Session) -> Self {
+        Self {
+            title: session.title.clone(),
+            command: non_empty(&session.command).map(str::to_string),
+            working_directory: non_empty(&session.working_directory).map(str::to_string),
+        }
+    }
+
+    /// A running session started the same way; titles are compared by `match_sessions`
+    fn matches(&self, session: &Session) -> bool {
+        session.status != "exited"
+            && non_empty(&session.command) == non_empty(&self.command)
+            && non_empty(&session.working_directory) == non_empty(&self.working_directory).map(expand_home).as_deref()
+    }
+
+    fn to_request(&self) -> CreateSessionRequest {
+        CreateSessionRequest {
+            title: Some(self.title.clone()).filter(|title| !title.is_empty()),
+            // Already joined the way the server runs it
+            command: non_empty(&self.command).map(str::to_string).into_iter().collect(),
+            cwd: non_empty(&self.working_directory).map(expand_home),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceWindow {
+    pub geometry: WindowGeometry,
+    pub visible: bool,
+    pub always_on_top: bool,
+    /// Index of the selected session in the workspace's sessions
+    pub selected: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Workspace {
+    pub name: String,
+    pub sessions: Vec<WorkspaceSession>,
+    /// Session window layout; the window is left alone on restore when unset
+    pub window: Option<WorkspaceWindow>,
+    /// RFC 3339
+    pub saved_at: Option<String>,
+}
+
+impl Workspace {
+    /// Snapshot of `sessions` and, when given, the session window's state and geometry
+    pub fn capture(name: &str, sessions: &[Session], window: Option<(SessionWindowState, WindowGeometry)>) -> Self {
+        let window = window.map(|(state, geometry)| WorkspaceWindow {
+            geometry,
+            visible: state.visible,
+            always_on_top: state.always_on_top,
+            selected: state.selected_session
+                .and_then(|selected| sessions.iter().position(|session| session.id == selected)),
+        });
+
+        Self {
+            name: name.to_string(),
+            sessions: sessions.iter().map(WorkspaceSession::from_session).collect(),
+            window,
+            saved_at: Some(chrono::Utc::now().to_rfc3339()),
+        }
+    }
+}
+
+pub fn validate_workspaces(workspaces: &[Workspace]) -> Result<(), String> {
+    let mut names = HashSet::new();
+
+    for workspace in workspaces {
+        if workspace.name.trim().is_empty() {
+            return Err("Workspace needs a name".to_string());
+        }
+        if !names.insert(workspace.name.to_lowercase()) {
+            return Err(format!("There is more than one workspace named {}", workspace.name));
+        }
+        let selected = workspace.window.as_ref().and_then(|window| window.selected);
+        if selected.is_some_and(|index| index >= workspace.sessions.len()) {
+            return Err(format!("Workspace {} selects a session it doesn't have", workspace.name));
+        }
+    }
+    Ok(())
+}
+
+/// The existing session to reuse for each workspace entry. Same titles are matched first, so
+/// two shells in one directory each find their own.
+fn match_sessions(entries: &[WorkspaceSession], live: &[Session]) -> Vec<Option<String>> {
+    let mut matched: Vec<Option<String>> = vec![None; entries.len()];
+    let mut claimed = HashSet::new();
+
+    for same_title in [true, false] {
+        for (entry, slot) in entries.iter().zip(matched.iter_mut()) {
+            if slot.is_some() {
+                continue;
+            }
+            let found = live.iter().find(|session| {
+                !claimed.contains(&session.id) && entry.matches(session) && (!same_title || session.title == entry.title)
+            });
+            if let Some(session) = found {
+                claimed.insert(session.id.clone());
+                *slot = Some(session.id.clone());
+            }
+        }
+    }
+    matched
+}
+
+fn collapse_home(path: &str) -> String {
+    let Some(home) = dirs::home_dir() else { return path.to_string() };
+    let home = home.to_string_lossy();
+    match path.strip_prefix(home.as_ref()) {
+        Some(rest) if !home.is_empty() && (rest.is_empty() || rest.starts_with('/')) => format!("~{}", rest),
+        _ => path.to_string(),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct WorkspaceFile {
+    version: u32,
+    workspace: Workspace,
+}
+
+/// File contents for sharing; directories in the home directory are written with `~` so they work for others
+pub fn export_workspace_json(workspace: &Workspace) -> Result<String, String> {
+    let mut workspace = workspace.clone();
+    for session in &mut workspace.sessions {
+        session.working_directory = session.working_directory.as_deref().map(collapse_home);
+    }
+
+    serde_json::to_string_pretty(&WorkspaceFile { version: WORKSPACE_FILE_VERSION, workspace })
+        .map_err(|e| format!("Failed to serialize workspace: {}", e))
+}
+
+pub fn import_workspace_json(text: &str) -> Result<Workspace, String> {
+    let file: WorkspaceFile = serde_json::from_str(text)
+        .map_err(|e| format!("Not a workspace file: {}", e))?;
+    if file.version > WORKSPACE_FILE_VERSION {
+        return Err(format!("Workspace file version {} is newer than this app supports", file.version));
+    }
+
+    validate_workspaces(std::slice::from_ref(&file.workspace))?;
+    Ok(file.workspace)
+}
+
+/// `name`, or `name (2)`, `name (3)`… when a workspace already has it
+fn unique_name(name: &str, workspaces: &[Workspace]) -> String {
+    let taken = |candidate: &str| workspaces.iter().any(|workspace| workspace.name.eq_ignore_ascii_case(candidate));
+    let mut candidate = name.to_string();
+    let mut n = 2;
+    while taken(&candidate) {
+        candidate = format!("{} ({})", name, n);
+        n += 1;
+    }
+    candidate
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RestoredSession {
+    pub session_id: String,
+    pub title: String,
+    /// An existing session was used instead of creating one
+    pub reused: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WorkspaceRestore {
+    pub sessions: Vec<RestoredSession>,
+    /// Sessions that could not be created, with the reason
+    pub failed: Vec<String>,
+}
+
+/// Size, position and selection of the session window; `session_ids` are the restored sessions in workspace order
+fn apply_window_layout(window: &SessionWindow, layout: &WorkspaceWindow, session_ids: &[Option<String>]) {
+    let mut state = window.get_state();
+    state.visible = layout.visible;
+    state.always_on_top = layout.always_on_top;
+    state.selected_session = layout.selected.and_then(|index| session_ids.get(index).cloned().flatten());
+    window.update_state(state);
+    window.set_geometry(layout.geometry);
+
+    if layout.visible {
+        if let Err(e) = window.show() {
+            warn!("Failed to show the session window for the workspace: {}", e);
+        }
+    }
+}
+
+fn find_workspace(app: &AppHandle, name: &str) -> Result<Workspace, String> {
+    app.state::<AppState>().config.lock().unwrap()
+        .workspaces.iter().find(|workspace| workspace.name == name).cloned()
+        .ok_or_else(|| format!("Workspace {} not found", name))
+}
+
+// Tauri commands for workspaces
+#[tauri::command]
+pub async fn list_workspaces(app: AppHandle) -> Result<Vec<Workspace>, String> {
+    Ok(app.state::<AppState>().config.lock().unwrap().workspaces.clone())
+}
+
+/// Save the running sessions, or just `session_ids` in that order, with the session window
+/// layout as workspace `name`, replacing a workspace of that name
+#[tauri::command]
+pub async fn save_workspace(
+    app: AppHandle,
+    session_manager: State<'_, SessionManager>,
+    name: String,
+    session_ids: Option<Vec<String>>
+) -> Result<Workspace, String> {
+    let mut sessions: Vec<Session> = session_manager.fetch_sessions().await?
+        .into_iter()
+        .filter(|session| session.status != "exited")
+        .collect();
+    match &session_ids {
+        Some(ids) => {
+            sessions.retain(|session| ids.contains(&session.id));
+            sessions.sort_by_key(|session| ids.iter().position(|id| *id == session.id));
+        }
+        None => sessions.sort_by(|a, b| a.created_at.cmp(&b.created_at)),
+    }
+    if sessions.is_empty() {
+        return Err("No running sessions to save".to_string());
+    }
+
+    let window = app.try_state::<SessionWindow>().map(|window| (window.get_state(), window.geometry()));
+    let workspace = Workspace::capture(&name, &sessions, window);
+    info!("Saving workspace {} with {} sessions", name, workspace.sessions.len());
+
+    update_and_apply(&app, |config| {
+        config.workspaces.retain(|existing| existing.name != workspace.name);
+        config.workspaces.push(workspace.clone());
+        Ok(workspace.clone())
+    })
+}
+
+#[tauri::command]
+pub async fn delete_workspace(app: AppHandle, name: String) -> Result<(), String> {
+    update_and_apply(&app, |config| {
+        let count = config.workspaces.len();
+        config.workspaces.retain(|workspace| workspace.name != name);
+        if config.workspaces.len() == count {
+            return Err(format!("Workspace {} not found", name));
+        }
+        Ok(())
+    })
+}
+
+/// Reuse running sessions that match the workspace, create the missing ones and lay out the window
+#[tauri::command]
+pub async fn restore_workspace(
+    app: AppHandle,
+    session_manager: State<'_, SessionManager>,
+    name: String
+) -> Result<WorkspaceRestore, String> {
+    let workspace = find_workspace(&app, &name)?;
+    let live = session_manager.fetch_sessions().await?;
+    let matched = match_sessions(&workspace.sessions, &live);
+
+    let mut restore = WorkspaceRestore::default();
+    let mut session_ids = Vec::with_capacity(matched.len());
+    for (entry, existing) in workspace.sessions.iter().zip(matched) {
+        let (session_id, reused) = match existing {
+            Some(session_id) => (session_id, true),
+            None => match session_manager.create_session(&entry.to_request()).await {
+                Ok(session) => (session.id, false),
+                Err(e) => {
+                    restore.failed.push(format!("{}: {}", entry.title, e));
+                    session_ids.push(None);
+                    continue;
+                }
+            },
+        };
+        restore.sessions.push(RestoredSession { session_id: session_id.clone(), title: entry.title.clone(), reused });
+        session_ids.push(Some(session_id));
+    }
+
+    if let (Some(layout), Some(window)) = (&workspace.window, app.try_state::<SessionWindow>()) {
+        apply_window_layout(&window, layout, &session_ids);
+    }
+
+    let reused = restore.sessions.iter().filter(|session| session.reused).count();
+    info!(
+        "Restored workspace {}: {} reused, {} created, {} failed",
+        name, reused, restore.sessions.len() - reused, restore.failed.len()
+    );
+    Ok(restore)
+}
+
+#[tauri::command]
+pub async fn export_workspace(app: AppHandle, name: String, path: String) -> Result<(), String> {
+    let json = export_workspace_json(&find_workspace(&app, &name)?)?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// Add a workspace from an exported file, renamed if one with its name already exists
+#[tauri::command]
+pub async fn import_workspace(app: AppHandle, path: String) -> Result<Workspace, String> {
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let mut workspace = import_workspace_json(&text)?;
+
+    update_and_apply(&app, |config| {
+        workspace.name = unique_name(&workspace.name, &config.workspaces);
+        config.workspaces.push(workspace.clone());
+        Ok(workspace)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, title: &str, command: &str, cwd: &str, status: &str) -> Session {
+        Session {
+            id: id.to_string(),
+            title: title.to_string(),
+            created_at: "2025-03-07T09:00:00Z".to_string(),
+            last_activity: "2025-03-07T09:00:00Z".to_string(),
+            status: status.to_string(),
+            pid: None,
+            command: Some(command.to_string()),
+            working_directory: Some(cwd.to_string()),
+            cols: 80,
+            rows: 24,
+            active: true,
+            clients: 0,
+        }
+    }
+
+    #[test]
+    fn test_reuses_matching_sessions_once() {
+        let live = vec![
+            session("a", "shell", "/bin/zsh", "/work/app", "running"),
+            session("b", "tests", "/bin/zsh", "/work/app", "running"),
+            session("c", "server", "npm run dev", "/work/app", "exited"),
+        ];
+        let workspace = Workspace::capture("morning", &[
+            session("1", "tests", "/bin/zsh", "/work/app", "running"),
+            session("2", "server", "npm run dev", "/work/app", "running"),
+            session("3", "logs", "/bin/zsh", "/work/app", "running"),
+            session("4", "shell 2", "/bin/zsh", "/work/app", "running"),
+        ], None);
+
+        let matched = match_sessions(&workspace.sessions, &live);
+        assert_eq!(matched, vec![Some("b".to_string()), None, Some("a".to_string()), None]);
+
+        let request = workspace.sessions[1].to_request();
+        assert_eq!((request.command, request.cwd.as_deref()), (vec!["npm run dev".to_string()], Some("/work/app")));
+    }
+
+    #[test]
+    fn test_captures_window_selection() {
+        let sessions = vec![session("a", "server", "make run", "/srv", "running"), session("b", "logs", "tail -f log", "/srv", "running")];
+        let state = SessionWindowState { selected_session: Some("b".to_string()), visible: true, ..Default::default() };
+        let geometry = WindowGeometry { x: Some(40.0), y: Some(60.0), width: 1200.0, height: 800.0 };
+
+        let workspace = Workspace::capture("ops", &sessions, Some((state, geometry)));
+        let window = workspace.window.clone().unwrap();
+        assert_eq!((window.selected, window.visible, window.geometry), (Some(1), true, geometry));
+        assert!(validate_workspaces(std::slice::from_ref(&workspace)).is_ok());
+        assert!(validate_workspaces(&[workspace.clone(), Workspace { name: "OPS".to_string(), ..workspace }]).is_err());
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let home = dirs::home_dir().unwrap().to_string_lossy().into_owned();
+        let workspace = Workspace::capture("share", &[session("a", "api", "cargo run", &format!("{}/code/api", home), "running")], None);
+
+        let json = export_workspace_json(&workspace).unwrap();
+        assert!(json.contains("\"~/code/api\""));
+        let imported = import_workspace_json(&json).unwrap();
+        assert_eq!(imported.sessions[0].to_request().cwd, Some(format!("{}/code/api", home)));
+
+        assert!(import_workspace_json(&json.replace("\"version\": 1", "\"version\": 9")).is_err());
+        assert_eq!(unique_name("share", &[workspace]), "share (2)");
+    }
+}
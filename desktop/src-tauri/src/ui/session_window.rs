@@ -1,7 +1,7 @@
 // Native Tauri Session Window Implementation
 // This provides a native interface for managing terminal sessions
 
-use tauri::{AppHandle, Manager, WebviewWindow, WebviewWindowBuilder, WebviewUrl};
+use tauri::{AppHandle, LogicalPosition, LogicalSize, Manager, WebviewWindow, WebviewWindowBuilder, WebviewUrl};
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 
@@ -14,6 +14,21 @@ pub struct SessionWindowState {
     pub always_on_top: bool,
     pub visible: bool,
     pub selected_session: Option<String>,
+    /// Position of the window; centered when unset
+    #[serde(default)]
+    pub x: Option<f64>,
+    #[serde(default)]
+    pub y: Option<f64>,
+}
+
+/// Position and size in logical pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowGeometry {
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub width: f64,
+    pub height: f64,
 }
 
 impl Default for SessionWindowState {
@@ -26,6 +41,8 @@ impl Default for SessionWindowState {
             always_on_top: false,
             visible: false,
             selected_session: None,
+            x: None,
+            y: None,
         }
     }
 }
@@ -53,7 +70,7 @@ impl SessionWindow {
         
         let state = self.state.lock().unwrap(");
 
-        let window = WebviewWindowBuilder::new(
+        let builder = WebviewWindowBuilder::new(
             app_handle,
             "sessions",
             WebviewUrl::External("http://localhost:4021/sessions".parse().unwrap())
@@ -65,10 +82,13 @@ impl SessionWindow {
         .always_on_top(state.always_on_top)
         .visible(state.visible)
         .decorations(true)
-        .user_agent("TunnelForge-Desktop/1.0 (Tauri)")
-        .center()
-        .build()
-        .map_err(|e| format!("Failed to create session window: {}", e))?;
+        .user_agent("TunnelForge-Desktop/1.0 (Tauri)");
+        let builder = match (state.x, state.y) {
+            (Some(x), Some(y)) => builder.position(x, y),
+            _ => builder.center(),
+        };
+        let window = builder.build()
+            .map_err(|e| format!("Failed to create session window: {}", e))?;
 
         *window_guard = Some(window");
         Ok(())
@@ -132,6 +152,44 @@ impl SessionWindow {
     pub fn get_state(&self) -> SessionWindowState {
         self.state.lock().unwrap().clone()
     }
+
+    /// Where the window is now, or where it will open while it doesn't exist
+    pub fn geometry(&self) -> WindowGeometry {
+        let state = self.get_state();
+        let mut geometry = WindowGeometry { x: state.x, y: state.y, width: state.width, height: state.height };
+
+        if let Some(window) = self.get_window() {
+            let scale = window.scale_factor().unwrap_or(1.0);
+            if let Ok(position) = window.outer_position() {
+                let position = position.to_logical::<f64>(scale);
+                (geometry.x, geometry.y) = (Some(position.x), Some(position.y));
+            }
+            if let Ok(size) = window.inner_size() {
+                let size = size.to_logical::<f64>(scale);
+                (geometry.width, geometry.height) = (size.width, size.height);
+            }
+        }
+        geometry
+    }
+
+    pub fn set_geometry(&self, geometry: WindowGeometry) {
+        {
+            let mut state = self.state.lock().unwrap();
+            (state.x, state.y) = (geometry.x, geometry.y);
+            (state.width, state.height) = (geometry.width, geometry.height);
+        }
+
+        if let Some(window) = self.get_window() {
+            if let Err(e) = window.set_size(LogicalSize::new(geometry.width, geometry.height)) {
+                log::warn!("Failed to resize session window: {}", e);
+            }
+            if let (Some(x), Some(y)) = (geometry.x, geometry.y) {
+                if let Err(e) = window.set_position(LogicalPosition::new(x, y)) {
+                    log::warn!("Failed to move session window: {}", e);
+                }
+            }
+        }
+    }
 }
 
 // Tauri commands for session window management
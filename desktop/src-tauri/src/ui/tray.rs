@@ -1,40 +1,161 @@
 // Native Tauri System Tray Implementation
 // This provides the system tray (menu bar) functionality for TunnelForge
 
-use tauri::{AppHandle, Manager, Emitter, tray::{TrayIconBuilder, TrayIcon, MouseButton, MouseButtonState}};
+use tauri::{AppHandle, Manager, tray::{TrayIconBuilder, TrayIcon, MouseButton, MouseButtonState}};
 use tauri::menu::{MenuBuilder, MenuItemBuilder, Menu, SubmenuBuilder};
 use std::sync::Arc;
 use std::sync::Mutex;
 
-#[derive(Clone)]
-pub struct TrayManager {
-    app_handle: AppHandle,
-    tray_icon: Option<Arc<Mutex<TrayIcon>>>,
+use crate::server::{
+    apply_external_server_policy, restart_managed_server, stop_managed_server, ExternalServerPolicy,
+    HealthState, ServerLifecycleEvent, ServerManager, ServerMode,
+};
+use crate::sessions::{spawn_profile_launch, PROFILE_MENU_PREFIX};
+
+/// What the tray shows, changed in place by status updates and server lifecycle events
+#[derive(Debug, Clone, PartialEq)]
+struct TrayState {
     server_running: bool,
+    server_mode: ServerMode,
     session_count: u32,
     access_mode: String,
 }
 
-impl TrayManager {
-    pub fn new(app_handle: AppHandle) -> Self {
-        Self { 
-            app_handle,
-            tray_icon: None,
+impl Default for TrayState {
+    fn default() -> Self {
+        Self {
             server_running: false,
+            server_mode: ServerMode::Stopped,
             session_count: 0,
             access_mode: "localhost".to_string(),
         }
     }
+}
+
+/// How the tray has to be redrawn after a state change
+#[derive(Debug, Clone, PartialEq)]
+enum TrayChange {
+    None,
+    Tooltip(String),
+    /// Rebuild the menu and status tooltip, then show `tooltip` over it when set
+    Menu { tooltip: Option<String> },
+}
+
+impl TrayState {
+    fn set_status(&mut self, server_running: bool, session_count: u32, access_mode: String) {
+        self.server_running = server_running;
+        if !server_running {
+            self.server_mode = ServerMode::Stopped;
+        } else if self.server_mode == ServerMode::Stopped {
+            self.server_mode = ServerMode::Managed;
+        }
+        self.session_count = session_count;
+        self.access_mode = access_mode;
+    }
+
+    fn set_running(&mut self, server_running: bool) {
+        self.set_status(server_running, self.session_count, self.access_mode.clone());
+    }
+
+    /// Launch profiles create sessions, so they need a server of any kind
+    fn profiles_enabled(&self) -> bool {
+        self.server_mode != ServerMode::Stopped
+    }
+
+    fn tooltip(&self) -> String {
+        match self.server_mode {
+            ServerMode::Attached => format!("TunnelForge - Attached to External Server ({} sessions)", self.session_count),
+            ServerMode::External => "TunnelForge - External Server Running".to_string(),
+            _ if self.server_running => format!("TunnelForge - Server Running ({} sessions)", self.session_count),
+            _ => "TunnelForge - Server Stopped".to_string(),
+        }
+    }
+
+    fn apply(&mut self, event: &ServerLifecycleEvent) -> TrayChange {
+        let menu = |tooltip: &str| TrayChange::Menu { tooltip: Some(tooltip.to_string()) };
+
+        match event {
+            ServerLifecycleEvent::Starting { .. } => TrayChange::Tooltip("TunnelForge - Server Starting...".to_string()),
+            ServerLifecycleEvent::Ready { .. } => {
+                self.server_mode = ServerMode::Managed;
+                self.set_running(true);
+                TrayChange::Menu { tooltip: None }
+            }
+            ServerLifecycleEvent::Crashed { restart_in_ms: Some(delay), .. } => {
+                self.set_running(false);
+                menu(&format!("TunnelForge - Server crashed, restarting in {}s", delay / 1000))
+            }
+            ServerLifecycleEvent::Crashed { restart_in_ms: None, .. }
+            | ServerLifecycleEvent::Stopped { .. } => {
+                self.set_running(false);
+                TrayChange::Menu { tooltip: None }
+            }
+            ServerLifecycleEvent::StartFailed { .. } => {
+                self.set_running(false);
+                menu("TunnelForge - Server failed to start")
+            }
+            ServerLifecycleEvent::GivingUp { .. } => {
+                self.set_running(false);
+                menu("TunnelForge - Server keeps crashing, automatic restart disabled")
+            }
+            ServerLifecycleEvent::PortReassigned { .. } => TrayChange::None,
+            // Adoption is followed by `Ready`, termination by the usual Starting/Ready events
+            ServerLifecycleEvent::OrphanFound { .. } => TrayChange::None,
+            ServerLifecycleEvent::RestartRequired { .. } => {
+                TrayChange::Tooltip("TunnelForge - Restart the server to apply new settings".to_string())
+            }
+            ServerLifecycleEvent::HealthChanged { to, .. } => match to {
+                HealthState::Healthy => {
+                    self.set_running(true);
+                    TrayChange::Menu { tooltip: None }
+                }
+                HealthState::Degraded => TrayChange::Tooltip("TunnelForge - Server responding slowly".to_string()),
+                HealthState::Unresponsive => TrayChange::Tooltip("TunnelForge - Server not responding".to_string()),
+            },
+            ServerLifecycleEvent::WatchdogRestart { .. } => {
+                TrayChange::Tooltip("TunnelForge - Restarting unresponsive server".to_string())
+            }
+            ServerLifecycleEvent::ExternalServerDetected { policy, .. } => {
+                self.server_mode = match policy {
+                    Some(ExternalServerPolicy::AttachReadOnly) => ServerMode::Attached,
+                    // A take over is followed by the usual Starting/Ready events
+                    Some(ExternalServerPolicy::TakeOver) => return TrayChange::None,
+                    _ => ServerMode::External,
+                };
+                self.set_running(true);
+                TrayChange::Menu { tooltip: None }
+            }
+        }
+    }
+}
+
+/// The system tray icon. One instance is managed by `setup_tray`; clones share its state.
+#[derive(Clone)]
+pub struct TrayManager {
+    app_handle: AppHandle,
+    tray_icon: Arc<Mutex<Option<TrayIcon>>>,
+    state: Arc<Mutex<TrayState>>,
+}
+
+impl TrayManager {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle,
+            tray_icon: Arc::new(Mutex::new(None)),
+            state: Arc::new(Mutex::new(TrayState::default())),
+        }
+    }
+
+    pub fn setup_tray(&self) -> Result<(), String> {
+        let app_handle = self.app_handle.clone();
+        let state = self.state.lock().unwrap().clone();
 
-    pub fn setup_tray(&mut self) -> Result<(), String> {
-        let app_handle = self.app_handle.clone(");
-        
         // Create the tray icon with a basic menu
         let tray = TrayIconBuilder::new()
             .tooltip("TunnelForge")
             .icon(tauri::image::Image::from_bytes(include_bytes!("../../assets/icon.png"))
                 .map_err(|e| format!("Failed to load icon: {}", e))?)
-            .menu(&Self::create_tray_menu(&app_handle, self.server_running, self.session_count, &self.access_mode)?)
+            .menu(&Self::create_tray_menu(&app_handle, &state)?)
             .on_menu_event(move |app, event| {
                 Self::handle_menu_event(app, event");
             })
@@ -51,11 +172,37 @@ impl TrayManager {
             .build(&self.app_handle)
             .map_err(|e| format!("Failed to create tray icon: {}", e))?;
         
-        self.tray_icon = Some(Arc::new(Mutex::new(tray))");
+        *self.tray_icon.lock().unwrap() = Some(tray);
+
+        // Telling our server apart from other listeners takes a request, so the status follows shortly
+        if let Some(server_manager) = app_handle.try_state::<ServerManager>() {
+            let server_manager = server_manager.inner().clone();
+            let tray_manager = self.clone();
+            tauri::async_runtime::spawn(async move {
+                let status = server_manager.status().await;
+                {
+                    let mut state = tray_manager.state.lock().unwrap();
+                    state.server_mode = status.mode;
+                    state.set_running(status.running);
+                }
+                if let Err(e) = tray_manager.refresh_menu() {
+                    log::error!("Failed to show server status in tray: {}", e);
+                }
+            });
+        }
         Ok(())
     }
 
-    fn create_tray_menu(app_handle: &AppHandle, server_running: bool, session_count: u32, access_mode: &str) -> Result<Menu<tauri::Wry>, String> {
+    fn tray(&self) -> Option<TrayIcon> {
+        self.tray_icon.lock().unwrap().clone()
+    }
+
+    fn create_tray_menu(app_handle: &AppHandle, state: &TrayState) -> Result<Menu<tauri::Wry>, String> {
+        let (server_mode, session_count, access_mode) = (state.server_mode, state.session_count, &state.access_mode);
+        // Only our own server can be stopped or restarted; external ones can be taken over instead
+        let managed = server_mode == ServerMode::Managed;
+        let external = matches!(server_mode, ServerMode::Attached | ServerMode::External);
+
         let show_item = MenuItemBuilder::new("Show TunnelForge")
             .id("show")
             .build(app_handle)
@@ -71,10 +218,11 @@ impl TrayManager {
             .build(app_handle)
             .map_err(|e| format!("Failed to create settings menu item: {}", e))?;
             
-        let server_status_item = MenuItemBuilder::new(if server_running { 
-            "Server: Running" 
-        } else { 
-            "Server: Stopped" 
+        let server_status_item = MenuItemBuilder::new(match server_mode {
+            ServerMode::Managed => "Server: Running",
+            ServerMode::Attached => "Server: Attached (read-only)",
+            ServerMode::External => "Server: External",
+            ServerMode::Stopped => "Server: Stopped",
         })
             .id("server_status")
             .enabled(false) // Make it non-clickable, just for display
@@ -95,22 +243,55 @@ impl TrayManager {
             
         let start_server_item = MenuItemBuilder::new("Start Server")
             .id("start_server")
-            .enabled(!server_running)
+            .enabled(server_mode == ServerMode::Stopped)
             .build(app_handle)
             .map_err(|e| format!("Failed to create start server menu item: {}", e))?;
             
         let stop_server_item = MenuItemBuilder::new("Stop Server")
             .id("stop_server")
-            .enabled(server_running)
+            .enabled(managed)
             .build(app_handle)
             .map_err(|e| format!("Failed to create stop server menu item: {}", e))?;
             
         let restart_server_item = MenuItemBuilder::new("Restart Server")
             .id("restart_server")
-            .enabled(server_running)
+            .enabled(managed)
             .build(app_handle)
             .map_err(|e| format!("Failed to create restart server menu item: {}", e))?;
+
+        let take_over_server_item = MenuItemBuilder::new("Take Over Server")
+            .id("take_over_server")
+            .enabled(external)
+            .build(app_handle)
+            .map_err(|e| format!("Failed to create take over server menu item: {}", e))?;
+
+        let attach_server_item = MenuItemBuilder::new("Attach Read-Only")
+            .id("attach_server")
+            .enabled(server_mode == ServerMode::External)
+            .build(app_handle)
+            .map_err(|e| format!("Failed to create attach server menu item: {}", e))?;
+
+        let leave_server_item = MenuItemBuilder::new("Leave Server Alone")
+            .id("leave_server")
+            .enabled(external)
+            .build(app_handle)
+            .map_err(|e| format!("Failed to create leave server menu item: {}", e))?;
             
+        let profiles = app_handle.try_state::<crate::AppState>()
+            .map(|state| state.config.lock().unwrap().launch_profiles.clone())
+            .unwrap_or_default();
+        let mut profiles_menu = SubmenuBuilder::new(app_handle, "Launch Profile").enabled(!profiles.is_empty());
+        for profile in &profiles {
+            let item = MenuItemBuilder::new(&profile.name)
+                .id(format!("{}{}", PROFILE_MENU_PREFIX, profile.id))
+                .enabled(state.profiles_enabled())
+                .build(app_handle)
+                .map_err(|e| format!("Failed to create launch profile menu item: {}", e))?;
+            profiles_menu = profiles_menu.item(&item);
+        }
+        let profiles_menu = profiles_menu.build()
+            .map_err(|e| format!("Failed to create launch profile menu: {}", e))?;
+
         let quit_item = MenuItemBuilder::new("Quit TunnelForge")
             .id("quit")
             .build(app_handle)
@@ -130,6 +311,11 @@ impl TrayManager {
             .item(&start_server_item)
             .item(&stop_server_item)
             .item(&restart_server_item)
+            .item(&take_over_server_item)
+            .item(&attach_server_item)
+            .item(&leave_server_item)
+            .separator()
+            .item(&profiles_menu)
             .separator()
             .item(&quit_item)
             .build()
@@ -146,8 +332,15 @@ impl TrayManager {
             "start_server" => Self::start_server(app),
             "stop_server" => Self::stop_server(app),
             "restart_server" => Self::restart_server(app),
+            "take_over_server" => Self::apply_external_policy(app, ExternalServerPolicy::TakeOver),
+            "attach_server" => Self::apply_external_policy(app, ExternalServerPolicy::AttachReadOnly),
+            "leave_server" => Self::apply_external_policy(app, ExternalServerPolicy::LeaveAlone),
             "quit" => Self::quit_application(app),
-            _ => {}
+            id => {
+                if let Some(profile_id) = id.strip_prefix(PROFILE_MENU_PREFIX) {
+                    spawn_profile_launch(app, profile_id);
+                }
+            }
         }
     }
 
@@ -179,62 +372,99 @@ impl TrayManager {
     }
 
     fn start_server(app: &AppHandle) {
-        // TODO: Implement server start functionality
-        let _ = app.emit("start_server", ()");
+        let server_manager = app.state::<ServerManager>().inner().clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = server_manager.launch().await {
+                log::error!("Failed to start server from tray: {}", e);
+            }
+        });
     }
 
     fn stop_server(app: &AppHandle) {
-        // TODO: Implement server stop functionality
-        let _ = app.emit("stop_server", ()");
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = stop_managed_server(&app).await {
+                log::error!("Failed to stop server from tray: {}", e);
+            }
+        });
     }
 
     fn restart_server(app: &AppHandle) {
-        // TODO: Implement server restart functionality
-        let _ = app.emit("restart_server", ()");
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = restart_managed_server(&app).await {
+                log::error!("Failed to restart server from tray: {}", e);
+            }
+        });
+    }
+
+    fn apply_external_policy(app: &AppHandle, policy: ExternalServerPolicy) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = apply_external_server_policy(&app, policy).await {
+                log::error!("Failed to apply external server policy {:?} from tray: {}", policy, e);
+            }
+        });
     }
 
     fn quit_application(app: &AppHandle) {
         app.exit(0");
     }
 
-    pub fn update_tray_menu(&mut self, server_running: bool, session_count: u32, access_mode: String) -> Result<(), String> {
-        self.server_running = server_running;
-        self.session_count = session_count;
-        self.access_mode = access_mode.clone(");
-        
-        if let Some(tray_icon) = &self.tray_icon {
-            let tray = tray_icon.lock().map_err(|_| "Failed to lock tray icon")?;
-            
-            let tooltip = if server_running {
-                format!("TunnelForge - Server Running ({} sessions)", session_count)
-            } else {
-                "TunnelForge - Server Stopped".to_string()
-            };
-            
-            tray.set_tooltip(Some(&tooltip))
+    pub fn update_tray_menu(&self, server_running: bool, session_count: u32, access_mode: String) -> Result<(), String> {
+        self.state.lock().unwrap().set_status(server_running, session_count, access_mode);
+        self.refresh_menu()
+    }
+
+    /// Rebuild the menu and status tooltip from the current state, e.g. after the launch profiles changed
+    pub fn refresh_menu(&self) -> Result<(), String> {
+        if let Some(tray) = self.tray() {
+            let state = self.state.lock().unwrap().clone();
+            tray.set_tooltip(Some(&state.tooltip()))
                 .map_err(|e| format!("Failed to update tooltip: {}", e))?;
-                
-            // Update the menu to show server status
-            let menu = Self::create_tray_menu(&self.app_handle, server_running, session_count, &access_mode)?;
+
+            let menu = Self::create_tray_menu(&self.app_handle, &state)?;
             tray.set_menu(Some(menu))
                 .map_err(|e| format!("Failed to update menu: {}", e))?;
         }
         Ok(())
     }
 
+    pub fn apply_lifecycle_event(&self, event: &ServerLifecycleEvent) -> Result<(), String> {
+        let change = self.state.lock().unwrap().apply(event);
+        match change {
+            TrayChange::None => Ok(()),
+            TrayChange::Tooltip(tooltip) => self.set_tray_tooltip(&tooltip),
+            TrayChange::Menu { tooltip } => {
+                self.refresh_menu()?;
+                match tooltip {
+                    Some(tooltip) => self.set_tray_tooltip(&tooltip),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+
     pub fn set_tray_tooltip(&self, tooltip: &str) -> Result<(), String> {
-        if let Some(tray_icon) = &self.tray_icon {
-            let tray = tray_icon.lock().map_err(|_| "Failed to lock tray icon")?;
+        if let Some(tray) = self.tray() {
             tray.set_tooltip(Some(tooltip))
                 .map_err(|e| format!("Failed to set tooltip: {}", e))?;
         }
         Ok(())
     }
 
+    /// Show `count` next to the tray icon, or nothing for 0
+    pub fn set_badge(&self, count: usize) -> Result<(), String> {
+        if let Some(tray) = self.tray() {
+            let title = (count > 0).then(|| count.to_string());
+            tray.set_title(title.as_deref())
+                .map_err(|e| format!("Failed to set tray badge: {}", e))?;
+        }
+        Ok(())
+    }
+
     pub fn set_tray_icon(&self, _icon_path: &str) -> Result<(), String> {
-        if let Some(tray_icon) = &self.tray_icon {
-            let tray = tray_icon.lock().map_err(|_| "Failed to lock tray icon")?;
-            
+        if let Some(tray) = self.tray() {
             // For now, we'll use a simple colored icon
             // In production, you'd load from the specified path
             let icon = tauri::image::Image::from_bytes(include_bytes!("../../assets/icon.png"))
@@ -250,9 +480,7 @@ impl TrayManager {
 // Tauri commands for tray management
 #[tauri::command]
 pub async fn update_tray_status(app_handle: AppHandle, server_running: bool, session_count: u32, access_mode: String) -> Result<(), String> {
-    let tray_manager = app_handle.state::<TrayManager>(");
-    let mut tray_manager = tray_manager.inner().clone(");
-    tray_manager.update_tray_menu(server_running, session_count, access_mode)
+    app_handle.state::<TrayManager>().update_tray_menu(server_running, session_count, access_mode)
 }
 
 #[tauri::command]
@@ -269,8 +497,41 @@ pub async fn set_tray_icon(app_handle: AppHandle, icon_path: String) -> Result<(
     tray_manager.set_tray_icon(&icon_path)
 }
 
-// Setup function to be called from lib.rs
+/// Create the tray icon and manage its `TrayManager`, during app setup
 pub fn setup_tray(app_handle: &AppHandle) -> Result<(), String> {
-    let mut tray_manager = TrayManager::new(app_handle.clone()");
-    tray_manager.setup_tray()
+    let tray_manager = TrayManager::new(app_handle.clone());
+    tray_manager.setup_tray()?;
+    app_handle.manage(tray_manager);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::ExitKind;
+
+    #[test]
+    fn test_lifecycle_events_update_state() {
+        let mut state = TrayState::default();
+
+        assert_eq!(state.apply(&ServerLifecycleEvent::Ready { pid: 1, port: 4021, startup_ms: 800 }), TrayChange::Menu { tooltip: None });
+        assert_eq!((state.server_running, state.server_mode), (true, ServerMode::Managed));
+        assert_eq!(state.tooltip(), "TunnelForge - Server Running (0 sessions)");
+
+        let change = state.apply(&ServerLifecycleEvent::Crashed { pid: 1, exit: ExitKind::Crashed { code: Some(1) }, restart_in_ms: Some(2000) });
+        assert_eq!(change, TrayChange::Menu { tooltip: Some("TunnelForge - Server crashed, restarting in 2s".to_string()) });
+        assert_eq!((state.server_running, state.server_mode), (false, ServerMode::Stopped));
+    }
+
+    #[test]
+    fn test_profiles_enabled_while_server_runs() {
+        let mut state = TrayState::default();
+        assert!(!state.profiles_enabled());
+
+        state.apply(&ServerLifecycleEvent::Ready { pid: 1, port: 4021, startup_ms: 800 });
+        assert!(state.profiles_enabled());
+
+        state.apply(&ServerLifecycleEvent::Stopped { pid: 1 });
+        assert!(!state.profiles_enabled());
+    }
 }
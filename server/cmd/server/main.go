@@ -2,6 +2,7 @@ package main
 
 import (
 	"context"
+	"fmt"
 	"log"
 	"net/http"
 	"os"
@@ -17,6 +18,15 @@ const (
 )
 
 func main() {
+	// Lets launchers check compatibility without starting a server
+	if len(os.Args) > 1 && os.Args[1] == "--version" {
+		fmt.Printf("tunnelforge-server %s\n", server.Version)
+		return
+	}
+
+	// Keep running when the desktop app reading our stdout/stderr dies, so its next launch can adopt us
+	signal.Ignore(syscall.SIGPIPE)
+
 	port := os.Getenv("PORT")
 	if port == "" {
 		port = DefaultPort
@@ -419,6 +419,24 @@ func (ba *BufferAggregator) unsubscribeClient(client *Client, sessionID string)
 
 // BroadcastBuffer sends buffer updates to subscribed clients
 func (ba *BufferAggregator) BroadcastBuffer(sessionID string, snapshot TerminalSnapshot) {
+	fullBuffer := ba.encodeFrame(sessionID, snapshot)
+
+	// Broadcast to subscribed clients
+	message := BufferMessage{
+		SessionID: sessionID,
+		Buffer:    fullBuffer,
+	}
+
+	select {
+	case ba.broadcast <- message:
+	default:
+		log.Printf("Broadcast channel full, dropping buffer update for session %s", sessionID)
+	}
+}
+
+// encodeFrame builds the binary message for one snapshot: magic byte, session ID length
+// (little-endian uint32), session ID, then the encoded snapshot
+func (ba *BufferAggregator) encodeFrame(sessionID string, snapshot TerminalSnapshot) []byte {
 	// Encode snapshot to binary format
 	buffer := ba.encodeSnapshot(snapshot)
 
@@ -443,17 +461,7 @@ func (ba *BufferAggregator) BroadcastBuffer(sessionID string, snapshot TerminalS
 	// Buffer data
 	copy(fullBuffer[offset:], buffer)
 
-	// Broadcast to subscribed clients
-	message := BufferMessage{
-		SessionID: sessionID,
-		Buffer:    fullBuffer,
-	}
-
-	select {
-	case ba.broadcast <- message:
-	default:
-		log.Printf("Broadcast channel full, dropping buffer update for session %s", sessionID)
-	}
+	return fullBuffer
 }
 
 // encodeSnapshot converts terminal snapshot to binary format
@@ -1,10 +1,14 @@
 package buffer
 
 import (
+	"bytes"
 	"encoding/binary"
 	"encoding/json"
+	"flag"
 	"net/http"
 	"net/http/httptest"
+	"os"
+	"path/filepath"
 	"strings"
 	"testing"
 	"time"
@@ -468,3 +472,60 @@ func TestBufferAggregator_ClientDisconnection(t *testing.T) {
 		t.Errorf("Expected 0 subscriptions after disconnect, got %d", cnt)
 	}
 }
+
+// The desktop app decodes these frames in its own tests (desktop/src-tauri/src/sessions/buffer.rs).
+// Regenerate them with `go test ./internal/buffer -run GoldenFrames -update` after changing the format.
+var updateGolden = flag.Bool("update", false, "rewrite the golden buffer frames in testdata")
+
+func TestBufferAggregator_GoldenFrames(t *testing.T) {
+	ba := NewBufferAggregator()
+
+	hello := make([]BufferCell, 0, 5)
+	for _, char := range []string{"H", "e", "l", "l", "o"} {
+		hello = append(hello, BufferCell{Char: char, FgColor: 7, BgColor: 0})
+	}
+
+	frames := []struct {
+		file      string
+		sessionID string
+		snapshot  TerminalSnapshot
+	}{
+		{
+			file:      "broadcast.frame",
+			sessionID: "test-session-broadcast",
+			snapshot:  TerminalSnapshot{Cols: 80, Rows: 24, CursorX: 5, CursorY: 2, Cells: [][]BufferCell{hello}},
+		},
+		{
+			// Nil cells marshal as null
+			file:      "no_cells.frame",
+			sessionID: "test-session-multi",
+			snapshot:  TerminalSnapshot{Cols: 80, Rows: 24, CursorX: 10, CursorY: 5},
+		},
+		{
+			// Go ints are wider than the desktop's screen model and may be negative
+			file:      "out_of_range.frame",
+			sessionID: "test-session-range",
+			snapshot:  TerminalSnapshot{Cols: 4, Rows: 2, ViewportY: 5000000000, CursorX: -1, CursorY: 7, Cells: [][]BufferCell{}},
+		},
+	}
+
+	for _, frame := range frames {
+		encoded := ba.encodeFrame(frame.sessionID, frame.snapshot)
+		path := filepath.Join("testdata", frame.file)
+
+		if *updateGolden {
+			if err := os.WriteFile(path, encoded, 0o644); err != nil {
+				t.Fatalf("Failed to write %s: %v", path, err)
+			}
+			continue
+		}
+
+		golden, err := os.ReadFile(path)
+		if err != nil {
+			t.Fatalf("Failed to read %s: %v", path, err)
+		}
+		if !bytes.Equal(encoded, golden) {
+			t.Errorf("%s is out of date:\n got: %q\nwant: %q", frame.file, encoded, golden)
+		}
+	}
+}
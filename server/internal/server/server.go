@@ -13,7 +13,9 @@ import (
 	"strings"
 	"sync"
 	"time"
+	"unicode/utf8"
 
+	"github.com/google/uuid"
 	"github.com/gorilla/mux"
 	"github.com/rs/cors"
 
@@ -40,6 +42,9 @@ import (
 	"github.com/ferg-cod3s/tunnelforge/go-server/pkg/types"
 )
 
+// Version is reported by /api/health and `tunnelforge-server --version`
+const Version = "1.0.0"
+
 type Config struct {
 	Port string
 }
@@ -64,6 +69,7 @@ type Server struct {
 	powerService       *power.Service
 	tunnelService      *tunnels.Service
 	startTime          time.Time
+	instanceID         string
 	analyticsService *analytics.Service
 	registryService *registry.Service
 	mu                 sync.RWMutex
@@ -201,6 +207,7 @@ func New(cfg *Config) (*Server, error) {
 		persistenceService: persistenceService,
 		eventBroadcaster:   eventBroadcaster,
 		startTime:          time.Now(),
+		instanceID:         instanceID(),
 	}
 
 	// Set up event broadcasting hooks
@@ -322,13 +329,7 @@ func (s *Server) setupRoutes() {
 	}
 	sessionRouter.HandleFunc("/sessions", s.handleListSessions).Methods("GET")
 	sessionRouter.HandleFunc("/sessions", s.handleCreateSession).Methods("POST")
-	sessionRouter.HandleFunc("/sessions/{id}", s.handleGetSession).Methods("GET")
-	sessionRouter.HandleFunc("/sessions/{id}", s.handleDeleteSession).Methods("DELETE")
-	sessionRouter.HandleFunc("/sessions/{id}/resize", s.handleResizeSession).Methods("POST")
-	sessionRouter.HandleFunc("/sessions/{id}/reset-size", s.handleResetSessionSize).Methods("POST")
-	sessionRouter.HandleFunc("/sessions/{id}/input", s.handleSessionInput).Methods("POST")
-	sessionRouter.HandleFunc("/sessions/{id}/stream", s.handleSessionStream).Methods("GET")
-	sessionRouter.HandleFunc("/cleanup-exited", s.handleCleanupExited).Methods("POST")
+	// Fixed paths first: mux matches in registration order, so "/sessions/{id}" would take "groups" or "tags" as an id
 	sessionRouter.HandleFunc("/sessions/bulk", s.handleBulkCreateSessions).Methods("POST")
 	sessionRouter.HandleFunc("/sessions/bulk/delete", s.handleBulkDeleteSessions).Methods("POST")
 	sessionRouter.HandleFunc("/sessions/bulk/resize", s.handleBulkResizeSessions).Methods("POST")
@@ -338,13 +339,20 @@ func (s *Server) setupRoutes() {
 	sessionRouter.HandleFunc("/sessions/groups/{groupId}", s.handleDeleteSessionGroup).Methods("DELETE")
 	sessionRouter.HandleFunc("/sessions/groups/{groupId}/sessions", s.handleAddSessionToGroup).Methods("POST")
 	sessionRouter.HandleFunc("/sessions/groups/{groupId}/sessions/{sessionId}", s.handleRemoveSessionFromGroup).Methods("DELETE")
-	sessionRouter.HandleFunc("/sessions/{id}/groups", s.handleGetSessionGroups).Methods("GET")
-	sessionRouter.HandleFunc("/sessions/{id}/hierarchy", s.handleGetSessionHierarchy).Methods("GET")
-	sessionRouter.HandleFunc("/sessions/{id}/dependencies", s.handleGetSessionDependencies).Methods("GET")
 	sessionRouter.HandleFunc("/sessions/tags", s.handleListSessionTags).Methods("GET")
 	sessionRouter.HandleFunc("/sessions/tags", s.handleCreateSessionTag).Methods("POST")
 	sessionRouter.HandleFunc("/sessions/tags/{tagName}", s.handleDeleteSessionTag).Methods("DELETE")
 	sessionRouter.HandleFunc("/sessions/by-tag/{tagName}", s.handleGetSessionsByTag).Methods("GET")
+	sessionRouter.HandleFunc("/sessions/{id}", s.handleGetSession).Methods("GET")
+	sessionRouter.HandleFunc("/sessions/{id}", s.handleDeleteSession).Methods("DELETE")
+	sessionRouter.HandleFunc("/sessions/{id}/resize", s.handleResizeSession).Methods("POST")
+	sessionRouter.HandleFunc("/sessions/{id}/reset-size", s.handleResetSessionSize).Methods("POST")
+	sessionRouter.HandleFunc("/sessions/{id}/input", s.handleSessionInput).Methods("POST")
+	sessionRouter.HandleFunc("/sessions/{id}/stream", s.handleSessionStream).Methods("GET")
+	sessionRouter.HandleFunc("/cleanup-exited", s.handleCleanupExited).Methods("POST")
+	sessionRouter.HandleFunc("/sessions/{id}/groups", s.handleGetSessionGroups).Methods("GET")
+	sessionRouter.HandleFunc("/sessions/{id}/hierarchy", s.handleGetSessionHierarchy).Methods("GET")
+	sessionRouter.HandleFunc("/sessions/{id}/dependencies", s.handleGetSessionDependencies).Methods("GET")
 	sessionRouter.HandleFunc("/registry/instances", s.handleListRegistryInstances).Methods("GET")
 	sessionRouter.HandleFunc("/registry/instances", s.handleRegisterRegistryInstance).Methods("POST")
 	sessionRouter.HandleFunc("/registry/instances/{instanceId}", s.handleGetRegistryInstance).Methods("GET")
@@ -591,10 +599,35 @@ shutdownEvent = shutdownEvent.WithMessage("TunnelForge Go server shutting down")
 	return s.httpServer.Shutdown(ctx)
 }
 
+// healthResponse identifies this process so clients can tell a TunnelForge
+// server apart from any other service listening on the same port
+type healthResponse struct {
+	Status     string `json:"status"`
+	Sessions   int    `json:"sessions"`
+	Uptime     string `json:"uptime"`
+	Name       string `json:"name"`
+	Version    string `json:"version"`
+	InstanceID string `json:"instanceId"`
+}
+
 func (s *Server) handleHealth(w http.ResponseWriter, r *http.Request) {
 	w.Header().Set("Content-Type", "application/json")
-	fmt.Fprintf(w, `{"status":"ok","sessions":%d,"uptime":"%s"}`,
-		s.sessionManager.Count(), time.Since(s.startTime).String())
+	json.NewEncoder(w).Encode(healthResponse{
+		Status:     "ok",
+		Sessions:   s.sessionManager.Count(),
+		Uptime:     time.Since(s.startTime).String(),
+		Name:       "tunnelforge",
+		Version:    Version,
+		InstanceID: s.instanceID,
+	})
+}
+
+// instanceID returns the id supplied by the launching process, or a fresh one
+func instanceID() string {
+	if id := os.Getenv("TUNNELFORGE_INSTANCE_ID"); id != "" {
+		return id
+	}
+	return uuid.New().String()
 }
 
 // API handlers for Phase 4 implementation
@@ -920,6 +953,24 @@ func (s *Server) handleSessionStream(w http.ResponseWriter, r *http.Request) {
 	}
 	defer s.sessionManager.RemoveSSEStream(sessionID, outputChan)
 
+	// Stream asciicast v2 events: a header with the terminal size, then [elapsed, "o", text] per chunk.
+	// JSON keeps newlines in the output from breaking the SSE framing.
+	start := time.Now()
+	header, _ := json.Marshal(map[string]interface{}{
+		"version":   2,
+		"width":     session.Cols,
+		"height":    session.Rows,
+		"timestamp": start.Unix(),
+	})
+	if _, err := fmt.Fprintf(w, "data: %s\n\n", header); err != nil {
+		log.Printf("Failed to write SSE header: %v", err)
+		return
+	}
+	flusher.Flush()
+
+	// Bytes of a UTF-8 sequence split across chunks wait for the rest of it
+	var pending []byte
+
 	// Stream output until client disconnects
 	for {
 		select {
@@ -928,8 +979,21 @@ func (s *Server) handleSessionStream(w http.ResponseWriter, r *http.Request) {
 				return
 			}
 
+			pending = append(pending, data...)
+			text, rest := splitIncompleteUTF8(pending)
+			pending = append([]byte(nil), rest...)
+			if len(text) == 0 {
+				continue
+			}
+
+			event, err := json.Marshal([]interface{}{time.Since(start).Seconds(), "o", string(text)})
+			if err != nil {
+				log.Printf("Failed to encode SSE data: %v", err)
+				continue
+			}
+
 			// Send SSE event
-			if _, err := fmt.Fprintf(w, "data: %snn", string(data)); err != nil {
+			if _, err := fmt.Fprintf(w, "data: %s\n\n", event); err != nil {
 				log.Printf("Failed to write SSE data: %v", err)
 				return
 			}
@@ -941,6 +1005,19 @@ func (s *Server) handleSessionStream(w http.ResponseWriter, r *http.Request) {
 	}
 }
 
+// splitIncompleteUTF8 splits off a trailing, not yet complete UTF-8 sequence
+func splitIncompleteUTF8(data []byte) ([]byte, []byte) {
+	for i := len(data) - 1; i >= 0 && i >= len(data)-utf8.UTFMax; i-- {
+		if utf8.RuneStart(data[i]) {
+			if !utf8.FullRune(data[i:]) {
+				return data[:i], data[i:]
+			}
+			break
+		}
+	}
+	return data, nil
+}
+
 // handleLogin handles user authentication and returns JWT token
 func (s *Server) handleLogin(w http.ResponseWriter, r *http.Request) {
 	var loginReq struct {
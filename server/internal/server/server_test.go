@@ -29,6 +29,7 @@ func TestServer_HealthCheck(t *testing.T) {
 	assert.Contains(t, w.Header().Get("Content-Type"), "application/json")
 	assert.Contains(t, w.Body.String(), "\"status\":\"ok\"")
 	assert.Contains(t, w.Body.String(), "\"sessions\":0")
+	assert.Contains(t, w.Body.String(), "\"name\":\"tunnelforge\"")
 }
 
 func TestServer_ListSessions_Empty(t *testing.T) {
@@ -558,3 +559,57 @@ func TestServer_LocalBypassAuth(t *testing.T) {
 		assert.Equal(t, http.StatusUnauthorized, w.Code)
 	})
 }
+
+// The desktop app tags profile sessions through these routes, in this order
+func TestServer_SessionGroupTagging(t *testing.T) {
+	server, err := New(&Config{Port: "0"})
+	require.NoError(t, err)
+	server.setupRoutes()
+
+	session, err := server.sessionManager.Create(&types.SessionCreateRequest{Command: "sleep 10", Title: "Tagged"})
+	require.NoError(t, err)
+
+	serve := func(method, path string, body interface{}) *httptest.ResponseRecorder {
+		var reader *bytes.Reader
+		if body != nil {
+			data, err := json.Marshal(body)
+			require.NoError(t, err)
+			reader = bytes.NewReader(data)
+		} else {
+			reader = bytes.NewReader(nil)
+		}
+		req := httptest.NewRequest(method, path, reader)
+		req.Header.Set("Content-Type", "application/json")
+		w := httptest.NewRecorder()
+		server.httpServer.Handler.ServeHTTP(w, req)
+		return w
+	}
+
+	// Not taken as a session id by /sessions/{id}
+	w := serve("GET", "/api/sessions/groups", nil)
+	require.Equal(t, http.StatusOK, w.Code, w.Body.String())
+	assert.JSONEq(t, "[]", w.Body.String())
+	assert.Equal(t, http.StatusOK, serve("GET", "/api/sessions/tags", nil).Code)
+
+	w = serve("POST", "/api/sessions/groups", map[string]interface{}{"name": "profile:api", "tags": []string{"profile:api"}})
+	require.Equal(t, http.StatusOK, w.Code, w.Body.String())
+	var group types.SessionGroup
+	require.NoError(t, json.Unmarshal(w.Body.Bytes(), &group))
+
+	w = serve("POST", "/api/sessions/groups/"+group.ID+"/sessions", map[string]string{"sessionId": session.ID})
+	require.Equal(t, http.StatusOK, w.Code, w.Body.String())
+
+	w = serve("GET", "/api/sessions/"+session.ID+"/groups", nil)
+	require.Equal(t, http.StatusOK, w.Code)
+	var groups []types.SessionGroup
+	require.NoError(t, json.Unmarshal(w.Body.Bytes(), &groups))
+	require.Len(t, groups, 1)
+	assert.Equal(t, []string{"profile:api"}, groups[0].Tags)
+
+	w = serve("GET", "/api/sessions/by-tag/profile:api", nil)
+	require.Equal(t, http.StatusOK, w.Code)
+	assert.Contains(t, w.Body.String(), session.ID)
+
+	// Sessions themselves are still found by id
+	assert.Equal(t, http.StatusOK, serve("GET", "/api/sessions/"+session.ID, nil).Code)
+}
@@ -43,6 +43,8 @@ type OptimizedPTYSession struct {
 	CreatedAt time.Time
 	UpdatedAt time.Time
 	Active    bool
+	// Extra KEY=value pairs for the process, applied when the PTY starts
+	Env       []string
 
 	// Lazy-initialized PTY resources
 	pty       *os.File
@@ -139,6 +141,7 @@ func (m *OptimizedPTYManager) CreateSession(req *types.SessionCreateRequest) (*t
 		CreatedAt: now,
 		UpdatedAt: now,
 		Active:    true,
+		Env:       extraEnv(req.Env),
 		clients:   make(map[string]*types.WSClient),
 		// PTY resources will be initialized lazily
 		initialized: 0,
@@ -242,13 +245,14 @@ func (s *OptimizedPTYSession) initializePTY(envTemplate []string) error {
 	cmd.Dir = s.Cwd
 
 	// Use pre-computed environment template + PTY-specific vars
-	cmd.Env = make([]string, len(envTemplate), len(envTemplate)+3)
+	cmd.Env = make([]string, len(envTemplate), len(envTemplate)+3+len(s.Env))
 	copy(cmd.Env, envTemplate)
 	cmd.Env = append(cmd.Env,
 		"TERM=xterm-256color",
 		fmt.Sprintf("COLUMNS=%d", s.Cols),
 		fmt.Sprintf("LINES=%d", s.Rows),
 	)
+	cmd.Env = append(cmd.Env, s.Env...)
 
 	// Start PTY (this is the expensive operation)
 	ptyFile, err := pty.StartWithSize(cmd, &pty.Winsize{
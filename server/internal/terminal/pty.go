@@ -7,6 +7,7 @@ import (
 	"log"
 	"os"
 	"os/exec"
+	"sort"
 	"strings"
 	"sync"
 	"time"
@@ -48,6 +49,19 @@ type PTYSession struct {
 	optimizedSession *OptimizedPTYSession
 }
 
+// extraEnv turns the env of a create request into sorted KEY=value pairs, skipping invalid names
+func extraEnv(env map[string]string) []string {
+	pairs := make([]string, 0, len(env))
+	for key, value := range env {
+		if key == "" || strings.ContainsAny(key, "=\x00") {
+			continue
+		}
+		pairs = append(pairs, key+"="+value)
+	}
+	sort.Strings(pairs)
+	return pairs
+}
+
 // NewPTYManager creates a new PTY manager
 func NewPTYManager() *PTYManager {
 	return &PTYManager{
@@ -131,6 +145,7 @@ func (m *PTYManager) CreateSession(req *types.SessionCreateRequest) (*types.Sess
 		fmt.Sprintf("COLUMNS=%d", cols),
 		fmt.Sprintf("LINES=%d", rows),
 	)
+	cmd.Env = append(cmd.Env, extraEnv(req.Env)...)
 
 	// Start PTY
 	ptyFile, err := pty.StartWithSize(cmd, &pty.Winsize{
@@ -167,7 +167,8 @@ func (h *Handler) handleClientOutput(client *Client, ptySession *terminal.PTYSes
 	// Set up ping/pong handler
 	client.Conn.SetPongHandler(func(string) error {
 		client.LastPing = time.Now()
-		return nil
+		// Each pong proves the client is alive, so push the read deadline out again
+		return client.Conn.SetReadDeadline(time.Now().Add(60 * time.Second))
 	})
 
 	// Set read deadline for ping/pong